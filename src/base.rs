@@ -1,13 +1,18 @@
 use std::f32::consts::PI;
-use std::time::Instant;
 use rand::prelude::*;
-use bevy::{ prelude::*, window::PrimaryWindow, sprite::collide_aabb::collide};
+use bevy::{ prelude::*, sprite::collide_aabb::collide};
 
 use crate::player::*;
 use crate::turret::*;
 use crate::part::*;
 use crate::enemy::*;
-use super::{GameOver, AppState, SimulationState};
+use crate::wave::WaveTimer;
+use crate::rng::GameRng;
+use crate::game_time::SimulationClock;
+use crate::controls::{ActionState, Action};
+use crate::health::*;
+use crate::spatial::EnemyGrid;
+use super::{GameOver, AppState, SimulationState, SoundEffect, Playfield};
 
 
 
@@ -27,7 +32,8 @@ impl Plugin for BasePlugin{
                 base_leveling,
                 base_levels,
                 cheat_leveling,
-                enemy_hit_base
+                enemy_hit_base.in_set(DamageSet),
+                base_destroyed.in_set(DeathSet)
     
             )
             .in_set(OnUpdate(AppState::Game))
@@ -46,14 +52,13 @@ impl Plugin for BasePlugin{
 
 #[derive(Component, Debug, PartialEq)]
 pub struct Base{
-    pub health : i64,
     pub level : i64,
     pub parts : Vec<Part>,
     pub parts_required: Vec<PartTier>,
     pub max_parts : usize,
     pub leveled_up : bool,
     pub size : Vec2,
-    instant : Instant
+    pub spawn_time : f32
 }
 
 #[derive(Component)]
@@ -62,7 +67,7 @@ pub struct BaseBuilding;
 
 
 impl Base{
-    fn push_part_required(&mut self, part_tier : PartTier) -> Result<(), ()>{
+    pub fn push_part_required(&mut self, part_tier : PartTier) -> Result<(), ()>{
         if self.parts_required.len() >= self.max_parts{
             return Err(());
         }
@@ -91,12 +96,11 @@ pub fn despawn_base_buildings(mut commands: Commands, base_building_query : Quer
 }
 
 
-pub fn spawn_base(mut commands: Commands, asset_server : Res<AssetServer>, window_query : Query<&Window, With<PrimaryWindow>>){
-    let window = window_query.get_single().unwrap();
+pub fn spawn_base(mut commands: Commands, asset_server : Res<AssetServer>, playfield : Res<Playfield>, simulation_clock : Res<SimulationClock>){
     commands.spawn((
         SpriteBundle{
             transform : Transform{
-                translation : Vec3::new(window.width()/2.0, window.height()/2.0, 0.0),
+                translation : Vec3::new(playfield.width/2.0, playfield.height/2.0, 0.0),
                 scale :Vec3::splat(0.3),
                 ..default()
             },
@@ -104,14 +108,15 @@ pub fn spawn_base(mut commands: Commands, asset_server : Res<AssetServer>, windo
             ..default()
 
         },
-        Base{health : 500, level : 1, parts : Vec::new(), parts_required : Vec::new(), max_parts : 3, leveled_up : false, size : Vec2::new(50.0, 50.0), instant : Instant::now()}
+        Base{level : 1, parts : Vec::new(), parts_required : Vec::new(), max_parts : 3, leveled_up : false, size : Vec2::new(50.0, 50.0), spawn_time : simulation_clock.now()},
+        Health::new(500)
     ));
 }
 
 
 
 
-pub fn base_leveling(mut base_query: Query<&mut Base, (With<Base>, Without<Player>)>){
+pub fn base_leveling(mut base_query: Query<&mut Base, (With<Base>, Without<Player>)>, mut game_rng : ResMut<GameRng>){
     for mut base in base_query.iter_mut(){
         if base.parts_required.is_empty() || base.parts.is_empty(){
             match base.level{
@@ -125,7 +130,7 @@ pub fn base_leveling(mut base_query: Query<&mut Base, (With<Base>, Without<Playe
                 },
                 2 => {
                     for _ in 0..base.max_parts{
-                        let part_chance = random::<f32>();
+                        let part_chance = game_rng.base.gen::<f32>();
                         if part_chance < 0.3{
                             match base.push_part_required(PartTier::Red){
                                 Ok(_) => {},
@@ -142,7 +147,7 @@ pub fn base_leveling(mut base_query: Query<&mut Base, (With<Base>, Without<Playe
                 },
                 3 => {
                     for _ in 0..base.max_parts{
-                        let part_chance = random::<f32>();
+                        let part_chance = game_rng.base.gen::<f32>();
                         if part_chance < 0.4{
                             match base.push_part_required(PartTier::Red){
                                 Ok(_) => {},
@@ -159,7 +164,7 @@ pub fn base_leveling(mut base_query: Query<&mut Base, (With<Base>, Without<Playe
                 },
                 4 => {
                     for _ in 0..base.max_parts{
-                        let part_chance = random::<f32>();
+                        let part_chance = game_rng.base.gen::<f32>();
                         if part_chance < 0.5{
                             match base.push_part_required(PartTier::Red){
                                 Ok(_) => {},
@@ -176,7 +181,7 @@ pub fn base_leveling(mut base_query: Query<&mut Base, (With<Base>, Without<Playe
                 },
                 5 => {
                     for _ in 0..base.max_parts{
-                        let part_chance = random::<f32>();
+                        let part_chance = game_rng.base.gen::<f32>();
                         if part_chance < 0.3{
                             match base.push_part_required(PartTier::Green){
                                 Ok(_) => {},
@@ -199,7 +204,7 @@ pub fn base_leveling(mut base_query: Query<&mut Base, (With<Base>, Without<Playe
                 },
                 6 => {
                     for _ in 0..base.max_parts{
-                        let part_chance = random::<f32>();
+                        let part_chance = game_rng.base.gen::<f32>();
                         if part_chance < 0.5{
                             match base.push_part_required(PartTier::Green){
                                 Ok(_) => {},
@@ -216,7 +221,7 @@ pub fn base_leveling(mut base_query: Query<&mut Base, (With<Base>, Without<Playe
                 },
                 _ => {
                     for _ in 0..base.max_parts{
-                        let part_chance = random::<f32>();
+                        let part_chance = game_rng.base.gen::<f32>();
                         if part_chance < 1.0/3.0{
                             match base.push_part_required(PartTier::Green){
                                 Ok(_) => {},
@@ -264,9 +269,11 @@ pub fn base_leveling(mut base_query: Query<&mut Base, (With<Base>, Without<Playe
 
 }
 
-pub fn base_levels(mut commands: Commands, mut base_query: Query<(&Transform, &mut Base), (With<Base>, Without<Player>)>, mut player_query: Query<&mut Player, (With<Player>, Without<Base>)>, mut blaster_timer: ResMut<BlasterCooldownTimer>, asset_server : Res<AssetServer>, audio : Res<Audio>){
-    if let Ok(mut player) = player_query.get_single_mut(){
-        for (base_transform, mut base) in base_query.iter_mut(){
+type PlayerHealthQuery<'w, 's> = Query<'w, 's, (&'static mut Player, &'static mut Health), (With<Player>, Without<Base>)>;
+
+pub fn base_levels(mut commands: Commands, mut base_query: Query<(&Transform, &mut Base, &mut Health), (With<Base>, Without<Player>)>, mut player_query: PlayerHealthQuery, asset_server : Res<AssetServer>, mut sound_effects : EventWriter<SoundEffect>){
+    if let Ok((mut player, mut player_health)) = player_query.get_single_mut(){
+        for (base_transform, mut base, mut base_health) in base_query.iter_mut(){
             match base.level {
                 2 => {
                     if base.leveled_up{
@@ -282,12 +289,10 @@ pub fn base_levels(mut commands: Commands, mut base_query: Query<(&Transform, &m
                             },
                             BaseBuilding{}
                         ));
-                        audio.play(asset_server.load("Audio/computerNoise_000.ogg"));
-                        blaster_timer.set_cooldown(0.4);
+                        sound_effects.send(SoundEffect("Audio/computerNoise_000.ogg"));
                         player.speed = 300.0;
-                        player.max_health = 150;
-                        player.health = 150;
-                        base.health = 1000;
+                        player_health.reset(150);
+                        base_health.reset(1000);
                         base.size = Vec2::new(55.0, 55.0);
                         base.leveled_up = false;
                     }
@@ -307,12 +312,10 @@ pub fn base_levels(mut commands: Commands, mut base_query: Query<(&Transform, &m
                             },
                             BaseBuilding{}
                         ));
-                        audio.play(asset_server.load("Audio/computerNoise_001.ogg"));
-                        blaster_timer.set_cooldown(0.3);
+                        sound_effects.send(SoundEffect("Audio/computerNoise_001.ogg"));
                         player.speed = 350.0;
-                        player.max_health = 200;
-                        player.health = 200;
-                        base.health = 1500;
+                        player_health.reset(200);
+                        base_health.reset(1500);
                         base.size = Vec2::new(60.0, 60.0);
                         base.leveled_up = false;
                     }
@@ -331,12 +334,10 @@ pub fn base_levels(mut commands: Commands, mut base_query: Query<(&Transform, &m
                             },
                             BaseBuilding{}
                         ));
-                        audio.play(asset_server.load("Audio/computerNoise_001.ogg"));
-                        blaster_timer.set_cooldown(0.2);
+                        sound_effects.send(SoundEffect("Audio/computerNoise_001.ogg"));
                         player.speed = 400.0;
-                        player.max_health = 250;
-                        player.health = 250;
-                        base.health = 2000;
+                        player_health.reset(250);
+                        base_health.reset(2000);
                         base.size = Vec2::new(65.0, 65.0);
                         base.leveled_up = false;
                     }
@@ -353,14 +354,12 @@ pub fn base_levels(mut commands: Commands, mut base_query: Query<(&Transform, &m
                             texture : asset_server.load("Sprites/spaceBuilding_020.png"),
                             ..default()
                         },
-                        Turret{target : None}
+                        Turret::new(TurretStats::default(), TargetingMode::Nearest)
                     ));
-                    audio.play(asset_server.load("Audio/computerNoise_002.ogg"));
-                    blaster_timer.set_cooldown(0.1);
+                    sound_effects.send(SoundEffect("Audio/computerNoise_002.ogg"));
                     player.speed = 450.0;
-                    player.max_health = 300;
-                    player.health = 300;
-                    base.health = 2500;
+                    player_health.reset(300);
+                    base_health.reset(2500);
                     base.size = Vec2::new(70.0, 70.0);
                     base.leveled_up = false;
                     }
@@ -376,7 +375,8 @@ pub fn base_levels(mut commands: Commands, mut base_query: Query<(&Transform, &m
                             texture : asset_server.load("Sprites/spaceBuilding_020.png"),
                             ..default()
                         },
-                        Turret{target : None}));
+                        //guards the base, reaches further but fires slower and harder
+                        Turret::new(TurretStats{range : 250.0, fire_rate : 1.5, damage : 60}, TargetingMode::ClosestToBase)));
                         commands.spawn((
                             SpriteBundle{
                                 transform : Transform{
@@ -390,15 +390,31 @@ pub fn base_levels(mut commands: Commands, mut base_query: Query<(&Transform, &m
                             },
                             BaseBuilding{}
                         ));
-                        audio.play(asset_server.load("Audio/computerNoise_003.ogg"));
+                        sound_effects.send(SoundEffect("Audio/computerNoise_003.ogg"));
                         player.speed = 500.0;
-                        player.max_health = 300;
-                        player.health = 300;
-                        base.health = 3000;
+                        player_health.reset(300);
+                        base_health.reset(3000);
                         base.leveled_up = false;
                     }
 
                 },
+                7 if base.leveled_up => {
+                    //a missile launcher that goes after the toughest enemy around
+                    commands.spawn((SpriteBundle{
+                        transform : Transform{
+                            translation : Vec3::new(base_transform.translation.x, base_transform.translation.y, 0.0) + Vec3::new(-30.0, 10.0, 0.0),
+                            scale : Vec3::splat(0.3),
+                            rotation : Quat::from_rotation_z(PI/2.0),
+                        },
+                        texture : asset_server.load("Sprites/spaceBuilding_020.png"),
+                        ..default()
+                    },
+                    Turret{weapon : TurretWeapon::Missiles, ..Turret::new(MISSILE_LAUNCHER_STATS, TargetingMode::Strongest)}));
+                    sound_effects.send(SoundEffect("Audio/computerNoise_003.ogg"));
+                    player_health.reset(350);
+                    base_health.reset(3500);
+                    base.leveled_up = false;
+                },
                 _ => {
                 }
             }
@@ -406,9 +422,9 @@ pub fn base_levels(mut commands: Commands, mut base_query: Query<(&Transform, &m
     }
 }
 
-pub fn cheat_leveling(mut base_query: Query<&mut Base, With<Base>>, keyboard_input: Res<Input<KeyCode>>){
+pub fn cheat_leveling(mut base_query: Query<&mut Base, With<Base>>, action_state: Res<ActionState>){
     for mut base in base_query.iter_mut(){
-        if keyboard_input.just_pressed(KeyCode::Tab){
+        if action_state.just_pressed(Action::CheatLevel){
             base.level += 1;
             base.leveled_up = true;
             println!("{}", base.level);
@@ -417,19 +433,32 @@ pub fn cheat_leveling(mut base_query: Query<&mut Base, With<Base>>, keyboard_inp
 }
 
 
-pub fn enemy_hit_base(mut commands: Commands, enemy_query: Query<(Entity, &Transform, &Enemy), With<Enemy>>, mut base_query: Query<(&Transform, &mut Base)>, asset_server: Res<AssetServer>, mut game_over_event_writer: EventWriter<GameOver>, audio : Res<Audio>) {
-    for (base_transform, mut base) in base_query.iter_mut(){
-        for (enemy_entity, enemy_transform, enemy) in enemy_query.iter() {
-            if collide(base_transform.translation, base.size, enemy_transform.translation, enemy.size).is_some(){
-                audio.play(asset_server.load("Audio/footstep_snow_002.ogg"));
-                commands.entity(enemy_entity).despawn();
-                base.health -= enemy.health;
-                if base.health <= 0 {
-                    audio.play(asset_server.load("Audio/explosionCrunch_002.ogg"));
-                    game_over_event_writer.send(GameOver{time_alive : base.instant.elapsed().as_secs(), base_level : base.level})
-                }
+pub fn enemy_hit_base(enemy_grid : Res<EnemyGrid>, enemy_query: Query<(&Enemy, &Health)>, base_query: Query<(Entity, &Transform, &Base)>, mut damage_event_writer : EventWriter<DamageEvent>, mut sound_effects : EventWriter<SoundEffect>) {
+    for (base_entity, base_transform, base) in base_query.iter(){
+        for enemy in enemy_grid.candidates(base_transform.translation, base.size / 2.0) {
+            let Ok((enemy_component, enemy_health)) = enemy_query.get(enemy.entity) else {
+                continue;
+            };
+            //bosses aren't used up, boss_contact hits with them
+            if matches!(enemy_component.variant, EnemyType::Boss(_)){
+                continue;
+            }
+            if collide(base_transform.translation, base.size, enemy.translation, enemy.size).is_some(){
+                sound_effects.send(SoundEffect("Audio/footstep_snow_002.ogg"));
+                damage_event_writer.send(DamageEvent{source : Some(enemy.entity), target : base_entity, amount : enemy_health.current, damage_type : DamageType::Collision});
+                damage_event_writer.send(DamageEvent{source : None, target : enemy.entity, amount : enemy_health.current, damage_type : DamageType::Collision});
             }
         }
     }
         
 }
+
+//only the hit that destroys the base ends the game
+pub fn base_destroyed(mut death_event_reader : EventReader<DeathEvent>, base_query: Query<&Base>, mut game_over_event_writer: EventWriter<GameOver>, mut sound_effects : EventWriter<SoundEffect>, wave_timer : Res<WaveTimer>, simulation_clock : Res<SimulationClock>){
+    for death_event in death_event_reader.iter(){
+        if let Ok(base) = base_query.get(death_event.entity){
+            sound_effects.send(SoundEffect("Audio/explosionCrunch_002.ogg"));
+            game_over_event_writer.send(GameOver{time_alive : simulation_clock.elapsed_since(base.spawn_time).as_secs(), base_level : base.level, wave : wave_timer.wave})
+        }
+    }
+}
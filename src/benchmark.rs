@@ -0,0 +1,94 @@
+use std::{f32::consts::PI, time::{Duration, Instant}};
+use bevy::{prelude::*, app::AppExit};
+
+use crate::{enemy::*, base::Base, turret::*, health::Health, game_time::SimulationClock};
+use super::AppState;
+
+//Fills the headless run with enemies and reports how long the updates take, e.g.
+//cargo run --release -- --headless --benchmark 1000
+pub struct BenchmarkPlugin{
+    pub enemies : usize
+}
+
+impl Plugin for BenchmarkPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        //Resources
+        .insert_resource(BenchmarkStats{enemies : self.enemies, ..default()})
+
+        //on the first update of the run, once the base is there
+        .add_system(spawn_benchmark_scenario.in_set(OnUpdate(AppState::Game)))
+        .add_system(measure_benchmark_frame.in_base_set(CoreSet::First).run_if(in_state(AppState::Game)));
+    }
+}
+
+
+
+pub const BENCHMARK_FRAMES : usize = 600;
+//the enemies start on a ring around the base and everything is too tough to die, so the count stays the same
+pub const BENCHMARK_MIN_DISTANCE : f32 = 350.0;
+pub const BENCHMARK_MAX_DISTANCE : f32 = 900.0;
+pub const BENCHMARK_TURRETS : usize = 32;
+pub const BENCHMARK_TURRET_DISTANCE : f32 = 120.0;
+pub const BENCHMARK_HEALTH : i64 = 1_000_000;
+
+
+#[derive(Resource, Debug, Default)]
+pub struct BenchmarkStats{
+    pub enemies : usize,
+    pub spawned : bool,
+    pub frames : usize,
+    pub total : Duration,
+    pub slowest : Duration,
+    pub last_frame : Option<Instant>
+}
+
+
+//Systems
+
+//mostly pawns with a bishop every ten enemies so deacons are in play too, spread evenly around the base
+pub fn spawn_benchmark_scenario(mut commands: Commands, asset_server : Res<AssetServer>, mut stats : ResMut<BenchmarkStats>, mut base_query : Query<(&Transform, &mut Health), With<Base>>, simulation_clock : Res<SimulationClock>){
+    if stats.spawned{
+        return;
+    }
+    let Ok((base_transform, mut base_health)) = base_query.get_single_mut() else {
+        return;
+    };
+    stats.spawned = true;
+    //enough for every enemy to ram it
+    base_health.reset(BENCHMARK_HEALTH * (stats.enemies as i64 + 1));
+    let center = base_transform.translation;
+    for i in 0..stats.enemies{
+        let kind = if i % 10 == 0 {EnemyKind::Bishop} else {EnemyKind::Pawn};
+        //golden angle, so no two enemies share a spot
+        let angle = i as f32 * PI * (3.0 - 5.0_f32.sqrt());
+        let distance = BENCHMARK_MIN_DISTANCE + (BENCHMARK_MAX_DISTANCE - BENCHMARK_MIN_DISTANCE) * (i as f32 / stats.enemies as f32);
+        let enemy_entity = spawn_enemy(&mut commands, &asset_server, kind, center + Vec3::new(angle.cos(), angle.sin(), 0.0) * distance, simulation_clock.now());
+        commands.entity(enemy_entity).insert(Health::new(BENCHMARK_HEALTH));
+    }
+    for i in 0..BENCHMARK_TURRETS{
+        let angle = i as f32 * 2.0 * PI / BENCHMARK_TURRETS as f32;
+        commands.spawn((
+            Transform::from_translation(center + Vec3::new(angle.cos(), angle.sin(), 0.0) * BENCHMARK_TURRET_DISTANCE),
+            Turret::new(TurretStats::default(), TargetingMode::Nearest)
+        ));
+    }
+}
+
+//wall clock time between the starts of two updates, there is nothing to render so that is all simulation
+pub fn measure_benchmark_frame(mut stats : ResMut<BenchmarkStats>, enemy_query : Query<With<Enemy>>, mut app_exit_event_writer : EventWriter<AppExit>){
+    let now = Instant::now();
+    if let Some(last_frame) = stats.last_frame{
+        let frame_time = now - last_frame;
+        stats.frames += 1;
+        stats.total += frame_time;
+        stats.slowest = stats.slowest.max(frame_time);
+    }
+    stats.last_frame = Some(now);
+
+    if stats.frames == BENCHMARK_FRAMES{
+        let average = stats.total.as_secs_f64() * 1000.0 / stats.frames as f64;
+        println!("benchmark | {} enemies | {} frames | average {:.2}ms | slowest {:.2}ms", enemy_query.iter().count(), stats.frames, average, stats.slowest.as_secs_f64() * 1000.0);
+        app_exit_event_writer.send(AppExit);
+    }
+}
@@ -0,0 +1,252 @@
+use std::f32::consts::PI;
+use serde::{Deserialize, Serialize};
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+
+use crate::base::Base;
+use crate::enemy::*;
+use crate::health::*;
+use crate::player::Player;
+use crate::part::{spawn_part, PartTier};
+use crate::spatial::SpatialIndexSet;
+use crate::game_time::SimulationClock;
+use crate::status::StatusEffects;
+use super::{AppState, SimulationState, SoundEffect};
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin{
+    fn build(&self, app: &mut App) {
+        app
+
+        //While in game appstate
+        .add_systems(
+            (
+                boss_behaviour.before(SpatialIndexSet),
+                boss_contact.in_set(DamageSet),
+                boss_loot.in_set(DeathSet)
+            )
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        );
+    }
+}
+
+
+
+//every BOSS_WAVE_INTERVAL-th wave brings a boss along
+pub const BOSS_WAVE_INTERVAL : usize = 10;
+pub const BOSS_HEALTH : i64 = 2500;
+//added for every boss after the first
+pub const BOSS_HEALTH_PER_ENCOUNTER : i64 = 1000;
+pub const BOSS_SPEED : f32 = 10.0;
+pub const BOSS_SIZE : f32 = 60.0;
+pub const BOSS_SCALE : f32 = 1.2;
+pub const BOSS_COLOR : Color = Color::rgb(1.0, 0.45, 0.45);
+//time between summons, shields and charges
+pub const BOSS_ABILITY_COOLDOWN : f32 = 5.0;
+//how far from the boss the summoned pawns appear, in ENEMY_POSITIONS units
+pub const BOSS_SUMMON_SPREAD : f32 = 8.0;
+pub const BOSS_SHIELD_DEACONS : usize = 8;
+pub const BOSS_SHIELD_RADIUS : f32 = 55.0;
+pub const BOSS_CHARGE_SPEED : f32 = 120.0;
+pub const BOSS_CHARGE_TIME : f32 = 1.5;
+//what the boss does to the player or the base it runs into, at most once every BOSS_CONTACT_COOLDOWN
+pub const BOSS_CONTACT_DAMAGE : i64 = 50;
+pub const BOSS_CONTACT_COOLDOWN : f32 = 1.0;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BossPhase{
+    //walks at the base and summons pawns
+    Summoning,
+    //stands still behind rings of deacons
+    Shielding,
+    //charges at the base
+    Charging
+}
+
+impl BossPhase{
+    //the boss moves on at two thirds and one third of its health
+    pub fn from_health(health : &Health) -> BossPhase{
+        let fraction = health.current as f32 / health.max.max(1) as f32;
+        if fraction > 2.0 / 3.0 {
+            BossPhase::Summoning
+        }
+        else if fraction > 1.0 / 3.0 {
+            BossPhase::Shielding
+        }
+        else{
+            BossPhase::Charging
+        }
+    }
+
+    pub fn number(&self) -> usize{
+        match self {
+            BossPhase::Summoning => 1,
+            BossPhase::Shielding => 2,
+            BossPhase::Charging => 3
+        }
+    }
+
+    pub fn label(&self) -> &'static str{
+        match self {
+            BossPhase::Summoning => "Summoning",
+            BossPhase::Shielding => "Shielding",
+            BossPhase::Charging => "Charging"
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BossState{
+    pub phase : BossPhase,
+    //when the boss last summoned, shielded or charged on the SimulationClock
+    pub last_ability : f32,
+    //the direction of the current charge and when it ends
+    pub charge : Option<(Vec3, f32)>,
+    //when the boss last hit the player or the base it ran into
+    pub last_contact : f32
+}
+
+pub fn is_boss_wave(wave : usize) -> bool{
+    wave > 0 && wave.is_multiple_of(BOSS_WAVE_INTERVAL)
+}
+
+pub fn boss_health(wave : usize) -> i64{
+    BOSS_HEALTH + BOSS_HEALTH_PER_ENCOUNTER * (wave / BOSS_WAVE_INTERVAL).saturating_sub(1) as i64
+}
+
+pub fn spawn_boss(commands: &mut Commands, asset_server : &Res<AssetServer>, translation : Vec3, spawn_time : f32, health : i64) -> Entity{
+    commands.spawn((
+        SpriteBundle{
+            transform : Transform{
+                translation,
+                scale : Vec3::splat(BOSS_SCALE),
+                ..default()
+            },
+            sprite : Sprite{color : BOSS_COLOR, ..default()},
+            texture : asset_server.load("Sprites/propogator.png"),
+            ..default()
+        },
+        Enemy{variant : EnemyType::Boss(BossState{phase : BossPhase::Summoning, last_ability : spawn_time, charge : None, last_contact : spawn_time - BOSS_CONTACT_COOLDOWN}), speed : BOSS_SPEED, size : Vec2::splat(BOSS_SIZE)},
+        Health::new(health)
+    )).id()
+}
+
+pub fn boss_behaviour(mut commands: Commands, mut boss_query : Query<(&mut Transform, &mut Enemy, &Health, Option<&StatusEffects>), Without<Base>>, base_query : Query<&Transform, (With<Base>, Without<Enemy>)>, time : Res<Time>, asset_server : Res<AssetServer>, mut sound_effects : EventWriter<SoundEffect>, simulation_clock : Res<SimulationClock>){
+    let Ok(base_transform) = base_query.get_single() else {
+        return;
+    };
+    let now = simulation_clock.now();
+    for (mut transform, mut enemy, health, status_effects) in boss_query.iter_mut(){
+        let EnemyType::Boss(mut state) = enemy.variant else {
+            continue;
+        };
+        //stunned, it neither moves nor uses its abilities
+        if status_effects.is_some_and(|status_effects| status_effects.stunned()){
+            continue;
+        }
+        let speed_multiplier = status_effects.map_or(1.0, |status_effects| status_effects.speed_multiplier());
+        //a new phase starts with its ability straight away
+        let phase = BossPhase::from_health(health);
+        if phase != state.phase{
+            state.phase = phase;
+            state.last_ability = now - BOSS_ABILITY_COOLDOWN;
+            state.charge = None;
+            sound_effects.send(SoundEffect("Audio/lowFrequency_explosion_000.ogg"));
+        }
+        let ability_ready = now - state.last_ability >= BOSS_ABILITY_COOLDOWN;
+        if ability_ready{
+            state.last_ability = now;
+        }
+        let to_base = Vec3::new(base_transform.translation.x - transform.translation.x, base_transform.translation.y - transform.translation.y, 0.0).normalize_or_zero();
+        match phase {
+            BossPhase::Summoning => {
+                if ability_ready{
+                    for position in ENEMY_POSITIONS.iter(){
+                        let offset = Vec3::new(position.0, position.1, 0.0) * BOSS_SUMMON_SPREAD;
+                        spawn_enemy(&mut commands, &asset_server, EnemyKind::Pawn, transform.translation + offset, now);
+                    }
+                }
+                transform.translation += to_base * enemy.speed * speed_multiplier * time.delta_seconds();
+            },
+            BossPhase::Shielding => {
+                if ability_ready{
+                    for i in 0..BOSS_SHIELD_DEACONS{
+                        let angle = i as f32 / BOSS_SHIELD_DEACONS as f32 * 2.0 * PI;
+                        commands.spawn((
+                            SpriteBundle{
+                                transform : Transform{
+                                    translation : transform.translation + Vec3::new(angle.cos(), angle.sin(), 0.0) * BOSS_SHIELD_RADIUS,
+                                    scale : Vec3::splat(0.2),
+                                    ..default()
+                                },
+                                texture : asset_server.load("Sprites/deacon.png"),
+                                ..default()
+                            },
+                            Deacon{speed : 0.0, size : Vec2::new(10.0, 10.0), direction : Vec3::ZERO, spawn_time : now}
+                        ));
+                    }
+                }
+            },
+            BossPhase::Charging => {
+                if ability_ready{
+                    state.charge = Some((to_base, now + BOSS_CHARGE_TIME));
+                    sound_effects.send(SoundEffect("Audio/footstep_snow_002.ogg"));
+                }
+                match state.charge {
+                    Some((direction, until)) if now < until => {
+                        transform.translation += direction * BOSS_CHARGE_SPEED * speed_multiplier * time.delta_seconds();
+                    },
+                    _ => {
+                        state.charge = None;
+                    }
+                }
+            }
+        }
+        transform.rotation *= Quat::from_rotation_z(-PI/360.0);
+        enemy.variant = EnemyType::Boss(state);
+    }
+}
+
+//the boss isn't used up like the enemies that ram, it keeps going and hits again once its contact cooldown is over
+pub fn boss_contact(mut boss_query : Query<(Entity, &Transform, &mut Enemy)>, player_query : Query<(Entity, &Transform, &Player), Without<Enemy>>, base_query : Query<(Entity, &Transform, &Base), Without<Enemy>>, mut damage_event_writer : EventWriter<DamageEvent>, mut sound_effects : EventWriter<SoundEffect>, simulation_clock : Res<SimulationClock>){
+    let now = simulation_clock.now();
+    for (boss_entity, boss_transform, mut enemy) in boss_query.iter_mut(){
+        let EnemyType::Boss(mut state) = enemy.variant else {
+            continue;
+        };
+        if now - state.last_contact < BOSS_CONTACT_COOLDOWN{
+            continue;
+        }
+        let players = player_query.iter().map(|(entity, transform, player)| (entity, transform, player.size));
+        let bases = base_query.iter().map(|(entity, transform, base)| (entity, transform, base.size));
+        let mut hit = false;
+        for (target, target_transform, target_size) in players.chain(bases){
+            if collide(boss_transform.translation, enemy.size, target_transform.translation, target_size).is_some(){
+                damage_event_writer.send(DamageEvent{source : Some(boss_entity), target, amount : BOSS_CONTACT_DAMAGE, damage_type : DamageType::Collision});
+                hit = true;
+            }
+        }
+        if hit{
+            state.last_contact = now;
+            enemy.variant = EnemyType::Boss(state);
+            sound_effects.send(SoundEffect("Audio/explosionCrunch_002.ogg"));
+        }
+    }
+}
+
+//a boss shot down always drops a Green part, the loot tables don't roll for it
+pub fn boss_loot(mut commands: Commands, mut death_event_reader : EventReader<DeathEvent>, enemy_query : Query<(&Transform, &Enemy)>, asset_server : Res<AssetServer>, simulation_clock : Res<SimulationClock>){
+    for death_event in death_event_reader.iter(){
+        if death_event.damage_type == DamageType::Collision{
+            continue;
+        }
+        let Ok((enemy_transform, enemy)) = enemy_query.get(death_event.entity) else {
+            continue;
+        };
+        if matches!(enemy.variant, EnemyType::Boss(_)){
+            spawn_part(&mut commands, &asset_server, PartTier::Green, enemy_transform.translation, simulation_clock.now());
+        }
+    }
+}
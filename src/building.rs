@@ -0,0 +1,447 @@
+use serde::{Deserialize, Serialize};
+use bevy::{prelude::*, sprite::collide_aabb::collide, render::render_resource::{Extent3d, TextureDimension, TextureFormat}};
+
+use crate::base::*;
+use crate::enemy::Enemy;
+use crate::part::PartTier;
+use crate::player::*;
+use crate::turret::*;
+use crate::health::*;
+use crate::controls::{ActionState, Action};
+use crate::spatial::EnemyGrid;
+use super::{AppState, SimulationState, SoundEffect, Playfield};
+
+//Parts picked up are banked in the PartInventory and spent in build mode to place turrets and walls.
+//The placement logic runs headless too, BuildPreviewPlugin only draws the preview and range circle.
+pub struct BuildingPlugin;
+
+impl Plugin for BuildingPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        //Resources
+        .init_resource::<PartInventory>()
+        .init_resource::<BuildMode>()
+
+        //every run starts with nothing banked
+        .add_system(reset_build_mode.in_schedule(OnEnter(AppState::Game)))
+
+        //Systems
+        .add_systems(
+            (
+                toggle_build_mode,
+                update_build_placement.after(toggle_build_mode),
+                place_building.after(update_build_placement),
+                sell_building.after(update_build_placement)
+            )
+            //after the input is known, before the player would shoot with the same click
+            .after(PlayerInputSet)
+            .before(player_shoot)
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
+        .add_systems(
+            (
+                enemy_hit_building.in_set(DamageSet),
+                building_destroyed.in_set(DeathSet)
+            )
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
+
+        .add_system(despawn_buildings.in_schedule(OnExit(AppState::Game)));
+    }
+}
+
+//the placement preview and range circle, needs rendering so it is only added with a window
+pub struct BuildPreviewPlugin;
+
+impl Plugin for BuildPreviewPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        //Startup
+        .add_startup_system(create_range_ring)
+
+        .add_system(update_build_preview.after(update_build_placement).run_if(in_state(AppState::Game)))
+        .add_system(despawn_build_preview.in_schedule(OnExit(AppState::Game)));
+    }
+}
+
+
+
+//share of the cost given back when a building is sold, rounded up
+pub const SELL_REFUND : f32 = 0.5;
+//room taken by the turrets and buildings the base spawns itself
+pub const BASE_BUILDING_FOOTPRINT : f32 = 30.0;
+pub const RANGE_RING_SIZE : u32 = 256;
+pub const RANGE_RING_WIDTH : f32 = 3.0;
+pub const PREVIEW_VALID_COLOR : Color = Color::rgba(0.4, 1.0, 0.4, 0.6);
+pub const PREVIEW_BLOCKED_COLOR : Color = Color::rgba(1.0, 0.3, 0.3, 0.6);
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuildingKind{
+    GunTurret,
+    MissileTurret,
+    //soaks up enemies that run into it
+    Wall
+}
+
+impl BuildingKind{
+    pub const ALL : [BuildingKind; 3] = [BuildingKind::GunTurret, BuildingKind::MissileTurret, BuildingKind::Wall];
+
+    pub fn label(&self) -> &'static str{
+        match self {
+            BuildingKind::GunTurret => "Gun turret",
+            BuildingKind::MissileTurret => "Missile launcher",
+            BuildingKind::Wall => "Wall"
+        }
+    }
+
+    pub fn cost(&self) -> PartInventory{
+        match self {
+            BuildingKind::GunTurret => PartInventory{blue : 3, red : 0, green : 0},
+            BuildingKind::MissileTurret => PartInventory{blue : 0, red : 2, green : 1},
+            BuildingKind::Wall => PartInventory{blue : 2, red : 0, green : 0}
+        }
+    }
+
+    pub fn size(&self) -> Vec2{
+        match self {
+            BuildingKind::GunTurret => Vec2::new(30.0, 30.0),
+            BuildingKind::MissileTurret => Vec2::new(30.0, 30.0),
+            BuildingKind::Wall => Vec2::new(40.0, 40.0)
+        }
+    }
+
+    pub fn texture(&self) -> &'static str{
+        match self {
+            BuildingKind::GunTurret => "Sprites/spaceBuilding_020.png",
+            BuildingKind::MissileTurret => "Sprites/spaceBuilding_020.png",
+            BuildingKind::Wall => "Sprites/spaceBuilding_002.png"
+        }
+    }
+
+    pub fn turret(&self) -> Option<Turret>{
+        match self {
+            BuildingKind::GunTurret => Some(Turret::new(TurretStats::default(), TargetingMode::Nearest)),
+            BuildingKind::MissileTurret => Some(Turret{weapon : TurretWeapon::Missiles, ..Turret::new(MISSILE_LAUNCHER_STATS, TargetingMode::Strongest)}),
+            BuildingKind::Wall => None
+        }
+    }
+
+    //walls can be destroyed, turrets can't
+    pub fn health(&self) -> Option<i64>{
+        match self {
+            BuildingKind::Wall => Some(400),
+            _ => None
+        }
+    }
+
+    pub fn next(&self) -> BuildingKind{
+        let index = BuildingKind::ALL.iter().position(|kind| kind == self).unwrap_or(0);
+        BuildingKind::ALL[(index + 1) % BuildingKind::ALL.len()]
+    }
+}
+
+//parts by tier, used both for what the player has banked and for what things cost
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartInventory{
+    pub blue : u32,
+    pub red : u32,
+    pub green : u32
+}
+
+impl PartInventory{
+    pub fn get_mut(&mut self, part_tier : PartTier) -> &mut u32{
+        match part_tier {
+            PartTier::Blue => &mut self.blue,
+            PartTier::Red => &mut self.red,
+            PartTier::Green => &mut self.green
+        }
+    }
+
+    pub fn add(&mut self, part_tier : PartTier){
+        *self.get_mut(part_tier) += 1;
+    }
+
+    pub fn can_afford(&self, cost : &PartInventory) -> bool{
+        self.blue >= cost.blue && self.red >= cost.red && self.green >= cost.green
+    }
+
+    //false and nothing spent when there aren't enough parts
+    pub fn spend(&mut self, cost : &PartInventory) -> bool{
+        if !self.can_afford(cost){
+            return false;
+        }
+        self.blue -= cost.blue;
+        self.red -= cost.red;
+        self.green -= cost.green;
+        true
+    }
+
+    pub fn refund(&mut self, cost : &PartInventory){
+        let refund = |parts : u32| (parts as f32 * SELL_REFUND).ceil() as u32;
+        self.blue += refund(cost.blue);
+        self.red += refund(cost.red);
+        self.green += refund(cost.green);
+    }
+
+    //e.g. "3 Blue, 1 Green"
+    pub fn describe(&self) -> String{
+        let parts : Vec<String> = [(self.blue, "Blue"), (self.red, "Red"), (self.green, "Green")].iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, tier)| format!("{} {}", count, tier))
+            .collect();
+        if parts.is_empty() {"free".to_string()} else {parts.join(", ")}
+    }
+}
+
+//a building the player placed and can sell again
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct PlacedBuilding{
+    pub kind : BuildingKind
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement{
+    pub translation : Vec3,
+    //free spot and enough parts
+    pub valid : bool
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct BuildMode{
+    pub active : bool,
+    pub selected : BuildingKind,
+    //where the selected building would go this frame, None outside build mode or without a cursor
+    pub placement : Option<Placement>
+}
+
+impl Default for BuildMode{
+    fn default() -> BuildMode {
+        BuildMode{active : false, selected : BuildingKind::GunTurret, placement : None}
+    }
+}
+
+#[derive(Component)]
+pub struct BuildPreview;
+
+#[derive(Component)]
+pub struct RangeRing;
+
+#[derive(Resource)]
+pub struct RangeRingImage{
+    pub handle : Handle<Image>
+}
+
+
+//inside the playfield and not overlapping the base or anything already built
+pub fn placement_is_free(translation : Vec3, size : Vec2, playfield : &Playfield, base : Option<(Vec3, Vec2)>, occupied : impl IntoIterator<Item = (Vec3, Vec2)>) -> bool{
+    let half_size = size / 2.0;
+    let inside = translation.x >= half_size.x && translation.x <= playfield.width - half_size.x
+        && translation.y >= half_size.y && translation.y <= playfield.height - half_size.y;
+    inside && base.into_iter().chain(occupied).all(|(other_translation, other_size)| collide(translation, size, other_translation, other_size).is_none())
+}
+
+pub fn spawn_building(commands : &mut Commands, asset_server : &AssetServer, kind : BuildingKind, translation : Vec3) -> Entity{
+    let mut building = commands.spawn((
+        SpriteBundle{
+            transform : Transform{
+                translation,
+                scale : Vec3::splat(0.3),
+                ..default()
+            },
+            texture : asset_server.load(kind.texture()),
+            ..default()
+        },
+        PlacedBuilding{kind}
+    ));
+    if let Some(turret) = kind.turret(){
+        building.insert(turret);
+    }
+    if let Some(health) = kind.health(){
+        building.insert(Health::new(health));
+    }
+    building.id()
+}
+
+
+//Systems
+
+pub fn reset_build_mode(mut part_inventory : ResMut<PartInventory>, mut build_mode : ResMut<BuildMode>){
+    *part_inventory = PartInventory::default();
+    *build_mode = BuildMode::default();
+}
+
+pub fn toggle_build_mode(action_state : Res<ActionState>, mut build_mode : ResMut<BuildMode>){
+    if action_state.just_pressed(Action::BuildMode){
+        build_mode.active = !build_mode.active;
+    }
+    if build_mode.active && action_state.just_pressed(Action::NextBuilding){
+        build_mode.selected = build_mode.selected.next();
+    }
+}
+
+type OccupiedFilter = Or<(With<PlacedBuilding>, With<Turret>, With<BaseBuilding>)>;
+
+//the selected building goes where the player aims
+pub fn update_build_placement(mut build_mode : ResMut<BuildMode>, player_input : Res<PlayerInput>, part_inventory : Res<PartInventory>, playfield : Res<Playfield>, base_query : Query<(&Transform, &Base)>, occupied_query : Query<(&Transform, Option<&PlacedBuilding>), OccupiedFilter>){
+    build_mode.placement = match (build_mode.active, player_input.aim) {
+        (true, Some(aim)) => {
+            let translation = aim.extend(0.0);
+            let kind = build_mode.selected;
+            let base = base_query.get_single().ok().map(|(base_transform, base)| (base_transform.translation, base.size));
+            let occupied = occupied_query.iter().map(|(transform, placed_building)| {
+                let size = placed_building.map_or(Vec2::splat(BASE_BUILDING_FOOTPRINT), |placed_building| placed_building.kind.size());
+                (transform.translation, size)
+            });
+            let free = placement_is_free(translation, kind.size(), &playfield, base, occupied);
+            Some(Placement{translation, valid : free && part_inventory.can_afford(&kind.cost())})
+        },
+        _ => None
+    };
+}
+
+//fire places the building instead of shooting while in build mode
+pub fn place_building(mut commands : Commands, action_state : Res<ActionState>, build_mode : Res<BuildMode>, mut player_input : ResMut<PlayerInput>, mut part_inventory : ResMut<PartInventory>, asset_server : Res<AssetServer>, mut sound_effects : EventWriter<SoundEffect>){
+    if !build_mode.active{
+        return;
+    }
+    player_input.fire = false;
+    if !action_state.just_pressed(Action::Fire){
+        return;
+    }
+    if let Some(placement) = build_mode.placement{
+        if placement.valid && part_inventory.spend(&build_mode.selected.cost()){
+            spawn_building(&mut commands, &asset_server, build_mode.selected, placement.translation);
+            sound_effects.send(SoundEffect("Audio/computerNoise_000.ogg"));
+        }
+    }
+}
+
+//sells whatever placed building is under the aim for part of its cost
+pub fn sell_building(mut commands : Commands, action_state : Res<ActionState>, build_mode : Res<BuildMode>, player_input : Res<PlayerInput>, mut part_inventory : ResMut<PartInventory>, building_query : Query<(Entity, &Transform, &PlacedBuilding)>, mut sound_effects : EventWriter<SoundEffect>){
+    if !build_mode.active || !action_state.just_pressed(Action::Sell){
+        return;
+    }
+    let Some(aim) = player_input.aim else {
+        return;
+    };
+    let under_aim = building_query.iter()
+        .find(|(_, transform, placed_building)| collide(aim.extend(0.0), Vec2::ONE, transform.translation, placed_building.kind.size()).is_some());
+    if let Some((building_entity, _, placed_building)) = under_aim{
+        part_inventory.refund(&placed_building.kind.cost());
+        commands.entity(building_entity).despawn();
+        sound_effects.send(SoundEffect("Audio/doorOpen_001.ogg"));
+    }
+}
+
+//walls take the hit the base would have taken, the enemy is used up
+pub fn enemy_hit_building(enemy_grid : Res<EnemyGrid>, enemy_query : Query<&Health, With<Enemy>>, building_query : Query<(Entity, &Transform, &PlacedBuilding), With<Health>>, mut damage_event_writer : EventWriter<DamageEvent>, mut sound_effects : EventWriter<SoundEffect>){
+    for (building_entity, building_transform, placed_building) in building_query.iter(){
+        let size = placed_building.kind.size();
+        for enemy in enemy_grid.candidates(building_transform.translation, size / 2.0){
+            let Ok(enemy_health) = enemy_query.get(enemy.entity) else {
+                continue;
+            };
+            if collide(building_transform.translation, size, enemy.translation, enemy.size).is_some(){
+                sound_effects.send(SoundEffect("Audio/footstep_snow_002.ogg"));
+                damage_event_writer.send(DamageEvent{source : Some(enemy.entity), target : building_entity, amount : enemy_health.current, damage_type : DamageType::Collision});
+                damage_event_writer.send(DamageEvent{source : None, target : enemy.entity, amount : enemy_health.current, damage_type : DamageType::Collision});
+            }
+        }
+    }
+}
+
+pub fn building_destroyed(mut commands : Commands, mut death_event_reader : EventReader<DeathEvent>, building_query : Query<Entity, With<PlacedBuilding>>, mut sound_effects : EventWriter<SoundEffect>){
+    for death_event in death_event_reader.iter(){
+        if let Ok(building_entity) = building_query.get(death_event.entity){
+            commands.entity(building_entity).despawn();
+            sound_effects.send(SoundEffect("Audio/explosionCrunch_003.ogg"));
+        }
+    }
+}
+
+pub fn despawn_buildings(mut commands : Commands, building_query : Query<Entity, With<PlacedBuilding>>){
+    for building_entity in building_query.iter(){
+        commands.entity(building_entity).despawn();
+    }
+}
+
+//a white circle outline, scaled to the range of whatever is previewed
+pub fn create_range_ring(mut commands : Commands, mut images : ResMut<Assets<Image>>){
+    let size = RANGE_RING_SIZE;
+    let radius = size as f32 / 2.0 - RANGE_RING_WIDTH;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size{
+        for x in 0..size{
+            let offset = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - Vec2::splat(size as f32 / 2.0);
+            let alpha = if (offset.length() - radius).abs() <= RANGE_RING_WIDTH / 2.0 {180} else {0};
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+    let image = Image::new(Extent3d{width : size, height : size, depth_or_array_layers : 1}, TextureDimension::D2, data, TextureFormat::Rgba8UnormSrgb);
+    commands.insert_resource(RangeRingImage{handle : images.add(image)});
+}
+
+type PreviewFilter = Or<(With<BuildPreview>, With<RangeRing>)>;
+type PreviewQuery<'w, 's> = Query<'w, 's, (&'static mut Transform, &'static mut Sprite, &'static mut Handle<Image>), (With<BuildPreview>, Without<RangeRing>)>;
+type RangeRingQuery<'w, 's> = Query<'w, 's, (&'static mut Transform, &'static mut Sprite, &'static mut Visibility), (With<RangeRing>, Without<BuildPreview>)>;
+
+pub fn update_build_preview(mut commands : Commands, build_mode : Res<BuildMode>, asset_server : Res<AssetServer>, range_ring_image : Res<RangeRingImage>, mut preview_query : PreviewQuery, mut ring_query : RangeRingQuery, preview_entity_query : Query<Entity, PreviewFilter>){
+    let Some(placement) = build_mode.placement else {
+        for preview_entity in preview_entity_query.iter(){
+            commands.entity(preview_entity).despawn();
+        }
+        return;
+    };
+    let kind = build_mode.selected;
+    let color = if placement.valid {PREVIEW_VALID_COLOR} else {PREVIEW_BLOCKED_COLOR};
+    let range = kind.turret().map(|turret| turret.stats.range);
+    let translation = placement.translation + Vec3::new(0.0, 0.0, 1.0);
+
+    match preview_query.get_single_mut() {
+        Ok((mut transform, mut sprite, mut texture)) => {
+            transform.translation = translation;
+            sprite.color = color;
+            *texture = asset_server.load(kind.texture());
+        },
+        Err(_) => {
+            commands.spawn((
+                SpriteBundle{
+                    transform : Transform{translation, scale : Vec3::splat(0.3), ..default()},
+                    sprite : Sprite{color, ..default()},
+                    texture : asset_server.load(kind.texture()),
+                    ..default()
+                },
+                BuildPreview
+            ));
+        }
+    }
+    match ring_query.get_single_mut() {
+        Ok((mut transform, mut sprite, mut visibility)) => {
+            transform.translation = translation;
+            sprite.color = color;
+            sprite.custom_size = range.map(|range| Vec2::splat(range * 2.0));
+            *visibility = if range.is_some() {Visibility::Inherited} else {Visibility::Hidden};
+        },
+        Err(_) => {
+            commands.spawn((
+                SpriteBundle{
+                    transform : Transform::from_translation(translation),
+                    sprite : Sprite{color, custom_size : range.map(|range| Vec2::splat(range * 2.0)), ..default()},
+                    texture : range_ring_image.handle.clone(),
+                    visibility : if range.is_some() {Visibility::Inherited} else {Visibility::Hidden},
+                    ..default()
+                },
+                RangeRing
+            ));
+        }
+    }
+}
+
+pub fn despawn_build_preview(mut commands : Commands, preview_query : Query<Entity, PreviewFilter>){
+    for preview_entity in preview_query.iter(){
+        commands.entity(preview_entity).despawn();
+    }
+}
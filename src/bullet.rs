@@ -1,16 +1,24 @@
-use std::{ f32::consts::PI, time::Instant};
-use bevy::{ prelude::*, window::PrimaryWindow};
+use std::f32::consts::PI;
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
 
 
-use crate::base::*;
-use super::AppState;
+use crate::health::DamageSet;
+use crate::status::StatusEffect;
+use crate::game_time::SimulationClock;
+use super::{AppState, SimulationState, Playfield};
 
 pub struct BulletPlugin;
 
 impl Plugin for BulletPlugin{
     fn build(&self, app: &mut App) {
         app
-        .add_system(update_bullets)
+        .add_system(
+            update_bullets
+            .before(DamageSet)
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
 
         
         .add_system(despawn_bullets.in_schedule(OnExit(AppState::Game)));
@@ -24,75 +32,140 @@ impl Plugin for BulletPlugin{
 
 
 pub const BULLET_LIFETIME : f32 = 1.5;
-pub const BULLET_SPEED: f32= 250.0;
 pub const BULLET_SIZE : f32 = 16.0;
+//base level bonuses stop at these levels
+pub const MAX_SPEED_BONUS_LEVEL : i64 = 7;
+pub const MAX_DAMAGE_BONUS_LEVEL : i64 = 6;
 
-#[derive(Component)]
+#[derive(Component, Debug, Clone, Default)]
 pub struct Bullet{
     pub speed: f32,
     pub direction : Vec2,
     pub size : Vec2,
     pub damage : i64,
-    pub instant : Instant
-    
+    pub spawn_time : f32,
+    //enemies it can still go through after the next hit
+    pub pierce : u32,
+    //times it can still bounce off the edge of the playfield
+    pub bounces : u32,
+    //how far an enemy it hits is pushed back
+    pub knockback : f32,
+    //enemies already hit, so a piercing bullet doesn't hit the same one twice
+    pub hits : Vec<Entity>,
+    //put on every enemy it hits
+    pub status : Option<StatusEffect>
+}
+
+//What a projectile is fired with, worked out once when it is spawned.
+//The shooter's own stats with every modifier that applies stacked on top.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProjectileStats{
+    pub damage : i64,
+    pub speed : f32,
+    pub size : f32,
+    pub pierce : u32,
+    pub bounces : u32,
+    pub knockback : f32,
+    pub status : Option<StatusEffect>
+}
+
+//one bonus to the projectiles fired, from the base level or a pickup
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProjectileModifier{
+    pub damage_multiplier : f32,
+    pub speed_multiplier : f32,
+    pub pierce : u32,
+    pub bounces : u32,
+    pub knockback : f32
+}
+
+//the modifiers picked up by whoever carries this, on top of the base level bonus
+#[derive(Component, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectileUpgrades{
+    pub modifiers : Vec<ProjectileModifier>
+}
+
+impl Default for ProjectileModifier{
+    fn default() -> ProjectileModifier {
+        ProjectileModifier{damage_multiplier : 1.0, speed_multiplier : 1.0, pierce : 0, bounces : 0, knockback : 0.0}
+    }
+}
+
+impl ProjectileModifier{
+    //the player's shots get faster and stronger with the base, twice as fast by level 7 and twice as strong by level 6
+    pub fn base_level(level : i64) -> ProjectileModifier{
+        ProjectileModifier{
+            damage_multiplier : 1.0 + 0.2 * (level.clamp(1, MAX_DAMAGE_BONUS_LEVEL) - 1) as f32,
+            speed_multiplier : 1.0 + (level.clamp(1, MAX_SPEED_BONUS_LEVEL) - 1) as f32 / (MAX_SPEED_BONUS_LEVEL - 1) as f32,
+            ..default()
+        }
+    }
+}
+
+impl ProjectileStats{
+    //multipliers multiply each other, everything else adds up
+    pub fn with<'a>(&self, modifiers : impl IntoIterator<Item = &'a ProjectileModifier>) -> ProjectileStats{
+        modifiers.into_iter().fold(*self, |stats, modifier| ProjectileStats{
+            damage : (stats.damage as f32 * modifier.damage_multiplier).round() as i64,
+            speed : stats.speed * modifier.speed_multiplier,
+            size : stats.size,
+            pierce : stats.pierce + modifier.pierce,
+            bounces : stats.bounces + modifier.bounces,
+            knockback : stats.knockback + modifier.knockback,
+            status : stats.status
+        })
+    }
 }
 
-pub fn update_bullets(mut commands: Commands, mut bullet_query: Query<(&mut Transform, &mut Bullet,Entity), (With<Bullet>, Without<Base>)>, time: Res<Time>, window_query : Query<&Window, With<PrimaryWindow>>, base_query: Query<&Base, (With<Base>, Without<Bullet>)>){
-    let window = window_query.get_single().unwrap();
+impl Bullet{
+    pub fn new(stats : &ProjectileStats, direction : Vec2, spawn_time : f32) -> Bullet{
+        Bullet{
+            speed : stats.speed,
+            direction,
+            size : Vec2::splat(stats.size),
+            damage : stats.damage,
+            spawn_time,
+            pierce : stats.pierce,
+            bounces : stats.bounces,
+            knockback : stats.knockback,
+            hits : Vec::new(),
+            status : stats.status
+        }
+    }
+}
+
+//bullets that reach the edge of the playfield bounce off it while they have bounces left
+pub fn update_bullets(mut commands: Commands, mut bullet_query: Query<(&mut Transform, &mut Bullet, Entity)>, time: Res<Time>, playfield : Res<Playfield>, simulation_clock : Res<SimulationClock>){
     for (mut transform, mut bullet, entity) in bullet_query.iter_mut(){
         
         let direction = Vec3::new(bullet.direction.x, bullet.direction.y, 0.0);
         transform.translation += direction*bullet.speed*time.delta_seconds();
         transform.rotation = Quat::from_rotation_z(bullet.direction.y.atan2(bullet.direction.x) - PI/2.0);
-        if bullet.instant.elapsed().as_secs() > BULLET_LIFETIME as u64{
+        if simulation_clock.elapsed_since(bullet.spawn_time).as_secs() > BULLET_LIFETIME as u64{
             commands.entity(entity).despawn();
             
         }
         let half_bullet_size = BULLET_SIZE/2.0;
         let x_lim = half_bullet_size;
-        let x_max = window.width() - half_bullet_size;
+        let x_max = playfield.width - half_bullet_size;
         let y_lim = half_bullet_size;
-        let y_max = window.height() - half_bullet_size;
+        let y_max = playfield.height - half_bullet_size;
 
         let translation = transform.translation;
-        if translation.x < x_lim{commands.entity(entity).despawn();}
-        else if translation.x > x_max {commands.entity(entity).despawn();}
-        else if translation.y < y_lim {commands.entity(entity).despawn();}
-        else if translation.y > y_max {commands.entity(entity).despawn();}
-        for base in base_query.iter(){
-            match  base.level {
-                1 => {
-                    bullet.speed = 300.0;
-                    bullet.damage = 50;
-                },
-                2 =>{
-                    bullet.speed = 350.0;
-                    bullet.damage = 60;
-                },
-                3 =>{
-                    bullet.speed = 400.0;
-                    bullet.damage = 70;
-                },
-                4 =>{
-                    bullet.speed = 450.0;
-                    bullet.damage = 80;
-                },
-                5 =>{
-                    bullet.speed = 500.0;
-                    bullet.damage = 90;
-                },
-                6 => {
-                    bullet.speed = 550.0;
-                    bullet.damage = 100;
-                },
-                _ =>{
-                    bullet.speed = 600.0;
-                    bullet.damage = 100;
-                }
-
-                
-            }
+        let outside_x = translation.x < x_lim || translation.x > x_max;
+        let outside_y = translation.y < y_lim || translation.y > y_max;
+        if !outside_x && !outside_y{
+            continue;
+        }
+        if bullet.bounces == 0{
+            commands.entity(entity).despawn();
+            continue;
         }
+        bullet.bounces -= 1;
+        if outside_x {bullet.direction.x = -bullet.direction.x;}
+        if outside_y {bullet.direction.y = -bullet.direction.y;}
+        transform.translation.x = translation.x.clamp(x_lim, x_max);
+        transform.translation.y = translation.y.clamp(y_lim, y_max);
     }
           
 }
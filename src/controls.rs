@@ -0,0 +1,305 @@
+use std::{collections::{BTreeMap, HashSet}, fs, path::PathBuf};
+use serde::{Deserialize, Serialize};
+use bevy::{prelude::*, input::{InputSystem, mouse::MouseWheel}, ecs::system::SystemParam};
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<InputBindings>()
+        .init_resource::<ActionState>()
+        .init_resource::<Rebinding>()
+
+        //read once per frame, right after bevy has collected the raw input
+        .add_system(update_action_state.in_base_set(CoreSet::PreUpdate).after(InputSystem));
+    }
+}
+
+
+
+pub const BINDINGS_PATH : &str = "bindings.ron";
+pub const STICK_DEADZONE : f32 = 0.2;
+//how far the right stick has to be pushed to aim and fire
+pub const AIM_DEADZONE : f32 = 0.3;
+//seconds the controls screen waits for the new input before giving up, so any key can be bound, escape too
+pub const REBIND_TIMEOUT : f32 = 5.0;
+
+
+//everything the player can do, the game only ever asks for these
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action{
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Pause,
+    StartGame,
+    MainMenu,
+    SpeedUp,
+    SlowDown,
+    CheatLevel,
+    Quit,
+    BuildMode,
+    NextBuilding,
+    Sell,
+    NextWeapon,
+    PreviousWeapon,
+    Weapon1,
+    Weapon2,
+    Weapon3,
+    Weapon4,
+    Weapon5,
+    Weapon6,
+    WaveSummary,
+    EnemyHealthBars
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding{
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType)
+}
+
+//which inputs trigger which action, saved when changed on the controls screen
+//only read from and written to disk when path is set
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct InputBindings{
+    pub bindings : BTreeMap<Action, Vec<Binding>>,
+    #[serde(skip)]
+    pub path : Option<PathBuf>
+}
+
+//the actions held and started this frame, plus the analog sticks
+#[derive(Resource, Default)]
+pub struct ActionState{
+    pressed : HashSet<Action>,
+    just_pressed : HashSet<Action>,
+    //left stick, or the move actions when no stick is pushed
+    pub movement : Vec2,
+    //right stick direction when pushed past AIM_DEADZONE
+    pub aim_stick : Option<Vec2>,
+    //mouse wheel lines this frame, positive is up
+    pub scroll : f32
+}
+
+//set while the controls screen waits for the new input of an action
+#[derive(Resource, Default)]
+pub struct Rebinding{
+    pub action : Option<Action>,
+    //real seconds, the menus don't run the simulation clock
+    pub started : f32
+}
+
+
+impl Action{
+    pub const ALL : [Action; 25] = [
+        Action::MoveUp, Action::MoveDown, Action::MoveLeft, Action::MoveRight, Action::Fire, Action::Pause,
+        Action::StartGame, Action::MainMenu, Action::SpeedUp, Action::SlowDown, Action::CheatLevel, Action::Quit,
+        Action::BuildMode, Action::NextBuilding, Action::Sell, Action::NextWeapon, Action::PreviousWeapon,
+        Action::Weapon1, Action::Weapon2, Action::Weapon3, Action::Weapon4, Action::Weapon5, Action::Weapon6,
+        Action::WaveSummary, Action::EnemyHealthBars
+    ];
+
+    //in the order of WeaponKind::ALL
+    pub const WEAPONS : [Action; 6] = [Action::Weapon1, Action::Weapon2, Action::Weapon3, Action::Weapon4, Action::Weapon5, Action::Weapon6];
+
+    pub fn label(&self) -> &'static str{
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Fire => "Fire",
+            Action::Pause => "Pause",
+            Action::StartGame => "Start game",
+            Action::MainMenu => "Main menu",
+            Action::SpeedUp => "Speed up",
+            Action::SlowDown => "Slow down",
+            Action::CheatLevel => "Level up (cheat)",
+            Action::Quit => "Quit",
+            Action::BuildMode => "Build mode",
+            Action::NextBuilding => "Next building",
+            Action::Sell => "Sell building",
+            Action::NextWeapon => "Next weapon",
+            Action::PreviousWeapon => "Previous weapon",
+            Action::Weapon1 => "Blaster",
+            Action::Weapon2 => "Spread shot",
+            Action::Weapon3 => "Rapid fire",
+            Action::Weapon4 => "Charge shot",
+            Action::Weapon5 => "Laser",
+            Action::Weapon6 => "Bomb",
+            Action::WaveSummary => "Wave summary",
+            Action::EnemyHealthBars => "Enemy health bars"
+        }
+    }
+}
+
+impl Binding{
+    pub fn is_gamepad(&self) -> bool{
+        matches!(self, Binding::Gamepad(_))
+    }
+
+    pub fn label(&self) -> String{
+        match self {
+            Binding::Key(key_code) => format!("{:?}", key_code),
+            Binding::Mouse(mouse_button) => format!("Mouse {:?}", mouse_button),
+            Binding::Gamepad(button_type) => format!("Pad {:?}", button_type)
+        }
+    }
+}
+
+impl Default for InputBindings{
+    fn default() -> InputBindings {
+        use Binding::*;
+        //nothing quits by default, the menus have quit buttons
+        let bindings = BTreeMap::from([
+            (Action::MoveUp, vec![Key(KeyCode::W), Gamepad(GamepadButtonType::DPadUp)]),
+            (Action::MoveDown, vec![Key(KeyCode::S), Gamepad(GamepadButtonType::DPadDown)]),
+            (Action::MoveLeft, vec![Key(KeyCode::A), Gamepad(GamepadButtonType::DPadLeft)]),
+            (Action::MoveRight, vec![Key(KeyCode::D), Gamepad(GamepadButtonType::DPadRight)]),
+            (Action::Fire, vec![Mouse(MouseButton::Left), Gamepad(GamepadButtonType::RightTrigger2)]),
+            (Action::Pause, vec![Key(KeyCode::Space), Gamepad(GamepadButtonType::Start)]),
+            (Action::StartGame, vec![Key(KeyCode::G), Gamepad(GamepadButtonType::South)]),
+            (Action::MainMenu, vec![Key(KeyCode::M), Gamepad(GamepadButtonType::Select)]),
+            (Action::SpeedUp, vec![Key(KeyCode::Equals), Key(KeyCode::NumpadAdd), Gamepad(GamepadButtonType::RightTrigger)]),
+            (Action::SlowDown, vec![Key(KeyCode::Minus), Key(KeyCode::NumpadSubtract), Gamepad(GamepadButtonType::LeftTrigger)]),
+            (Action::CheatLevel, vec![Key(KeyCode::Tab)]),
+            (Action::Quit, vec![]),
+            (Action::BuildMode, vec![Key(KeyCode::B), Gamepad(GamepadButtonType::North)]),
+            (Action::NextBuilding, vec![Key(KeyCode::R), Gamepad(GamepadButtonType::East)]),
+            (Action::Sell, vec![Key(KeyCode::X), Gamepad(GamepadButtonType::West)]),
+            (Action::NextWeapon, vec![Key(KeyCode::E), Gamepad(GamepadButtonType::RightThumb)]),
+            (Action::PreviousWeapon, vec![Key(KeyCode::Q), Gamepad(GamepadButtonType::LeftThumb)]),
+            (Action::Weapon1, vec![Key(KeyCode::Key1)]),
+            (Action::Weapon2, vec![Key(KeyCode::Key2)]),
+            (Action::Weapon3, vec![Key(KeyCode::Key3)]),
+            (Action::Weapon4, vec![Key(KeyCode::Key4)]),
+            (Action::Weapon5, vec![Key(KeyCode::Key5)]),
+            (Action::Weapon6, vec![Key(KeyCode::Key6)]),
+            (Action::WaveSummary, vec![Key(KeyCode::I)]),
+            (Action::EnemyHealthBars, vec![Key(KeyCode::H)]),
+        ]);
+        InputBindings{bindings, path : None}
+    }
+}
+
+impl InputBindings{
+    //actions missing from the file keep their default bindings
+    pub fn load(path : PathBuf) -> InputBindings{
+        let mut input_bindings = InputBindings::default();
+        if let Ok(contents) = fs::read_to_string(&path){
+            match ron::from_str::<InputBindings>(&contents) {
+                Ok(loaded) => input_bindings.bindings.extend(loaded.bindings),
+                Err(error) => println!("Couldn't read {}: {}", path.display(), error)
+            }
+        }
+        input_bindings.path = Some(path);
+        input_bindings
+    }
+
+    pub fn save(&self){
+        if let Some(path) = &self.path{
+            let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(|error| error.to_string())
+                .and_then(|contents| fs::write(path, contents).map_err(|error| error.to_string()));
+            if let Err(error) = result{
+                println!("Couldn't save {}: {}", path.display(), error);
+            }
+        }
+    }
+
+    pub fn get(&self, action : Action) -> &[Binding]{
+        self.bindings.get(&action).map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    //a gamepad button replaces the gamepad bindings of the action, a key or mouse button the others
+    pub fn rebind(&mut self, action : Action, binding : Binding){
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|existing| existing.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+    }
+}
+
+impl ActionState{
+    pub fn pressed(&self, action : Action) -> bool{
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action : Action) -> bool{
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn release_all(&mut self){
+        self.pressed.clear();
+        self.just_pressed.clear();
+        self.movement = Vec2::ZERO;
+        self.aim_stick = None;
+        self.scroll = 0.0;
+    }
+}
+
+fn stick(axes : &Axis<GamepadAxis>, gamepad : Gamepad, x : GamepadAxisType, y : GamepadAxisType) -> Vec2{
+    Vec2::new(
+        axes.get(GamepadAxis::new(gamepad, x)).unwrap_or(0.0),
+        axes.get(GamepadAxis::new(gamepad, y)).unwrap_or(0.0)
+    )
+}
+
+
+//Systems
+
+pub fn load_input_bindings(mut input_bindings : ResMut<InputBindings>){
+    *input_bindings = InputBindings::load(PathBuf::from(BINDINGS_PATH));
+}
+
+//every connected gamepad's buttons and sticks
+#[derive(SystemParam)]
+pub struct GamepadInput<'w>{
+    gamepads : Res<'w, Gamepads>,
+    gamepad_buttons : Res<'w, Input<GamepadButton>>,
+    gamepad_axes : Res<'w, Axis<GamepadAxis>>
+}
+
+pub fn update_action_state(mut action_state : ResMut<ActionState>, input_bindings : Res<InputBindings>, rebinding : Res<Rebinding>, keyboard_input : Res<Input<KeyCode>>, mouse_input : Res<Input<MouseButton>>, gamepad_input : GamepadInput, mut mouse_wheel : EventReader<MouseWheel>){
+    let GamepadInput{gamepads, gamepad_buttons, gamepad_axes} = gamepad_input;
+    let held : HashSet<Action> = Action::ALL.into_iter()
+        .filter(|action| input_bindings.get(*action).iter().any(|binding| match binding {
+            Binding::Key(key_code) => keyboard_input.pressed(*key_code),
+            Binding::Mouse(mouse_button) => mouse_input.pressed(*mouse_button),
+            Binding::Gamepad(button_type) => gamepads.iter().any(|gamepad| gamepad_buttons.pressed(GamepadButton::new(gamepad, *button_type)))
+        }))
+        .collect();
+    let previously_held = std::mem::take(&mut action_state.pressed);
+    action_state.release_all();
+    //the input that is being bound shouldn't also do what it is bound to
+    if rebinding.action.is_some(){
+        return;
+    }
+    for action in held{
+        if !previously_held.contains(&action){
+            action_state.just_pressed.insert(action);
+        }
+        action_state.pressed.insert(action);
+    }
+
+    let mut movement = Vec2::ZERO;
+    if action_state.pressed(Action::MoveUp) {movement.y += 1.0;}
+    if action_state.pressed(Action::MoveDown) {movement.y -= 1.0;}
+    if action_state.pressed(Action::MoveLeft) {movement.x -= 1.0;}
+    if action_state.pressed(Action::MoveRight) {movement.x += 1.0;}
+    for gamepad in gamepads.iter(){
+        let left_stick = stick(&gamepad_axes, gamepad, GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY);
+        if left_stick.length() > STICK_DEADZONE{
+            movement = left_stick;
+        }
+        let right_stick = stick(&gamepad_axes, gamepad, GamepadAxisType::RightStickX, GamepadAxisType::RightStickY);
+        if right_stick.length() > AIM_DEADZONE{
+            action_state.aim_stick = Some(right_stick.normalize());
+        }
+    }
+    action_state.movement = movement;
+    action_state.scroll = mouse_wheel.iter().map(|wheel| wheel.y).sum();
+}
@@ -0,0 +1,242 @@
+use bevy::prelude::*;
+use crate::{repetitive_code::*, controls::*};
+use super::AppState;
+
+pub struct ControlsMenuPlugin;
+
+impl Plugin for ControlsMenuPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .add_system(spawn_controls_menu.in_schedule(OnEnter(AppState::Controls)))
+        .add_systems(
+            (
+                //before the buttons so the click that starts a rebind isn't bound itself
+                capture_rebinding.before(interact_with_binding_buttons),
+                interact_with_binding_buttons,
+                interact_with_reset_button,
+                interact_with_back_button,
+                update_binding_texts
+            ).in_set(OnUpdate(AppState::Controls))
+        )
+        .add_system(despawn_controls_menu.in_schedule(OnExit(AppState::Controls)));
+    }
+}
+
+
+//Components
+
+#[derive(Component)]
+pub struct ControlsMenu;
+
+#[derive(Component)]
+pub struct BindingButton{
+    pub action : Action
+}
+
+#[derive(Component)]
+pub struct BindingText{
+    pub action : Action
+}
+
+#[derive(Component)]
+pub struct ResetBindingsButton;
+
+#[derive(Component)]
+pub struct BackButton;
+
+
+fn describe_bindings(action : Action, input_bindings : &InputBindings, rebinding : &Rebinding) -> String{
+    if rebinding.action == Some(action){
+        return "press a key or button, wait to cancel".to_string();
+    }
+    let labels : Vec<String> = input_bindings.get(action).iter().map(|binding| binding.label()).collect();
+    if labels.is_empty() {"unbound".to_string()} else {labels.join(", ")}
+}
+
+
+//Layout
+
+pub fn spawn_controls_menu(mut commands : Commands, asset_server: Res<AssetServer>, input_bindings : Res<InputBindings>, rebinding : Res<Rebinding>) {
+    build_controls_menu(&mut commands, &asset_server, &input_bindings, &rebinding);
+}
+
+pub fn despawn_controls_menu(mut commands : Commands, controls_menu_query: Query<Entity, With<ControlsMenu>>, mut rebinding : ResMut<Rebinding>) {
+    if let Ok(controls_menu_entity) = controls_menu_query.get_single(){
+        commands.entity(controls_menu_entity).despawn_recursive();
+    }
+    rebinding.action = None;
+}
+
+pub fn build_controls_menu(commands : &mut Commands, asset_server: &Res<AssetServer>, input_bindings : &InputBindings, rebinding : &Rebinding) -> Entity{
+    let text_style = get_leaderboard_text_style(asset_server);
+    let controls_menu_entity = commands
+        .spawn((
+            NodeBundle{
+                style : MAIN_MENU_STYLE,
+                ..default()
+            },
+            ControlsMenu{},
+        ))
+        .with_children(|parent|{
+            //title
+            parent.spawn(TextBundle{
+                text : Text{
+                    sections : vec![TextSection::new("Controls", get_title_text_style(asset_server))],
+                    alignment : TextAlignment::Center,
+                    ..default()
+                },
+                ..default()
+            });
+            //one row per action, click the bindings to change them
+            for action in Action::ALL{
+                parent.spawn(NodeBundle{
+                    style : CONTROLS_ROW_STYLE,
+                    ..default()
+                })
+                .with_children(|parent|{
+                    parent.spawn(TextBundle{
+                        text : Text{
+                            sections : vec![TextSection::new(action.label(), text_style.clone())],
+                            alignment : TextAlignment::Left,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                    parent.spawn((
+                        ButtonBundle{
+                            style : BINDING_BUTTON_STYLE,
+                            background_color : NORMAL_BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        BindingButton{action},
+                    ))
+                    .with_children(|parent|{
+                        parent.spawn((
+                            TextBundle{
+                                text : Text{
+                                    sections : vec![TextSection::new(describe_bindings(action, input_bindings, rebinding), text_style.clone())],
+                                    alignment : TextAlignment::Center,
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            BindingText{action},
+                        ));
+                    });
+                });
+            }
+            //reset button
+            parent.spawn((
+                ButtonBundle{
+                    style : BUTTON_STYLE,
+                    background_color : NORMAL_BUTTON_COLOR.into(),
+                    ..default()
+                },
+                ResetBindingsButton{},
+            ))
+            .with_children(|parent|{
+                parent.spawn(TextBundle{
+                    text : Text{
+                        sections : vec![TextSection::new("Reset", get_button_text_style(asset_server))],
+                        alignment : TextAlignment::Center,
+                        ..default()
+                    },
+                    ..default()
+                });
+            });
+            //back button
+            parent.spawn((
+                ButtonBundle{
+                    style : BUTTON_STYLE,
+                    background_color : NORMAL_BUTTON_COLOR.into(),
+                    ..default()
+                },
+                BackButton{},
+            ))
+            .with_children(|parent|{
+                parent.spawn(TextBundle{
+                    text : Text{
+                        sections : vec![TextSection::new("Back", get_button_text_style(asset_server))],
+                        alignment : TextAlignment::Center,
+                        ..default()
+                    },
+                    ..default()
+                });
+            });
+        })
+        .id();
+    controls_menu_entity
+}
+
+
+//Interactions
+
+pub fn interact_with_binding_buttons(mut button_query : Query<(&Interaction, &mut BackgroundColor, &BindingButton), Changed<Interaction>>, mut rebinding : ResMut<Rebinding>, time : Res<Time>){
+    for (interaction, mut background_color, binding_button) in button_query.iter_mut(){
+        match *interaction{
+            Interaction::Clicked => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                *rebinding = Rebinding{action : Some(binding_button.action), started : time.elapsed_seconds()};
+            },
+            Interaction::Hovered => *background_color = HOVERED_BUTTON_COLOR.into(),
+            Interaction::None => *background_color = NORMAL_BUTTON_COLOR.into()
+        }
+    }
+}
+
+pub fn interact_with_reset_button(mut button_query : ButtonInteractionQuery<ResetBindingsButton>, mut input_bindings : ResMut<InputBindings>, mut rebinding : ResMut<Rebinding>){
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut(){
+        match *interaction{
+            Interaction::Clicked => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                input_bindings.bindings = InputBindings::default().bindings;
+                input_bindings.save();
+                rebinding.action = None;
+            },
+            Interaction::Hovered => *background_color = HOVERED_BUTTON_COLOR.into(),
+            Interaction::None => *background_color = NORMAL_BUTTON_COLOR.into()
+        }
+    }
+}
+
+pub fn interact_with_back_button(mut button_query : ButtonInteractionQuery<BackButton>, mut app_state_next_state : ResMut<NextState<AppState>>){
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut(){
+        match *interaction{
+            Interaction::Clicked => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                app_state_next_state.set(AppState::MainMenu);
+            },
+            Interaction::Hovered => *background_color = HOVERED_BUTTON_COLOR.into(),
+            Interaction::None => *background_color = NORMAL_BUTTON_COLOR.into()
+        }
+    }
+}
+
+//other systems
+
+//the next key, mouse or gamepad button pressed becomes the binding, nothing for REBIND_TIMEOUT seconds cancels
+pub fn capture_rebinding(mut rebinding : ResMut<Rebinding>, mut input_bindings : ResMut<InputBindings>, keyboard_input : Res<Input<KeyCode>>, mouse_input : Res<Input<MouseButton>>, gamepad_buttons : Res<Input<GamepadButton>>, time : Res<Time>){
+    let Some(action) = rebinding.action else {
+        return;
+    };
+    if time.elapsed_seconds() - rebinding.started > REBIND_TIMEOUT{
+        rebinding.action = None;
+        return;
+    }
+    let binding = keyboard_input.get_just_pressed().next().map(|key_code| Binding::Key(*key_code))
+        .or_else(|| mouse_input.get_just_pressed().next().map(|mouse_button| Binding::Mouse(*mouse_button)))
+        .or_else(|| gamepad_buttons.get_just_pressed().next().map(|gamepad_button| Binding::Gamepad(gamepad_button.button_type)));
+    if let Some(binding) = binding{
+        input_bindings.rebind(action, binding);
+        input_bindings.save();
+        rebinding.action = None;
+    }
+}
+
+pub fn update_binding_texts(input_bindings : Res<InputBindings>, rebinding : Res<Rebinding>, mut text_query : Query<(&mut Text, &BindingText)>){
+    if input_bindings.is_changed() || rebinding.is_changed(){
+        for (mut text, binding_text) in text_query.iter_mut(){
+            text.sections[0].value = describe_bindings(binding_text.action, &input_bindings, &rebinding);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use rand::prelude::*;
+use bevy::{prelude::*, ecs::system::SystemParam};
+
+use crate::base::Base;
+use crate::player::Player;
+use crate::enemy::Enemy;
+use crate::health::*;
+use crate::wave_script::SpawnEdge;
+use crate::game_time::SimulationClock;
+use super::{AppState, SimulationState, Playfield, RunStats};
+
+//The director watches how the run is going and moves the intensity up while the player is doing well
+//and down while they are struggling. The wave and trickle spawners read it for how many enemies to send,
+//which variants to pick and which edges to send them from.
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        //Resources
+        .init_resource::<Difficulty>()
+        .init_resource::<Director>()
+
+        //On enter Game Appstate
+        .add_system(start_director.in_schedule(OnEnter(AppState::Game)))
+
+        //While in game appstate
+        //samples once every gameplay system has run, so the spawners see the same numbers on every replay of a seed
+        .add_system(
+            update_director
+            .in_base_set(CoreSet::PostUpdate)
+            .run_if(in_state(AppState::Game))
+            .run_if(in_state(SimulationState::Running))
+        );
+    }
+}
+
+
+
+//seconds between two looks at how the player is doing
+pub const DIRECTOR_SAMPLE_INTERVAL : f32 = 5.0;
+//the part of the spawned enemies the player is expected to shoot down, the rest reach something
+pub const TARGET_KILL_RATIO : f32 = 0.8;
+pub const KILL_WEIGHT : f32 = 1.0;
+pub const PART_WEIGHT : f32 = 0.1;
+pub const MAX_PART_SCORE : f32 = 0.5;
+pub const DEATH_WEIGHT : f32 = 1.0;
+//the base losing 2% of its health a second counts as much as a death
+pub const BASE_TREND_WEIGHT : f32 = 50.0;
+//how far the spawn edges lean towards or away from the player
+pub const MAX_EDGE_TILT : f32 = 0.75;
+
+
+//picked in the main menu, the director of every new run starts from it
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Difficulty{
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyPreset{
+    //multiplies the enemies of every wave and the trickle budget on top of the intensity
+    pub enemy_count : f32,
+    pub start_intensity : f32,
+    pub min_intensity : f32,
+    pub max_intensity : f32,
+    //how far a single sample can move the intensity
+    pub adapt_rate : f32
+}
+
+impl Difficulty{
+    pub const ALL : [Difficulty; 4] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Nightmare];
+
+    pub fn preset(&self) -> DifficultyPreset{
+        match self {
+            Difficulty::Easy => DifficultyPreset{enemy_count : 0.7, start_intensity : 0.8, min_intensity : 0.5, max_intensity : 1.2, adapt_rate : 0.05},
+            Difficulty::Normal => DifficultyPreset{enemy_count : 1.0, start_intensity : 1.0, min_intensity : 0.7, max_intensity : 1.6, adapt_rate : 0.08},
+            Difficulty::Hard => DifficultyPreset{enemy_count : 1.3, start_intensity : 1.2, min_intensity : 0.9, max_intensity : 2.0, adapt_rate : 0.1},
+            Difficulty::Nightmare => DifficultyPreset{enemy_count : 1.6, start_intensity : 1.5, min_intensity : 1.2, max_intensity : 2.5, adapt_rate : 0.12}
+        }
+    }
+
+    pub fn name(&self) -> &'static str{
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Nightmare => "Nightmare"
+        }
+    }
+
+    pub fn from_name(name : &str) -> Option<Difficulty>{
+        Difficulty::ALL.into_iter().find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+
+    //the main menu button cycles through them
+    pub fn next(&self) -> Difficulty{
+        let index = Difficulty::ALL.iter().position(|difficulty| difficulty == self).unwrap_or(0);
+        Difficulty::ALL[(index + 1) % Difficulty::ALL.len()]
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Director{
+    pub difficulty : Difficulty,
+    //1.0 is the game as the wave script describes it
+    pub intensity : f32,
+    //smoothed change of the base health fraction per second, negative while the base is losing health
+    pub base_trend : f32,
+    //how likely each edge is to be picked, in the order Top, Bottom, Left, Right
+    pub edge_weights : [f32; 4],
+    //what happened since the last sample
+    pub spawned : u32,
+    pub player_deaths : u32,
+    pub kills_at_sample : u64,
+    pub parts_at_sample : u64,
+    pub base_health_at_sample : Option<f32>,
+    pub last_sample : f32
+}
+
+impl Default for Director{
+    fn default() -> Director {
+        Director::new(Difficulty::default())
+    }
+}
+
+impl Director{
+    pub fn new(difficulty : Difficulty) -> Director{
+        Director{
+            difficulty,
+            intensity : difficulty.preset().start_intensity,
+            base_trend : 0.0,
+            edge_weights : [1.0; 4],
+            spawned : 0,
+            player_deaths : 0,
+            kills_at_sample : 0,
+            parts_at_sample : 0,
+            base_health_at_sample : None,
+            last_sample : 0.0
+        }
+    }
+
+    pub fn enemy_multiplier(&self) -> f32{
+        self.difficulty.preset().enemy_count * self.intensity
+    }
+
+    //never rounds a wave entry away completely
+    pub fn scale_count(&self, count : usize) -> usize{
+        if count == 0{
+            return 0;
+        }
+        ((count as f32 * self.enemy_multiplier()).round() as usize).max(1)
+    }
+
+    //how well the player did over the last sample, from -1 struggling to 1 cruising
+    pub fn performance(&self, kills : u64, parts : u64) -> f32{
+        let mut score = 0.0;
+        if self.spawned > 0{
+            let kill_ratio = kills.saturating_sub(self.kills_at_sample) as f32 / self.spawned as f32;
+            score += ((kill_ratio - TARGET_KILL_RATIO) * KILL_WEIGHT).clamp(-1.0, 1.0);
+        }
+        score += (parts.saturating_sub(self.parts_at_sample) as f32 * PART_WEIGHT).min(MAX_PART_SCORE);
+        score -= self.player_deaths as f32 * DEATH_WEIGHT;
+        score += self.base_trend * BASE_TREND_WEIGHT;
+        score.clamp(-1.0, 1.0)
+    }
+
+    pub fn adapt(&mut self, performance : f32){
+        let preset = self.difficulty.preset();
+        self.intensity = (self.intensity + performance * preset.adapt_rate).clamp(preset.min_intensity, preset.max_intensity);
+    }
+
+    //above 1.0 the enemies come from the edges away from the player, below it from the edges next to them
+    pub fn update_edge_weights(&mut self, player_translation : Vec3, playfield : &Playfield){
+        let tilt = (self.intensity - 1.0).clamp(-MAX_EDGE_TILT, MAX_EDGE_TILT);
+        let x = (player_translation.x / playfield.width).clamp(0.0, 1.0);
+        let y = (player_translation.y / playfield.height).clamp(0.0, 1.0);
+        //how far the player is from each edge, from 0 on it to 1 across the playfield
+        let distances = [1.0 - y, y, x, 1.0 - x];
+        for (weight, distance) in self.edge_weights.iter_mut().zip(distances){
+            *weight = 1.0 + tilt * (2.0 * distance - 1.0);
+        }
+    }
+
+    pub fn edge_weight(&self, edge : SpawnEdge) -> f32{
+        match edge {
+            SpawnEdge::Top => self.edge_weights[0],
+            SpawnEdge::Bottom => self.edge_weights[1],
+            SpawnEdge::Left => self.edge_weights[2],
+            SpawnEdge::Right => self.edge_weights[3]
+        }
+    }
+
+    //picks one of the edges a wave is allowed to use
+    pub fn pick_edge(&self, edges : &[SpawnEdge], rng : &mut impl Rng) -> SpawnEdge{
+        let total : f32 = edges.iter().map(|edge| self.edge_weight(*edge)).sum();
+        let mut roll = rng.gen::<f32>() * total;
+        for edge in edges.iter(){
+            roll -= self.edge_weight(*edge);
+            if roll < 0.0{
+                return *edge;
+            }
+        }
+        edges[edges.len() - 1]
+    }
+}
+
+
+//Systems
+
+pub fn start_director(mut director : ResMut<Director>, difficulty : Res<Difficulty>){
+    *director = Director::new(*difficulty);
+}
+
+//where the player is and how the base is holding up
+#[derive(SystemParam)]
+pub struct Battlefield<'w, 's>{
+    player_query : Query<'w, 's, &'static Transform, With<Player>>,
+    base_query : Query<'w, 's, &'static Health, With<Base>>
+}
+
+pub fn update_director(mut director : ResMut<Director>, spawned_query : Query<(), Added<Enemy>>, mut death_event_reader : EventReader<DeathEvent>, battlefield : Battlefield, run_stats : Res<RunStats>, playfield : Res<Playfield>, simulation_clock : Res<SimulationClock>){
+    let Battlefield{player_query, base_query} = battlefield;
+    director.spawned += spawned_query.iter().count() as u32;
+    for death_event in death_event_reader.iter(){
+        if player_query.contains(death_event.entity){
+            director.player_deaths += 1;
+        }
+    }
+    let now = simulation_clock.now();
+    let elapsed = now - director.last_sample;
+    if elapsed < DIRECTOR_SAMPLE_INTERVAL{
+        return;
+    }
+    if let Ok(base_health) = base_query.get_single(){
+        let fraction = base_health.current as f32 / base_health.max as f32;
+        if let Some(previous) = director.base_health_at_sample{
+            director.base_trend = (director.base_trend + (fraction - previous) / elapsed) / 2.0;
+        }
+        director.base_health_at_sample = Some(fraction);
+    }
+    let performance = director.performance(run_stats.kills, run_stats.parts);
+    director.adapt(performance);
+    if let Ok(player_transform) = player_query.get_single(){
+        director.update_edge_weights(player_transform.translation, &playfield);
+    }
+    director.spawned = 0;
+    director.player_deaths = 0;
+    director.kills_at_sample = run_stats.kills;
+    director.parts_at_sample = run_stats.parts;
+    director.last_sample = now;
+}
@@ -1,12 +1,21 @@
-use std::{ f32::consts::PI, time::{Duration, Instant}};
+use std::f32::consts::PI;
 use rand::prelude::*;
-use bevy::{prelude::*, sprite::collide_aabb::collide};
+use serde::{Deserialize, Serialize};
+use bevy::{prelude::*, sprite::collide_aabb::collide, ecs::system::SystemParam};
 
 
 use crate::base::*;
 use crate::player::*;
 use crate::bullet::*;
-use super::{AppState, SimulationState};
+use crate::rng::GameRng;
+use crate::health::*;
+use crate::spatial::{EnemyGrid, SpatialIndexSet};
+use crate::game_time::SimulationClock;
+use crate::boss::BossState;
+use crate::enemy_projectile::spawn_enemy_projectile;
+use crate::steering::Steering;
+use crate::status::*;
+use super::{AppState, SimulationState, SoundEffect, RunStats};
 
 pub struct EnemyPlugin;
 
@@ -23,9 +32,10 @@ impl Plugin for EnemyPlugin{
         .add_systems(
             (
                 enemy_spawn_timer_ticker,
-                enemy_movement,
+                enemy_movement.before(SpatialIndexSet),
                 enemy_ability_timer,
-                deacon_behaviour
+                deacon_behaviour.after(SpatialIndexSet),
+                enemy_death.in_set(DeathSet)
             )
             .in_set(OnUpdate(AppState::Game))
             .in_set(OnUpdate(SimulationState::Running))
@@ -42,18 +52,20 @@ impl Plugin for EnemyPlugin{
 
 pub const ENEMY_SPAWN_COOLDOWN : f32 = 3.0;
 pub const ENEMY_ABILITY_CYCLE : f32 = 10.0;
+//how close an enemy has to be for a deacon to home in on it
+pub const DEACON_RANGE : f32 = 35.0;
+pub const DEACON_SHIELD : StatusEffect = StatusEffect::new(StatusKind::Shield, 10.0, 10.0);
+pub const DEACON_HASTE : StatusEffect = StatusEffect::new(StatusKind::Haste, 0.1, 10.0);
 pub const ENEMY_POSITIONS: [(f32, f32);4] = [(5.0, 0.0), (-5.0, 0.0), (0.0, 5.0), (0.0, -5.0)];
+//gunners stop this far from the base and shoot at whatever is in range
+pub const GUNNER_STANDOFF : f32 = 200.0;
+pub const GUNNER_RANGE : f32 = 260.0;
+pub const GUNNER_FIRE_COOLDOWN : f32 = 2.0;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct NeonateGestation{
     pub direction : Vec3,
-    pub spawn_time : Instant
-}
-
-impl NeonateGestation{
-    fn time_since_spawn(&self) -> Duration{
-        self.spawn_time.elapsed()
-    }
+    pub spawn_time : f32
 }
 
 
@@ -61,11 +73,13 @@ impl NeonateGestation{
 pub enum EnemyType {
     Pawn,
     Stinger,
-    Splitter{split_count : usize, instant: Instant, direction: Vec3},
+    Splitter{split_count : usize, spawn_time: f32, direction: Vec3},
     Rogue,
     Bishop,
     Propagator,
     Neonate(NeonateGestation),
+    Gunner{last_shot : f32},
+    Boss(BossState),
 }
 
 #[derive(Component)]
@@ -73,18 +87,118 @@ pub struct Deacon{
     pub speed : f32,
     pub size : Vec2,
     pub direction : Vec3,
-    pub instant : Instant
+    pub spawn_time : f32
 
 }
 
 #[derive(Component)]
 pub struct Enemy{
-    pub health : i64,
     pub variant : EnemyType,
     pub speed : f32,
     pub size : Vec2
 }
 
+//the enemies a wave can spawn, used by the wave script and the wave variants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EnemyKind {
+    Pawn,
+    Stinger,
+    Splitter,
+    Rogue,
+    Bishop,
+    Propagator,
+    Gunner,
+}
+
+impl EnemyKind{
+    pub fn health(&self) -> i64{
+        match self {
+            EnemyKind::Pawn => 50,
+            EnemyKind::Stinger => 50,
+            EnemyKind::Splitter => 100,
+            EnemyKind::Rogue => 200,
+            EnemyKind::Bishop => 300,
+            EnemyKind::Propagator => 500,
+            EnemyKind::Gunner => 80,
+        }
+    }
+
+    //for the wave banner
+    pub fn plural(&self) -> &'static str{
+        match self {
+            EnemyKind::Pawn => "Pawns",
+            EnemyKind::Stinger => "Stingers",
+            EnemyKind::Splitter => "Splitters",
+            EnemyKind::Rogue => "Rogues",
+            EnemyKind::Bishop => "Bishops",
+            EnemyKind::Propagator => "Propagators",
+            EnemyKind::Gunner => "Gunners",
+        }
+    }
+
+    //what an enemy takes out of the trickle spawner's budget
+    pub fn cost(&self) -> f32{
+        match self {
+            EnemyKind::Pawn => 1.0,
+            EnemyKind::Stinger => 2.0,
+            EnemyKind::Splitter => 3.0,
+            EnemyKind::Rogue => 3.0,
+            EnemyKind::Bishop => 5.0,
+            EnemyKind::Propagator => 8.0,
+            EnemyKind::Gunner => 4.0,
+        }
+    }
+
+    //sprite, scale and stats of an enemy spawned at spawn_time on the SimulationClock
+    pub fn template(&self, spawn_time : f32) -> (&'static str, f32, Enemy){
+        match self {
+            EnemyKind::Pawn => ("Sprites/pawn.png", 0.15, Enemy{variant : EnemyType::Pawn, speed : 25.0, size : Vec2::new(10.0, 10.0)}),
+            EnemyKind::Stinger => ("Sprites/stinger.png", 0.2, Enemy{variant : EnemyType::Stinger, speed : 40.0, size : Vec2::new(10.0, 10.0)}),
+            EnemyKind::Splitter => ("Sprites/splitter.png", 0.3, Enemy{variant : EnemyType::Splitter{split_count : 0, spawn_time, direction : Vec3::ZERO}, speed : 20.0, size : Vec2::new(15.0, 15.0)}),
+            EnemyKind::Rogue => ("Sprites/rogue.png", 0.3, Enemy{variant : EnemyType::Rogue, speed : 25.0, size : Vec2::new(15.0, 15.0)}),
+            EnemyKind::Bishop => ("Sprites/bishop.png", 0.4, Enemy{variant : EnemyType::Bishop, speed : 15.0, size : Vec2::new(20.0, 20.0)}),
+            EnemyKind::Propagator => ("Sprites/propogator.png", 0.5, Enemy{variant : EnemyType::Propagator, speed : 5.0, size : Vec2::new(25.0, 25.0)}),
+            EnemyKind::Gunner => ("Sprites/stinger.png", 0.3, Enemy{variant : EnemyType::Gunner{last_shot : spawn_time}, speed : 30.0, size : Vec2::new(15.0, 15.0)}),
+        }
+    }
+}
+
+pub fn spawn_enemy(commands: &mut Commands, asset_server : &Res<AssetServer>, kind : EnemyKind, translation : Vec3, spawn_time : f32) -> Entity{
+    let (texture, scale, enemy) = kind.template(spawn_time);
+    commands.spawn((
+        SpriteBundle{
+            transform : Transform{
+                translation,
+                scale: Vec3::splat(scale),
+                ..default()
+            },
+            texture : asset_server.load(texture),
+            ..default()
+        },
+        enemy,
+        Health::new(kind.health())
+    )).id()
+}
+
+//a scaled up version of a normal enemy, used for the optional wave boss
+pub fn spawn_boss_enemy(commands: &mut Commands, asset_server : &Res<AssetServer>, kind : EnemyKind, translation : Vec3, spawn_time : f32, health_multiplier : f32, size_multiplier : f32) -> Entity{
+    let (texture, scale, mut enemy) = kind.template(spawn_time);
+    enemy.size *= size_multiplier;
+    commands.spawn((
+        SpriteBundle{
+            transform : Transform{
+                translation,
+                scale: Vec3::splat(scale * size_multiplier),
+                ..default()
+            },
+            texture : asset_server.load(texture),
+            ..default()
+        },
+        enemy,
+        Health::new((kind.health() as f32 * health_multiplier) as i64)
+    )).id()
+}
+
 
 #[derive(Resource)]
 pub struct EnemySpawnCooldownTimer{
@@ -126,45 +240,63 @@ pub fn enemy_ability_timer(mut enemy_ability_timer : ResMut<EnemyAbilityTimer>,
     enemy_ability_timer.timer.tick(time.delta());
 }
 
-pub fn enemy_movement( mut enemy_query: Query<(&mut Transform, &mut Enemy), (With<Enemy>, Without<Base>, Without<Player>)>, time : Res<Time>, base_query: Query<&Transform, (With<Base>, Without<Player>, Without<Enemy>)>, player_query: Query<&Transform, (With<Player>, Without<Enemy>, Without<Base>)>, enemy_ability_timer : Res<EnemyAbilityTimer>, mut commands: Commands, asset_server : Res<AssetServer>){
-    
-    for (mut t, enemy) in enemy_query.iter_mut(){
+//what enemies need to spawn other enemies, deacons or shots
+#[derive(SystemParam)]
+pub struct EnemySpawner<'w, 's>{
+    commands : Commands<'w, 's>,
+    asset_server : Res<'w, AssetServer>,
+    game_rng : ResMut<'w, GameRng>,
+    simulation_clock : Res<'w, SimulationClock>
+}
+
+//each enemy type picks its own direction, the steering force keeps them from piling up
+//and status effects slow, stun or speed them up
+pub fn enemy_movement( mut enemy_query: Query<(&mut Transform, &mut Enemy, Option<&mut Steering>, Option<&StatusEffects>), (With<Enemy>, Without<Base>, Without<Player>)>, time : Res<Time>, base_query: Query<&Transform, (With<Base>, Without<Player>, Without<Enemy>)>, player_query: Query<&Transform, (With<Player>, Without<Enemy>, Without<Base>)>, enemy_ability_timer : Res<EnemyAbilityTimer>, spawner : EnemySpawner){
+    let EnemySpawner{mut commands, asset_server, mut game_rng, simulation_clock} = spawner;
+    for (mut t, mut enemy, steering, status_effects) in enemy_query.iter_mut(){
+        //stunned enemies don't move or use their abilities
+        if status_effects.is_some_and(|status_effects| status_effects.stunned()){
+            continue;
+        }
+        let speed = enemy.speed * status_effects.map_or(1.0, |status_effects| status_effects.speed_multiplier());
         let base_translation = base_query.get_single().unwrap().translation;
+        let steer = steering.as_ref().map_or(Vec3::ZERO, |steering| steering.force);
+        let start = t.translation;
         match enemy.variant{
             EnemyType::Pawn => {
-                let direction = (base_translation - t.translation).normalize() + Vec3::new(rand::thread_rng().gen_range(-0.4..=0.4),rand::thread_rng().gen_range(-0.4..=0.4),0.0, );
-                t.translation += direction* enemy.speed *time.delta_seconds();
+                let direction = (base_translation - t.translation).normalize() + steer;
+                t.translation += direction* speed *time.delta_seconds();
                 t.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x) - PI/2.0);
             },
             EnemyType::Stinger => {
-                let direction = (base_translation - t.translation).normalize() + Vec3::new(rand::thread_rng().gen_range(-0.5..=0.5),rand::thread_rng().gen_range(-0.5..=0.5),0.0, );
-                t.translation += direction* enemy.speed *time.delta_seconds();
+                let direction = (base_translation - t.translation).normalize() + steer;
+                t.translation += direction* speed *time.delta_seconds();
                 t.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x) - PI/2.0);
             },
-            EnemyType::Splitter{split_count : ct, instant : inst, direction : mut dir} => {
+            EnemyType::Splitter{split_count : ct, spawn_time, direction : mut dir} => {
                 match ct {
                     0 =>{
                         dir = Vec3::new(base_translation.x - t.translation.x, base_translation.y - t.translation.y, 0.0).normalize();
                     },
                     _ => {
-                        if inst.elapsed().as_secs() > 1{
+                        if simulation_clock.elapsed_since(spawn_time).as_secs() > 1{
                             dir = Vec3::new(base_translation.x - t.translation.x, base_translation.y - t.translation.y, 0.0).normalize();
                         }
                     } 
                 }
-                t.translation += dir * enemy.speed *time.delta_seconds();
+                t.translation += (dir + steer) * speed *time.delta_seconds();
                 t.rotation *= Quat::from_rotation_z(PI/135.0);
             },
             EnemyType::Rogue => {
                 if let Ok(player_transform) = player_query.get_single(){
-                    let direction = (player_transform.translation - t.translation).normalize();
-                    t.translation += direction* enemy.speed *time.delta_seconds();
+                    let direction = (player_transform.translation - t.translation).normalize() + steer;
+                    t.translation += direction* speed *time.delta_seconds();
                     t.rotation *= Quat::from_rotation_z(-PI/90.0);
     
                 }
                 else{
-                    let direction = (base_translation - t.translation).normalize();
-                    t.translation += direction* enemy.speed *time.delta_seconds();
+                    let direction = (base_translation - t.translation).normalize() + steer;
+                    t.translation += direction* speed *time.delta_seconds();
                     t.rotation *= Quat::from_rotation_z(PI/90.0);
                 }     
             },
@@ -181,13 +313,13 @@ pub fn enemy_movement( mut enemy_query: Query<(&mut Transform, &mut Enemy), (Wit
                                 texture : asset_server.load("Sprites/deacon.png"),
                                 ..default()
                             },
-                            //Enemy{health: 1, variant : EnemyType::Deacon(DeaconGestation{direction : Vec3::new(rand::thread_rng().gen_range(-1.0..1.0), rand::thread_rng().gen_range(-1.0..1.0), 0.0).normalize(), spawn_time : Instant::now()}), speed : 20.0, size : Vec2::new(10.0, 10.0)}
-                            Deacon{speed: 20.0, size : Vec2::new(10.0, 10.0), direction : Vec3::new(rand::thread_rng().gen_range(-1.0..1.0), rand::thread_rng().gen_range(-1.0..1.0), 0.0), instant : Instant::now()}
+                            //Enemy{health: 1, variant : EnemyType::Deacon(DeaconGestation{direction : Vec3::new(game_rng.movement.gen_range(-1.0..1.0), game_rng.movement.gen_range(-1.0..1.0), 0.0).normalize(), spawn_time : simulation_clock.now()}), speed : 20.0, size : Vec2::new(10.0, 10.0)}
+                            Deacon{speed: 20.0, size : Vec2::new(10.0, 10.0), direction : Vec3::new(game_rng.abilities.gen_range(-1.0..1.0), game_rng.abilities.gen_range(-1.0..1.0), 0.0), spawn_time : simulation_clock.now()}
                         ));
                     }
                 }
-                let direction = (base_translation - t.translation).normalize();
-                t.translation += direction* enemy.speed *time.delta_seconds();
+                let direction = (base_translation - t.translation).normalize() + steer;
+                t.translation += direction* speed *time.delta_seconds();
                 t.rotation *= Quat::from_rotation_z(-PI/90.0);
             },
             EnemyType::Propagator => {
@@ -203,25 +335,48 @@ pub fn enemy_movement( mut enemy_query: Query<(&mut Transform, &mut Enemy), (Wit
                                 texture : asset_server.load("Sprites/neonate.png"),
                                 ..default()
                             },
-                            Enemy{health: 50, variant : EnemyType::Neonate(NeonateGestation{direction : Vec3::new(positon.0, positon.1, 0.0).normalize(), spawn_time : Instant::now()}), speed : 20.0, size : Vec2::new(10.0, 10.0)}
+                            Enemy{variant : EnemyType::Neonate(NeonateGestation{direction : Vec3::new(positon.0, positon.1, 0.0).normalize(), spawn_time : simulation_clock.now()}), speed : 20.0, size : Vec2::new(10.0, 10.0)},
+                            Health::new(50)
                         ));
                     } 
                 }else{
-                let direction = (base_translation - t.translation).normalize();
-                t.translation += direction* enemy.speed *time.delta_seconds();
+                let direction = (base_translation - t.translation).normalize() + steer;
+                t.translation += direction* speed *time.delta_seconds();
                 t.rotation *= Quat::from_rotation_z(-PI/180.0);
                 }
             },
             EnemyType::Neonate(mut inner_struct) => {
-                if inner_struct.time_since_spawn().as_secs() > 1{
-                    let direction = (base_translation - t.translation).normalize() + Vec3::new(rand::thread_rng().gen_range(-0.5..=0.5),rand::thread_rng().gen_range(-0.5..=0.5),0.0, );
+                if simulation_clock.elapsed_since(inner_struct.spawn_time).as_secs() > 1{
+                    let direction = (base_translation - t.translation).normalize() + steer;
                     inner_struct.direction = direction;
                 }
                 let direction = Vec3::new(inner_struct.direction.x, inner_struct.direction.y, 0.0);
-                t.translation += direction* enemy.speed *time.delta_seconds();
+                t.translation += direction* speed *time.delta_seconds();
                 t.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x) - PI/2.0);
                 
-            }
+            },
+            EnemyType::Gunner{last_shot} => {
+                let to_base = Vec3::new(base_translation.x - t.translation.x, base_translation.y - t.translation.y, 0.0);
+                if to_base.length() > GUNNER_STANDOFF{
+                    t.translation += (to_base.normalize() + steer) * speed * time.delta_seconds();
+                }
+                //the player is shot at when it comes in range, the base otherwise
+                let target = player_query.get_single().ok()
+                    .map(|player_transform| player_transform.translation)
+                    .filter(|player_translation| player_translation.truncate().distance(t.translation.truncate()) <= GUNNER_RANGE)
+                    .unwrap_or(base_translation);
+                let aim = (target - t.translation).truncate().normalize_or_zero();
+                t.rotation = Quat::from_rotation_z(aim.y.atan2(aim.x) - PI/2.0);
+                if target.truncate().distance(t.translation.truncate()) <= GUNNER_RANGE && simulation_clock.elapsed_since(last_shot).as_secs_f32() >= GUNNER_FIRE_COOLDOWN{
+                    spawn_enemy_projectile(&mut commands, &asset_server, t.translation, aim, simulation_clock.now());
+                    enemy.variant = EnemyType::Gunner{last_shot : simulation_clock.now()};
+                }
+            },
+            //moved by boss_behaviour
+            EnemyType::Boss(_) => {}
+        }
+        if let Some(mut steering) = steering{
+            steering.heading = Vec3::new(t.translation.x - start.x, t.translation.y - start.y, 0.0).normalize_or_zero();
         }
     }
 
@@ -234,23 +389,34 @@ pub fn despawn_deacons(mut commands : Commands, deacon_query : Query<Entity, Wit
 
 }
 
-pub fn deacon_behaviour(mut commands: Commands, mut deacon_query : Query<(Entity, &mut Transform, &mut Deacon), (With<Deacon>, Without<Enemy>, Without<Bullet>)>, mut enemy_query: Query<(&Transform, &mut Enemy), (With<Enemy>, Without<Deacon>, Without<Bullet>)>, time : Res<Time>, bullet_query: Query<(Entity, &Transform, &Bullet), (With<Bullet>, Without<Enemy>, Without<Deacon>)>, asset_server : Res<AssetServer>, audio : Res<Audio>){
+//what a wandering deacon can run into
+#[derive(SystemParam)]
+pub struct DeaconContacts<'w, 's>{
+    enemy_grid : Res<'w, EnemyGrid>,
+    enemy_query : Query<'w, 's, &'static Enemy>,
+    bullet_query : Query<'w, 's, (Entity, &'static Transform, &'static Bullet), Without<Deacon>>
+}
+
+//a deacon merging into an enemy shields it and speeds it up for a while
+pub fn deacon_behaviour(mut commands: Commands, mut deacon_query : Query<(Entity, &mut Transform, &mut Deacon), (With<Deacon>, Without<Enemy>, Without<Bullet>)>, contacts : DeaconContacts, mut status_event_writer : EventWriter<StatusEvent>, time : Res<Time>, mut sound_effects : EventWriter<SoundEffect>, simulation_clock : Res<SimulationClock>){
+    let DeaconContacts{enemy_grid, enemy_query, bullet_query} = contacts;
     for (deacon_entity, mut deacon_transform, mut deacon) in deacon_query.iter_mut(){
-        if deacon.instant.elapsed().as_secs() > 1{
+        if simulation_clock.elapsed_since(deacon.spawn_time).as_secs() > 1{
             deacon.speed = 0.0;
-            for (enemy_transform, mut enemy) in enemy_query.iter_mut(){
+            for nearby_enemy in enemy_grid.within(deacon_transform.translation, DEACON_RANGE){
+                let Ok(enemy) = enemy_query.get(nearby_enemy.entity) else {
+                    continue;
+                };
                 if enemy.variant != EnemyType::Bishop{
-                    let distance = enemy_transform.translation.distance(deacon_transform.translation);
-                    if distance <= 35.0{
-                        deacon.speed = 10.0;
-                        deacon.direction = Vec3::new(enemy_transform.translation.x - deacon_transform.translation.x, enemy_transform.translation.y - deacon_transform.translation.y, 0.0);
-                    if collide(enemy_transform.translation, enemy.size, deacon_transform.translation, deacon.size).is_some(){
-                        let sound_effect = asset_server.load("Audio/doorClose_000.ogg");
-                        audio.play(sound_effect);
+                    deacon.speed = 10.0;
+                    deacon.direction = Vec3::new(nearby_enemy.translation.x - deacon_transform.translation.x, nearby_enemy.translation.y - deacon_transform.translation.y, 0.0);
+                    if collide(nearby_enemy.translation, enemy.size, deacon_transform.translation, deacon.size).is_some(){
+                        let sound_effect = SoundEffect("Audio/doorClose_000.ogg");
+                        sound_effects.send(sound_effect);
                         commands.entity(deacon_entity).despawn();
-                        enemy.health += 10;
-                        enemy.speed += 1.0;
-                    }}
+                        status_event_writer.send(StatusEvent{target : nearby_enemy.entity, effect : DEACON_SHIELD});
+                        status_event_writer.send(StatusEvent{target : nearby_enemy.entity, effect : DEACON_HASTE});
+                    }
                 }
                 
             }
@@ -260,11 +426,52 @@ pub fn deacon_behaviour(mut commands: Commands, mut deacon_query : Query<(Entity
         deacon_transform.rotation *= Quat::from_rotation_z(-PI/180.0);
         for (bullet_entity, bullet_transform, bullet) in bullet_query.iter(){
             if collide(bullet_transform.translation, bullet.size, deacon_transform.translation, deacon.size).is_some(){
-                let sound_effect = asset_server.load("Audio/doorOpen_001.ogg");
-                audio.play(sound_effect);
+                let sound_effect = SoundEffect("Audio/doorOpen_001.ogg");
+                sound_effects.send(sound_effect);
                 commands.entity(bullet_entity).despawn();
                 commands.entity(deacon_entity).despawn();
             }
         }
     }
-}
\ No newline at end of file
+}
+
+//splits, sounds and the kill count for enemies shot down, enemies used up by ramming something just disappear
+//the drops are rolled by drop_loot
+pub fn enemy_death(mut death_event_reader : EventReader<DeathEvent>, enemy_query: Query<(&Transform, &Enemy)>, mut sound_effects : EventWriter<SoundEffect>, mut run_stats : ResMut<RunStats>, spawner : EnemySpawner){
+    let EnemySpawner{mut commands, asset_server, mut game_rng, simulation_clock} = spawner;
+    for death_event in death_event_reader.iter(){
+        let enemy_entity = death_event.entity;
+        if let Ok((enemy_transform, enemy)) = enemy_query.get(enemy_entity){
+            commands.entity(enemy_entity).despawn();
+            if death_event.damage_type == DamageType::Collision{
+                continue;
+            }
+            run_stats.kills += 1;
+            match enemy.variant {
+                EnemyType::Splitter{split_count, ..} if split_count < 2 => {
+                    //big splitters split into two smaller, faster ones
+                    let (health, scale, speed, size) = if split_count == 0 {(150, 0.25, 20.0, 10.0)} else {(100, 0.20, 25.0, 5.0)};
+                    for _ in 0..2{
+                        commands.spawn((SpriteBundle{
+                            transform : Transform{
+                                translation : enemy_transform.translation + Vec3::new(10.0, 10.0, 0.0),
+                                scale : Vec3::splat(scale),
+                                ..default()
+                            },
+                            texture : asset_server.load("Sprites/splitter.png"),
+                            ..default()
+
+                        },
+                        Enemy{variant: EnemyType::Splitter { split_count: split_count + 1, spawn_time: simulation_clock.now(), direction : Vec3::new(game_rng.abilities.gen_range(-1.0..=1.0), game_rng.abilities.gen_range(-1.0..=1.0), 0.0)}, speed, size : Vec2::splat(size)},
+                        Health::new(health)
+                    ));
+                    }
+                    sound_effects.send(SoundEffect("Audio/lowFrequency_explosion_001.ogg"));
+                },
+                _ => {
+                    sound_effects.send(SoundEffect("Audio/explosionCrunch_004.ogg"));
+                }
+            }
+        }
+    }
+}
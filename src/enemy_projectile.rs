@@ -0,0 +1,93 @@
+use std::f32::consts::PI;
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+
+use crate::base::Base;
+use crate::player::Player;
+use crate::health::*;
+use crate::game_time::SimulationClock;
+use super::{AppState, SimulationState, SoundEffect, Playfield};
+
+pub struct EnemyProjectilePlugin;
+
+impl Plugin for EnemyProjectilePlugin{
+    fn build(&self, app: &mut App) {
+        app
+
+        //While in game appstate
+        .add_systems(
+            (
+                update_enemy_projectiles.before(DamageSet),
+                enemy_projectile_hit.in_set(DamageSet)
+            )
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
+
+        //On exit Game Appstate
+        .add_system(despawn_enemy_projectiles.in_schedule(OnExit(AppState::Game)));
+    }
+}
+
+
+
+pub const ENEMY_PROJECTILE_SPEED : f32 = 180.0;
+pub const ENEMY_PROJECTILE_DAMAGE : i64 = 15;
+pub const ENEMY_PROJECTILE_SIZE : f32 = 8.0;
+pub const ENEMY_PROJECTILE_LIFETIME : f32 = 3.0;
+
+//fired by enemies, only hits the player and the base and flies through other enemies and deacons
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct EnemyProjectile{
+    pub direction : Vec2,
+    pub speed : f32,
+    pub size : Vec2,
+    pub damage : i64,
+    pub spawn_time : f32
+}
+
+pub fn spawn_enemy_projectile(commands: &mut Commands, asset_server : &Res<AssetServer>, translation : Vec3, direction : Vec2, spawn_time : f32) -> Entity{
+    commands.spawn((
+        SpriteBundle{
+            transform : Transform{
+                translation,
+                rotation : Quat::from_rotation_z(direction.y.atan2(direction.x) - PI/2.0),
+                scale : Vec3::splat(0.5)
+            },
+            texture : asset_server.load("Sprites/spaceMissiles_037.png"),
+            ..default()
+        },
+        EnemyProjectile{direction, speed : ENEMY_PROJECTILE_SPEED, size : Vec2::splat(ENEMY_PROJECTILE_SIZE), damage : ENEMY_PROJECTILE_DAMAGE, spawn_time}
+    )).id()
+}
+
+pub fn update_enemy_projectiles(mut commands: Commands, mut projectile_query : Query<(Entity, &mut Transform, &EnemyProjectile)>, time : Res<Time>, playfield : Res<Playfield>, simulation_clock : Res<SimulationClock>){
+    for (entity, mut transform, projectile) in projectile_query.iter_mut(){
+        transform.translation += projectile.direction.extend(0.0) * projectile.speed * time.delta_seconds();
+        let translation = transform.translation;
+        let outside = translation.x < 0.0 || translation.x > playfield.width || translation.y < 0.0 || translation.y > playfield.height;
+        if outside || simulation_clock.elapsed_since(projectile.spawn_time).as_secs_f32() > ENEMY_PROJECTILE_LIFETIME{
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+//each shot hits the player or the base once and is gone
+pub fn enemy_projectile_hit(mut commands: Commands, projectile_query : Query<(Entity, &Transform, &EnemyProjectile)>, player_query : Query<(Entity, &Transform, &Player)>, base_query : Query<(Entity, &Transform, &Base)>, mut damage_event_writer : EventWriter<DamageEvent>, mut sound_effects : EventWriter<SoundEffect>){
+    let targets : Vec<(Entity, Vec3, Vec2)> = player_query.iter().map(|(entity, transform, player)| (entity, transform.translation, player.size))
+        .chain(base_query.iter().map(|(entity, transform, base)| (entity, transform.translation, base.size)))
+        .collect();
+    for (projectile_entity, projectile_transform, projectile) in projectile_query.iter(){
+        let hit = targets.iter().find(|(_, translation, size)| collide(projectile_transform.translation, projectile.size, *translation, *size).is_some());
+        if let Some((target, _, _)) = hit{
+            damage_event_writer.send(DamageEvent{source : Some(projectile_entity), target : *target, amount : projectile.damage, damage_type : DamageType::Bullet});
+            sound_effects.send(SoundEffect("Audio/impactMining_002.ogg"));
+            commands.entity(projectile_entity).despawn();
+        }
+    }
+}
+
+pub fn despawn_enemy_projectiles(mut commands: Commands, projectile_query : Query<Entity, With<EnemyProjectile>>){
+    for i in projectile_query.iter(){
+        commands.entity(i).despawn()
+    }
+}
@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+
+use crate::base::Base;
+use crate::player::Player;
+use crate::enemy::*;
+use crate::health::*;
+use crate::status::*;
+use crate::game_time::SimulationClock;
+use crate::settings::Settings;
+use crate::repetitive_code::get_damage_number_text_style;
+use super::{AppState, SimulationState};
+
+//Damage numbers, hit flashes and screen shake, everything here is only for show.
+//The knockback from hits is applied by player_shoot_enemy, what is turned on comes from the Settings.
+pub struct FeedbackPlugin;
+
+impl Plugin for FeedbackPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        //Resources
+        .init_resource::<ScreenShake>()
+
+        //While in game appstate
+        .add_systems(
+            (
+                spawn_damage_numbers,
+                //after the status tint so the flash isn't painted over the frame it starts
+                start_hit_flashes.after(tint_status_effects),
+                //after the new hits so a flash that is topped up isn't ended
+                end_hit_flashes.after(start_hit_flashes),
+                start_screen_shake
+            )
+            .after(DeathSet)
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
+        .add_systems(
+            (
+                float_damage_numbers,
+                shake_camera.after(start_screen_shake)
+            )
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
+
+        //On exit Game Appstate
+        .add_systems(
+            (
+                despawn_damage_numbers,
+                stop_screen_shake
+            ).in_schedule(OnExit(AppState::Game))
+        );
+    }
+}
+
+
+
+pub const DAMAGE_NUMBER_TIME : f32 = 0.8;
+//pixels a second
+pub const DAMAGE_NUMBER_RISE : f32 = 40.0;
+//above the enemies and their health bars
+pub const DAMAGE_NUMBER_Z : f32 = 6.0;
+pub const HIT_FLASH_TIME : f32 = 0.1;
+pub const HIT_FLASH_COLOR : Color = Color::rgb(1.0, 0.3, 0.3);
+//on top of the weapon's own knockback, for every bullet that hits
+pub const HIT_KNOCKBACK : f32 = 2.0;
+//a hit on the base shakes harder than one on the player
+pub const BASE_HIT_TRAUMA : f32 = 0.6;
+pub const PLAYER_HIT_TRAUMA : f32 = 0.4;
+//trauma lost a second
+pub const SCREEN_SHAKE_DECAY : f32 = 2.0;
+//pixels at full trauma
+pub const MAX_SCREEN_SHAKE : f32 = 12.0;
+
+
+//from 0 still to 1 shaking as hard as it gets, the camera is moved by offset
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Default)]
+pub struct ScreenShake{
+    pub trauma : f32,
+    pub offset : Vec2
+}
+
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct DamageNumber{
+    pub spawn_time : f32
+}
+
+//on the enemy until its sprite gets its own color back
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct HitFlash{
+    pub until : f32
+}
+
+
+pub fn damage_number_color(damage_type : DamageType) -> Color{
+    match damage_type {
+        DamageType::Explosion => Color::rgb(1.0, 0.75, 0.3),
+        DamageType::Burn => BURN_COLOR,
+        _ => Color::WHITE
+    }
+}
+
+//the screen doesn't need real noise, two sines that never line up look random enough
+fn shake_offset(trauma : f32, now : f32) -> Vec2{
+    Vec2::new((now * 47.0).sin(), (now * 53.0).cos()) * MAX_SCREEN_SHAKE * trauma * trauma
+}
+
+
+//Systems
+
+//one number per enemy and damage type a frame, so a spread shot doesn't stack a pile of them
+pub fn spawn_damage_numbers(mut commands : Commands, mut damage_event_reader : EventReader<DamageEvent>, settings : Res<Settings>, enemy_query : Query<(&Transform, &Enemy)>, asset_server : Res<AssetServer>, simulation_clock : Res<SimulationClock>){
+    if !settings.damage_numbers{
+        return;
+    }
+    let mut hits : Vec<(Entity, DamageType, i64)> = Vec::new();
+    for damage_event in damage_event_reader.iter(){
+        if damage_event.damage_type == DamageType::Collision || !enemy_query.contains(damage_event.target){
+            continue;
+        }
+        match hits.iter_mut().find(|(target, damage_type, _)| *target == damage_event.target && *damage_type == damage_event.damage_type) {
+            Some((_, _, amount)) => *amount += damage_event.amount,
+            None => hits.push((damage_event.target, damage_event.damage_type, damage_event.amount))
+        }
+    }
+    for (target, damage_type, amount) in hits{
+        let Ok((enemy_transform, enemy)) = enemy_query.get(target) else {
+            continue;
+        };
+        let mut text_style = get_damage_number_text_style(&asset_server);
+        text_style.color = damage_number_color(damage_type);
+        commands.spawn((
+            Text2dBundle{
+                text : Text::from_section(amount.to_string(), text_style).with_alignment(TextAlignment::Center),
+                transform : Transform::from_xyz(enemy_transform.translation.x, enemy_transform.translation.y + enemy.size.y / 2.0, DAMAGE_NUMBER_Z),
+                ..default()
+            },
+            DamageNumber{spawn_time : simulation_clock.now()}
+        ));
+    }
+}
+
+//rise and fade out
+pub fn float_damage_numbers(mut commands : Commands, mut damage_number_query : Query<(Entity, &DamageNumber, &mut Transform, &mut Text)>, time : Res<Time>, simulation_clock : Res<SimulationClock>){
+    for (damage_number_entity, damage_number, mut transform, mut text) in damage_number_query.iter_mut(){
+        let age = simulation_clock.now() - damage_number.spawn_time;
+        if age >= DAMAGE_NUMBER_TIME{
+            commands.entity(damage_number_entity).despawn();
+            continue;
+        }
+        transform.translation.y += DAMAGE_NUMBER_RISE * time.delta_seconds();
+        for section in text.sections.iter_mut(){
+            section.style.color.set_a(1.0 - age / DAMAGE_NUMBER_TIME);
+        }
+    }
+}
+
+pub fn start_hit_flashes(mut commands : Commands, mut damage_event_reader : EventReader<DamageEvent>, settings : Res<Settings>, mut enemy_query : Query<(&Health, Option<&mut HitFlash>, &mut Sprite), With<Enemy>>, simulation_clock : Res<SimulationClock>){
+    if !settings.hit_flash{
+        return;
+    }
+    let until = simulation_clock.now() + HIT_FLASH_TIME;
+    for damage_event in damage_event_reader.iter(){
+        if damage_event.damage_type == DamageType::Collision{
+            continue;
+        }
+        let Ok((health, hit_flash, mut sprite)) = enemy_query.get_mut(damage_event.target) else {
+            continue;
+        };
+        //the dead are despawned by enemy_death this frame
+        if health.is_dead(){
+            continue;
+        }
+        sprite.color = HIT_FLASH_COLOR;
+        match hit_flash {
+            Some(mut hit_flash) => hit_flash.until = until,
+            None => {commands.entity(damage_event.target).insert(HitFlash{until});}
+        }
+    }
+}
+
+type FlashingEnemyQuery<'w, 's> = Query<'w, 's, (Entity, &'static HitFlash, &'static Enemy, &'static Health, Option<&'static StatusEffects>, &'static mut Sprite)>;
+
+//the sprite goes back to the color of its status effects
+pub fn end_hit_flashes(mut commands : Commands, settings : Res<Settings>, mut enemy_query : FlashingEnemyQuery, simulation_clock : Res<SimulationClock>){
+    for (enemy_entity, hit_flash, enemy, health, status_effects, mut sprite) in enemy_query.iter_mut(){
+        if health.is_dead() || (settings.hit_flash && simulation_clock.now() < hit_flash.until){
+            continue;
+        }
+        sprite.color = enemy_tint(enemy, status_effects);
+        commands.entity(enemy_entity).remove::<HitFlash>();
+    }
+}
+
+//enemies ramming the base or the player, the hits from enemy_hit_base and enemy_hit_player
+pub fn start_screen_shake(mut damage_event_reader : EventReader<DamageEvent>, settings : Res<Settings>, mut screen_shake : ResMut<ScreenShake>, base_query : Query<(), With<Base>>, player_query : Query<(), With<Player>>){
+    for damage_event in damage_event_reader.iter(){
+        if !settings.screen_shake || damage_event.damage_type != DamageType::Collision{
+            continue;
+        }
+        let trauma = if base_query.contains(damage_event.target) {BASE_HIT_TRAUMA} else if player_query.contains(damage_event.target) {PLAYER_HIT_TRAUMA} else {continue};
+        screen_shake.trauma = (screen_shake.trauma + trauma).min(1.0);
+    }
+}
+
+//moves the camera by the change in offset, so it ends up where it started once the shake is over
+pub fn shake_camera(mut screen_shake : ResMut<ScreenShake>, settings : Res<Settings>, mut camera_query : Query<&mut Transform, With<Camera2d>>, time : Res<Time>, simulation_clock : Res<SimulationClock>){
+    if screen_shake.trauma <= 0.0 && screen_shake.offset == Vec2::ZERO{
+        return;
+    }
+    screen_shake.trauma = if settings.screen_shake {(screen_shake.trauma - SCREEN_SHAKE_DECAY * time.delta_seconds()).max(0.0)} else {0.0};
+    let offset = shake_offset(screen_shake.trauma, simulation_clock.now());
+    for mut camera_transform in camera_query.iter_mut(){
+        camera_transform.translation += (offset - screen_shake.offset).extend(0.0);
+    }
+    screen_shake.offset = offset;
+}
+
+pub fn despawn_damage_numbers(mut commands : Commands, damage_number_query : Query<Entity, With<DamageNumber>>){
+    for damage_number_entity in damage_number_query.iter(){
+        commands.entity(damage_number_entity).despawn();
+    }
+}
+
+pub fn stop_screen_shake(mut screen_shake : ResMut<ScreenShake>, mut camera_query : Query<&mut Transform, With<Camera2d>>){
+    for mut camera_transform in camera_query.iter_mut(){
+        camera_transform.translation -= screen_shake.offset.extend(0.0);
+    }
+    *screen_shake = ScreenShake::default();
+}
@@ -1,5 +1,5 @@
 use bevy::{prelude::*, app::AppExit};
-use crate::{repetitive_code::*, FinalScore};
+use crate::{repetitive_code::*, rng::GameRng, high_scores::*, FinalScore};
 use super::AppState;
 
 
@@ -48,8 +48,8 @@ pub struct CommentText {}
 
 //Layout
 
-pub fn spawn_game_over_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
-    build_game_over_menu(&mut commands, &asset_server);
+pub fn spawn_game_over_menu(mut commands: Commands, asset_server: Res<AssetServer>, game_rng: Res<GameRng>, high_scores: Res<HighScores>) {
+    build_game_over_menu(&mut commands, &asset_server, game_rng.seed, &high_scores);
 }
 
 pub fn despawn_game_over_menu(mut commands: Commands, game_over_menu_query: Query<Entity, With<GameOverMenu>>,) {
@@ -58,7 +58,7 @@ pub fn despawn_game_over_menu(mut commands: Commands, game_over_menu_query: Quer
     }
 }
 
-pub fn build_game_over_menu(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+pub fn build_game_over_menu(commands: &mut Commands, asset_server: &Res<AssetServer>, seed: u64, high_scores: &HighScores) -> Entity {
     let game_over_menu_entity = commands
         .spawn((
             NodeBundle {
@@ -106,6 +106,18 @@ pub fn build_game_over_menu(commands: &mut Commands, asset_server: &Res<AssetSer
                         },
                         FinalScoreText {}
                     ));
+                    // Seed Text
+                    parent.spawn(TextBundle {
+                        text: Text {
+                            sections: vec![TextSection::new(
+                                format!("Seed: {}", seed),
+                                get_button_text_style(asset_server)
+                            )],
+                            alignment: TextAlignment::Center,
+                            ..default()
+                        },
+                        ..default()
+                    });
                     // Restart Button
                     parent
                         .spawn((
@@ -179,6 +191,8 @@ pub fn build_game_over_menu(commands: &mut Commands, asset_server: &Res<AssetSer
                             });
                         });
                 });
+            // High Scores
+            build_leaderboard(parent, asset_server, high_scores);
         })
         .id();
 
@@ -261,25 +275,32 @@ pub fn interact_with_quit_button(
 
 pub fn update_final_score_text(final_score : Res<FinalScore>, mut text_query: Query<&mut Text, With<FinalScoreText>>,
 ) {
-    let time_alive = final_score.scores.last().unwrap().1;
-    let base_level = final_score.scores.last().unwrap().0;
+    //the score is only pushed once update_final_score has seen the GameOver event
+    let Some(&(base_level, time_alive)) = final_score.scores.last() else {
+        return;
+    };
     for mut text in text_query.iter_mut(){
         text.sections[0].value = format!("Time alive: {}\nBase level: {}", time_alive, base_level);
     }
 }
 
 pub fn final_score_checker(final_score : Res<FinalScore>, mut text_query: Query<&mut Text, With<CommentText>>){
-    let time_survived = final_score.scores.last().unwrap().1;
+    let Some(&(_, time_survived)) = final_score.scores.last() else {
+        return;
+    };
     for mut text in text_query.iter_mut(){
-        let result = match time_survived {
-            time_survived if time_survived < 120 => "Better luck next time",
-            time_survived if time_survived < 240 => "Nice",
-            time_survived if time_survived < 600 => "Fantastic score",
-            _ => "You are awesome" 
-        };
-        text.sections[0].value = format!("{}", result)
+        text.sections[0].value = score_comment(time_survived).to_string()
 
     }
     
     
 }
+
+pub fn score_comment(time_survived : u64) -> &'static str{
+    match time_survived {
+        time_survived if time_survived < 120 => "Better luck next time",
+        time_survived if time_survived < 240 => "Nice",
+        time_survived if time_survived < 600 => "Fantastic score",
+        _ => "You are awesome" 
+    }
+}
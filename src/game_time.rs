@@ -0,0 +1,99 @@
+use std::time::Duration;
+use bevy::{prelude::*, time::Stopwatch};
+
+use crate::controls::{ActionState, Action};
+use super::{AppState, SimulationState};
+
+pub struct GameTimePlugin;
+
+impl Plugin for GameTimePlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<SimulationClock>()
+        .init_resource::<GameSpeed>()
+
+        //every run starts at zero
+        .add_system(reset_simulation_clock.in_schedule(OnEnter(AppState::Game)))
+
+        //ticked before the gameplay systems so they all see the same time in a frame
+        .add_system(
+            tick_simulation_clock
+            .in_base_set(CoreSet::PreUpdate)
+            .run_if(in_state(AppState::Game))
+            .run_if(in_state(SimulationState::Running))
+        )
+        .add_system(change_game_speed.run_if(in_state(AppState::Game)))
+        .add_system(apply_game_speed)
+
+        //the menus and the next run start at normal speed
+        .add_system(reset_game_speed.in_schedule(OnExit(AppState::Game)));
+    }
+}
+
+
+
+pub const MIN_GAME_SPEED : f32 = 0.5;
+pub const MAX_GAME_SPEED : f32 = 4.0;
+
+
+//Time spent in the current run while it was running, scaled by the game speed.
+//Lifetimes are measured against this instead of the wall clock so they stop while paused.
+#[derive(Resource, Default)]
+pub struct SimulationClock{
+    pub stopwatch : Stopwatch
+}
+
+impl SimulationClock{
+    //seconds since the run started, stored by entities as their spawn time
+    pub fn now(&self) -> f32{
+        self.stopwatch.elapsed_secs()
+    }
+
+    pub fn elapsed_since(&self, spawn_time : f32) -> Duration{
+        Duration::from_secs_f32((self.now() - spawn_time).max(0.0))
+    }
+}
+
+//multiplies Time, so every timer, lifetime and movement speeds up together
+#[derive(Resource)]
+pub struct GameSpeed{
+    pub speed : f32
+}
+
+impl Default for GameSpeed{
+    fn default() -> GameSpeed {
+        GameSpeed{speed : 1.0}
+    }
+}
+
+
+//Systems
+
+pub fn reset_simulation_clock(mut simulation_clock : ResMut<SimulationClock>){
+    simulation_clock.stopwatch.reset();
+}
+
+pub fn tick_simulation_clock(mut simulation_clock : ResMut<SimulationClock>, time : Res<Time>){
+    simulation_clock.stopwatch.tick(time.delta());
+}
+
+//+ and - by default, double or halve the game speed
+pub fn change_game_speed(action_state : Res<ActionState>, mut game_speed : ResMut<GameSpeed>){
+    if action_state.just_pressed(Action::SpeedUp){
+        game_speed.speed = (game_speed.speed * 2.0).min(MAX_GAME_SPEED);
+    }
+    if action_state.just_pressed(Action::SlowDown){
+        game_speed.speed = (game_speed.speed / 2.0).max(MIN_GAME_SPEED);
+    }
+}
+
+pub fn apply_game_speed(game_speed : Res<GameSpeed>, mut time : ResMut<Time>){
+    if game_speed.is_changed(){
+        time.set_relative_speed(game_speed.speed.clamp(MIN_GAME_SPEED, MAX_GAME_SPEED));
+    }
+}
+
+pub fn reset_game_speed(mut game_speed : ResMut<GameSpeed>, mut time : ResMut<Time>){
+    *game_speed = GameSpeed::default();
+    time.set_relative_speed(game_speed.speed);
+}
@@ -0,0 +1,265 @@
+use std::{path::{Path, PathBuf}, time::{Duration, Instant}};
+use bevy::{prelude::*, app::AppExit, input::InputPlugin, time::{TimeSystem, TimeUpdateStrategy}, window::ReceivedCharacter, reflect::TypeUuid, utils::BoxedFuture};
+use bevy::asset::{AssetIo, AssetIoError, AssetLoader, FileAssetIo, LoadContext, LoadedAsset, Metadata};
+
+use crate::{player::*, enemy::*, base::*, part::*, wave::*, director::Difficulty, rng::GameRng, game_time::SimulationClock, benchmark::BenchmarkPlugin};
+use super::{AppState, SimulationState, GameOver, GamePlugin, Playfield, RunStats};
+
+//Runs the game loop without a window, GPU or audio device for balance runs, e.g.
+//cargo run --release -- --headless --runs 100 --waves 50 --seed 42 --difficulty hard
+//--benchmark 1000 measures the frame time with that many enemies instead, see BenchmarkPlugin
+pub struct HeadlessPlugin{
+    pub config : HeadlessConfig
+}
+
+impl Plugin for HeadlessPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .add_plugin(HeadlessGamePlugin{step : self.config.step})
+
+        //Resources
+        .insert_resource(self.config.clone())
+        .insert_resource(Playfield{width : self.config.width, height : self.config.height})
+        .insert_resource(self.config.difficulty)
+        .init_resource::<HeadlessRuns>()
+
+        //Startup
+        .add_startup_system(start_headless_run)
+
+        //Systems
+        .add_system(autopilot.in_set(PlayerInputSet).run_if(in_state(AppState::Game)))
+        .add_system(keep_simulation_running.run_if(in_state(SimulationState::Paused)))
+        .add_system(end_run_at_wave_limit.run_if(in_state(AppState::Game)))
+        .add_system(report_headless_run)
+
+        //a run ended, start the next one or stop
+        .add_system(start_headless_run.in_schedule(OnEnter(AppState::GameOver)));
+
+        if let Some(enemies) = self.config.benchmark{
+            app.add_plugin(BenchmarkPlugin{enemies});
+        }
+    }
+}
+
+
+//The game without a window, GPU or audio device, on a clock that moves a fixed step every update.
+//Used by the headless runs and the tests.
+pub struct HeadlessGamePlugin{
+    pub step : f32
+}
+
+impl Plugin for HeadlessGamePlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .add_plugins(MinimalPlugins)
+        //sprites, fonts and sounds are never read or decoded, only the wave script is
+        .insert_resource(AssetServer::new(HeadlessAssetIo{inner : FileAssetIo::new(AssetPlugin::default().asset_folder, false)}))
+        .add_plugin(AssetPlugin::default())
+        .add_asset::<SkippedAsset>()
+        .init_asset_loader::<SkippedAssetLoader>()
+        .add_plugin(InputPlugin)
+        .add_event::<ReceivedCharacter>()
+        .add_plugin(GamePlugin)
+
+        //Resources
+        .insert_resource(HeadlessClock{step : Duration::from_secs_f32(self.step)})
+        .insert_resource(TimeUpdateStrategy::ManualInstant(Instant::now()))
+
+        //Systems
+        .add_system(advance_headless_clock.in_base_set(CoreSet::First).before(TimeSystem));
+    }
+}
+
+
+pub const HEADLESS_STEP : f32 = 1.0/60.0;
+pub const AUTOPILOT_GUARD_DISTANCE : f32 = 80.0;
+pub const SKIPPED_ASSET_EXTENSIONS : [&str; 3] = ["png", "ogg", "ttf"];
+
+
+#[derive(Resource, Debug, Clone)]
+pub struct HeadlessConfig{
+    pub runs : usize,
+    pub max_waves : usize,
+    pub seed : Option<u64>,
+    pub difficulty : Difficulty,
+    //simulated seconds per update
+    pub step : f32,
+    pub width : f32,
+    pub height : f32,
+    //enemies spawned for a frame time benchmark
+    pub benchmark : Option<usize>
+}
+
+impl Default for HeadlessConfig{
+    fn default() -> HeadlessConfig {
+        let playfield = Playfield::default();
+        HeadlessConfig{runs : 1, max_waves : 100, seed : None, difficulty : Difficulty::default(), step : HEADLESS_STEP, width : playfield.width, height : playfield.height, benchmark : None}
+    }
+}
+
+impl HeadlessConfig{
+    //None unless the game was started with --headless
+    pub fn from_args() -> Option<HeadlessConfig>{
+        let args: Vec<String> = std::env::args().collect();
+        if !args.iter().any(|arg| arg == "--headless"){
+            return None;
+        }
+        let mut config = HeadlessConfig::default();
+        for pair in args.windows(2){
+            match pair[0].as_str() {
+                "--runs" => if let Ok(runs) = pair[1].parse() {config.runs = runs},
+                "--waves" => if let Ok(max_waves) = pair[1].parse() {config.max_waves = max_waves},
+                "--seed" => if let Ok(seed) = pair[1].parse() {config.seed = Some(seed)},
+                "--difficulty" => if let Some(difficulty) = Difficulty::from_name(&pair[1]) {config.difficulty = difficulty},
+                "--step" => if let Ok(step) = pair[1].parse() {config.step = step},
+                "--benchmark" => if let Ok(enemies) = pair[1].parse() {config.benchmark = Some(enemies)},
+                _ => {}
+            }
+        }
+        Some(config)
+    }
+}
+
+#[derive(Resource)]
+pub struct HeadlessClock{
+    pub step : Duration
+}
+
+#[derive(Resource, Default)]
+pub struct HeadlessRuns{
+    pub started : usize,
+    pub run_over : bool
+}
+
+
+//Assets
+
+//hands out empty files for skipped assets and reads everything else from disk
+pub struct HeadlessAssetIo{
+    pub inner : FileAssetIo
+}
+
+fn is_skipped_asset(path : &Path) -> bool{
+    path.extension().and_then(|extension| extension.to_str()).is_some_and(|extension| SKIPPED_ASSET_EXTENSIONS.contains(&extension))
+}
+
+impl AssetIo for HeadlessAssetIo{
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        if is_skipped_asset(path){
+            Box::pin(async { Ok(Vec::new()) })
+        }
+        else{
+            self.inner.load_path(path)
+        }
+    }
+
+    fn read_directory(&self, path: &Path) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        self.inner.read_directory(path)
+    }
+
+    fn get_metadata(&self, path: &Path) -> Result<Metadata, AssetIoError> {
+        self.inner.get_metadata(path)
+    }
+
+    fn watch_path_for_changes(&self, _to_watch: &Path, _to_reload: Option<PathBuf>) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+}
+
+//stands in for every skipped asset so the asset server sees it as loaded and doesn't try again
+#[derive(TypeUuid)]
+#[uuid = "d5d3c8fa-6e09-48e2-b305-5f00a101705d"]
+pub struct SkippedAsset;
+
+#[derive(Default)]
+pub struct SkippedAssetLoader;
+
+impl AssetLoader for SkippedAssetLoader{
+    fn load<'a>(&'a self, _bytes: &'a [u8], load_context: &'a mut LoadContext) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            load_context.set_default_asset(LoadedAsset::new(SkippedAsset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &SKIPPED_ASSET_EXTENSIONS
+    }
+}
+
+
+//Systems
+
+pub fn start_headless_run(config : Res<HeadlessConfig>, mut runs : ResMut<HeadlessRuns>, mut game_rng : ResMut<GameRng>, mut next_app_state : ResMut<NextState<AppState>>, mut app_exit_event_writer : EventWriter<AppExit>){
+    if runs.started >= config.runs{
+        app_exit_event_writer.send(AppExit);
+        return;
+    }
+    //a fixed seed makes the whole batch reproducible, each run still gets its own
+    if let Some(seed) = config.seed{
+        game_rng.seed = seed.wrapping_add(runs.started as u64);
+        game_rng.fixed_seed = true;
+    }
+    runs.started += 1;
+    runs.run_over = false;
+    next_app_state.set(AppState::Game);
+}
+
+//time moves a fixed step every update instead of following the wall clock
+pub fn advance_headless_clock(clock : Res<HeadlessClock>, mut time_update_strategy : ResMut<TimeUpdateStrategy>){
+    if let TimeUpdateStrategy::ManualInstant(instant) = *time_update_strategy{
+        *time_update_strategy = TimeUpdateStrategy::ManualInstant(instant + clock.step);
+    }
+}
+
+//nobody is there to press space
+pub fn keep_simulation_running(mut next_simulation_state : ResMut<NextState<SimulationState>>){
+    next_simulation_state.set(SimulationState::Running);
+}
+
+pub fn end_run_at_wave_limit(config : Res<HeadlessConfig>, runs : Res<HeadlessRuns>, wave_timer : Res<WaveTimer>, base_query : Query<&Base>, simulation_clock : Res<SimulationClock>, mut game_over_event_writer : EventWriter<GameOver>){
+    if wave_timer.wave > config.max_waves && !runs.run_over{
+        if let Ok(base) = base_query.get_single(){
+            game_over_event_writer.send(GameOver{time_alive : simulation_clock.elapsed_since(base.spawn_time).as_secs(), base_level : base.level, wave : wave_timer.wave});
+        }
+    }
+}
+
+pub fn report_headless_run(mut game_over_event_reader : EventReader<GameOver>, mut runs : ResMut<HeadlessRuns>, game_rng : Res<GameRng>, run_stats : Res<RunStats>, simulation_clock : Res<SimulationClock>){
+    for game_over in game_over_event_reader.iter(){
+        if runs.run_over{
+            continue;
+        }
+        runs.run_over = true;
+        println!("run {} | seed {} | waves {} | base level {} | kills {} | simulated time {:.0}s", runs.started, game_rng.seed, game_over.wave, game_over.base_level, run_stats.kills, simulation_clock.now());
+    }
+}
+
+//stays near the base, picks up parts and shoots whatever is closest to the base
+pub fn autopilot(mut player_input : ResMut<PlayerInput>, player_query : Query<&Transform, With<Player>>, enemy_query : Query<&Transform, With<Enemy>>, part_query : Query<&Transform, With<Part>>, base_query : Query<&Transform, With<Base>>){
+    if let (Ok(player_transform), Ok(base_transform)) = (player_query.get_single(), base_query.get_single()){
+        let player_translation = player_transform.translation;
+        let base_translation = base_transform.translation;
+
+        let threat = enemy_query.iter()
+            .map(|enemy_transform| enemy_transform.translation)
+            .min_by(|a, b| a.distance(base_translation).total_cmp(&b.distance(base_translation)));
+        let nearest_part = part_query.iter()
+            .map(|part_transform| part_transform.translation)
+            .min_by(|a, b| a.distance(player_translation).total_cmp(&b.distance(player_translation)));
+
+        let destination = match (nearest_part, threat) {
+            (Some(part_translation), _) => part_translation,
+            (None, Some(enemy_translation)) => base_translation + (enemy_translation - base_translation).normalize_or_zero() * AUTOPILOT_GUARD_DISTANCE,
+            (None, None) => base_translation
+        };
+        let to_destination = destination - player_translation;
+        player_input.movement = if to_destination.length() > 5.0 {to_destination} else {Vec3::ZERO};
+        player_input.aim = threat.map(|enemy_translation| enemy_translation.truncate());
+        player_input.fire = threat.is_some();
+    }
+}
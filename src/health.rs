@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::status::StatusEffects;
+use super::{AppState, SimulationState};
+
+//Collisions and weapons send DamageEvents in DamageSet, apply_damage subtracts them from Health
+//and the systems in DeathSet react to the DeathEvents it sends, all in the same frame.
+#[derive(SystemSet, Debug, Hash, Clone, PartialEq, Eq)]
+pub struct DamageSet;
+
+#[derive(SystemSet, Debug, Hash, Clone, PartialEq, Eq)]
+pub struct DeathSet;
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        //Ordering
+        .configure_set(DamageSet.before(DeathSet))
+
+        //Events
+        .add_event::<DamageEvent>()
+        .add_event::<DeathEvent>()
+
+        .add_system(
+            apply_damage
+            .after(DamageSet)
+            .before(DeathSet)
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        );
+    }
+}
+
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Health{
+    pub current : i64,
+    pub max : i64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageType{
+    Bullet,
+    //splash damage, e.g. from turret missiles
+    Explosion,
+    //running into something, the enemy is used up by the hit
+    Collision,
+    //damage over time from a status effect
+    Burn
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DamageEvent{
+    //what dealt the damage, e.g. the bullet or the enemy that rammed the target
+    pub source : Option<Entity>,
+    pub target : Entity,
+    pub amount : i64,
+    pub damage_type : DamageType
+}
+
+//sent once when an entity's health drops to zero, the entity is still there for the handlers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeathEvent{
+    pub entity : Entity,
+    pub damage_type : DamageType
+}
+
+
+impl Health{
+    pub fn new(max : i64) -> Health{
+        Health{current : max, max}
+    }
+
+    pub fn is_dead(&self) -> bool{
+        self.current <= 0
+    }
+
+    //level ups raise the maximum and heal fully
+    pub fn reset(&mut self, max : i64){
+        *self = Health::new(max);
+    }
+}
+
+
+//Systems
+
+//shields soak up everything but collisions, which use the rammer up whatever happens
+pub fn apply_damage(mut damage_event_reader : EventReader<DamageEvent>, mut health_query : Query<(&mut Health, Option<&mut StatusEffects>)>, mut death_event_writer : EventWriter<DeathEvent>){
+    for damage_event in damage_event_reader.iter(){
+        //something killed earlier in the frame doesn't get to hit back
+        if damage_event.source.and_then(|source| health_query.get(source).ok()).is_some_and(|(health, _)| health.is_dead()){
+            continue;
+        }
+        if let Ok((mut health, status_effects)) = health_query.get_mut(damage_event.target){
+            //already dead this frame, don't die twice
+            if health.is_dead(){
+                continue;
+            }
+            let amount = match status_effects {
+                Some(mut status_effects) if damage_event.damage_type != DamageType::Collision => status_effects.absorb(damage_event.amount),
+                _ => damage_event.amount
+            };
+            health.current -= amount;
+            if health.is_dead(){
+                death_event_writer.send(DeathEvent{entity : damage_event.target, damage_type : damage_event.damage_type});
+            }
+        }
+    }
+}
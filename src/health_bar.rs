@@ -0,0 +1,135 @@
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::enemy::*;
+use crate::health::*;
+use crate::settings::Settings;
+use super::{AppState, SimulationState};
+
+//Floating health bars over damaged enemies, in the world rather than the HUD so they follow the enemy.
+//The boss has its own bar at the top of the screen.
+pub struct HealthBarPlugin;
+
+impl Plugin for HealthBarPlugin{
+    fn build(&self, app: &mut App) {
+        app
+
+        //While in game appstate
+        .add_systems(
+            (
+                add_enemy_health_bars,
+                update_enemy_health_bars
+            )
+            .chain()
+            .after(DeathSet)
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
+
+        //On exit Game Appstate
+        .add_system(despawn_enemy_health_bars.in_schedule(OnExit(AppState::Game)));
+    }
+}
+
+
+
+pub const ENEMY_HEALTH_BAR_HEIGHT : f32 = 3.0;
+pub const ENEMY_HEALTH_BAR_MIN_WIDTH : f32 = 16.0;
+//between the top of the enemy and the bar
+pub const ENEMY_HEALTH_BAR_GAP : f32 = 6.0;
+//above the enemies and bullets
+pub const ENEMY_HEALTH_BAR_Z : f32 = 5.0;
+pub const ENEMY_HEALTH_BAR_BACKGROUND : Color = Color::rgba(0.1, 0.1, 0.1, 0.7);
+pub const ENEMY_HEALTH_BAR_COLOR : Color = Color::rgb(0.85, 0.2, 0.2);
+
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct EnemyHealthBar{
+    pub enemy : Entity,
+    pub width : f32
+}
+
+#[derive(Component)]
+pub struct EnemyHealthBarFill;
+
+//on the enemy while it has a bar
+#[derive(Component)]
+pub struct HasHealthBar;
+
+
+pub fn enemy_health_bar_width(enemy : &Enemy) -> f32{
+    (enemy.size.x * 1.5).max(ENEMY_HEALTH_BAR_MIN_WIDTH)
+}
+
+pub fn enemy_health_bar_translation(enemy_translation : Vec3, enemy : &Enemy) -> Vec3{
+    Vec3::new(enemy_translation.x, enemy_translation.y + enemy.size.y / 2.0 + ENEMY_HEALTH_BAR_GAP, ENEMY_HEALTH_BAR_Z)
+}
+
+fn health_fraction(health : &Health) -> f32{
+    (health.current as f32 / health.max.max(1) as f32).clamp(0.0, 1.0)
+}
+
+
+//Systems
+
+type DamagedEnemyQuery<'w, 's> = Query<'w, 's, (Entity, &'static Transform, &'static Enemy, &'static Health), (Changed<Health>, Without<HasHealthBar>)>;
+
+//an enemy gets its bar the first time it is hurt
+pub fn add_enemy_health_bars(mut commands : Commands, settings : Res<Settings>, enemy_query : DamagedEnemyQuery){
+    if !settings.enemy_health_bars{
+        return;
+    }
+    for (enemy_entity, enemy_transform, enemy, health) in enemy_query.iter(){
+        //the dead are despawned by enemy_death this frame
+        if health.is_dead() || health.current >= health.max || matches!(enemy.variant, EnemyType::Boss(_)){
+            continue;
+        }
+        let width = enemy_health_bar_width(enemy);
+        commands.spawn((
+            SpriteBundle{
+                sprite : Sprite{color : ENEMY_HEALTH_BAR_BACKGROUND, custom_size : Some(Vec2::new(width, ENEMY_HEALTH_BAR_HEIGHT)), ..default()},
+                transform : Transform::from_translation(enemy_health_bar_translation(enemy_transform.translation, enemy)),
+                ..default()
+            },
+            EnemyHealthBar{enemy : enemy_entity, width}
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpriteBundle{
+                    sprite : Sprite{color : ENEMY_HEALTH_BAR_COLOR, custom_size : Some(Vec2::new(width * health_fraction(health), ENEMY_HEALTH_BAR_HEIGHT)), anchor : Anchor::CenterLeft, ..default()},
+                    transform : Transform::from_xyz(-width / 2.0, 0.0, 0.1),
+                    ..default()
+                },
+                EnemyHealthBarFill
+            ));
+        });
+        commands.entity(enemy_entity).insert(HasHealthBar);
+    }
+}
+
+//follows the enemy and goes with it, or with the setting
+pub fn update_enemy_health_bars(mut commands : Commands, settings : Res<Settings>, mut bar_query : Query<(Entity, &EnemyHealthBar, &mut Transform, &Children)>, mut fill_query : Query<&mut Sprite, With<EnemyHealthBarFill>>, enemy_query : Query<(&Transform, &Enemy, &Health), Without<EnemyHealthBar>>){
+    for (bar_entity, bar, mut bar_transform, children) in bar_query.iter_mut(){
+        let enemy = enemy_query.get(bar.enemy).ok().filter(|(_, _, health)| !health.is_dead());
+        let Some((enemy_transform, enemy, health)) = enemy else {
+            commands.entity(bar_entity).despawn_recursive();
+            continue;
+        };
+        if !settings.enemy_health_bars{
+            commands.entity(bar_entity).despawn_recursive();
+            commands.entity(bar.enemy).remove::<HasHealthBar>();
+            continue;
+        }
+        bar_transform.translation = enemy_health_bar_translation(enemy_transform.translation, enemy);
+        for child in children.iter(){
+            if let Ok(mut sprite) = fill_query.get_mut(*child){
+                sprite.custom_size = Some(Vec2::new(bar.width * health_fraction(health), ENEMY_HEALTH_BAR_HEIGHT));
+            }
+        }
+    }
+}
+
+pub fn despawn_enemy_health_bars(mut commands : Commands, bar_query : Query<Entity, With<EnemyHealthBar>>){
+    for bar_entity in bar_query.iter(){
+        commands.entity(bar_entity).despawn_recursive();
+    }
+}
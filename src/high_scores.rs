@@ -0,0 +1,191 @@
+use std::{fs, path::PathBuf, time::{SystemTime, UNIX_EPOCH}};
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
+
+use crate::{repetitive_code::*, rng::GameRng};
+use super::{AppState, GameOver, RunStats, handle_game_over};
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<HighScores>()
+
+        //recorded before the game over menu is built so it can show the new entry
+        .add_system(record_high_score.before(handle_game_over))
+
+        .add_system(clear_new_record.in_schedule(OnEnter(AppState::Game)));
+    }
+}
+
+
+
+pub const HIGH_SCORES_PATH : &str = "highscores.ron";
+pub const MAX_HIGH_SCORES : usize = 10;
+pub const NEW_RECORD_COLOR : Color = Color::rgb(1.0, 0.85, 0.2);
+
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScore{
+    //seconds since the unix epoch
+    pub date : u64,
+    pub seed : u64,
+    pub waves : usize,
+    pub base_level : i64,
+    pub time_alive : u64,
+    pub kills : u64
+}
+
+//the best runs, best first
+//only read from and written to disk when path is set, so headless runs and tests don't touch the player's table
+#[derive(Resource, Default)]
+pub struct HighScores{
+    pub scores : Vec<HighScore>,
+    pub path : Option<PathBuf>,
+    //where the last run landed in the table
+    pub new_record : Option<usize>
+}
+
+#[derive(Component)]
+pub struct Leaderboard;
+
+
+impl HighScore{
+    //more waves is better, then a higher base level, then more kills
+    fn beats(&self, other : &HighScore) -> bool{
+        (self.waves, self.base_level, self.kills) > (other.waves, other.base_level, other.kills)
+    }
+}
+
+impl HighScores{
+    pub fn load(path : PathBuf) -> HighScores{
+        let scores = match fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+                println!("Couldn't read {}: {}", path.display(), error);
+                Vec::new()
+            }),
+            Err(_) => Vec::new()
+        };
+        HighScores{scores, path : Some(path), new_record : None}
+    }
+
+    pub fn save(&self){
+        if let Some(path) = &self.path{
+            let result = ron::ser::to_string_pretty(&self.scores, ron::ser::PrettyConfig::default())
+                .map_err(|error| error.to_string())
+                .and_then(|contents| fs::write(path, contents).map_err(|error| error.to_string()));
+            if let Err(error) = result{
+                println!("Couldn't save {}: {}", path.display(), error);
+            }
+        }
+    }
+
+    //returns the rank the score got, None if it didn't make the table
+    pub fn insert(&mut self, high_score : HighScore) -> Option<usize>{
+        let rank = self.scores.iter().position(|other| high_score.beats(other)).unwrap_or(self.scores.len());
+        if rank >= MAX_HIGH_SCORES{
+            return None;
+        }
+        self.scores.insert(rank, high_score);
+        self.scores.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+pub fn unix_time() -> u64{
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs())
+}
+
+//year-month-day from seconds since the unix epoch
+pub fn format_date(unix_seconds : u64) -> String{
+    let days = (unix_seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {month_index + 3} else {month_index - 9};
+    let year = year_of_era + era * 400 + if month <= 2 {1} else {0};
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+
+//Layout
+
+//top ten table, shared by the main menu and the game over menu
+pub fn build_leaderboard(parent : &mut ChildBuilder, asset_server : &Res<AssetServer>, high_scores : &HighScores){
+    let text_style = get_leaderboard_text_style(asset_server);
+    let mut sections = vec![TextSection::new("High scores\n", get_button_text_style(asset_server))];
+    if high_scores.scores.is_empty(){
+        sections.push(TextSection::new("No runs yet", text_style.clone()));
+    }
+    for (rank, high_score) in high_scores.scores.iter().enumerate(){
+        let mut style = text_style.clone();
+        if high_scores.new_record == Some(rank){
+            style.color = NEW_RECORD_COLOR;
+        }
+        sections.push(TextSection::new(
+            format!("\n{:>2}. {}  wave {:>3}  level {}  {:>4} kills  seed {}", rank + 1, format_date(high_score.date), high_score.waves, high_score.base_level, high_score.kills, high_score.seed),
+            style
+        ));
+    }
+    parent.spawn((
+        NodeBundle{
+            style : LEADERBOARD_STYLE,
+            background_color : BACKGROUND_COLOR.into(),
+            ..default()
+        },
+        Leaderboard{}
+    ))
+    .with_children(|parent|{
+        if let Some(rank) = high_scores.new_record{
+            let mut style = get_button_text_style(asset_server);
+            style.color = NEW_RECORD_COLOR;
+            parent.spawn(TextBundle{
+                text : Text{
+                    sections : vec![TextSection::new(format!("New record! #{}", rank + 1), style)],
+                    alignment : TextAlignment::Center,
+                    ..default()
+                },
+                ..default()
+            });
+        }
+        parent.spawn(TextBundle{
+            text : Text{
+                sections,
+                alignment : TextAlignment::Left,
+                ..default()
+            },
+            ..default()
+        });
+    });
+}
+
+
+//Systems
+
+pub fn load_high_scores(mut high_scores : ResMut<HighScores>){
+    *high_scores = HighScores::load(PathBuf::from(HIGH_SCORES_PATH));
+}
+
+pub fn record_high_score(mut game_over_event_reader : EventReader<GameOver>, mut high_scores : ResMut<HighScores>, game_rng : Res<GameRng>, run_stats : Res<RunStats>){
+    for game_over in game_over_event_reader.iter(){
+        high_scores.new_record = high_scores.insert(HighScore{
+            date : unix_time(),
+            seed : game_rng.seed,
+            waves : game_over.wave,
+            base_level : game_over.base_level,
+            time_alive : game_over.time_alive,
+            kills : run_stats.kills
+        });
+        if high_scores.new_record.is_some(){
+            high_scores.save();
+        }
+    }
+}
+
+pub fn clear_new_record(mut high_scores : ResMut<HighScores>){
+    high_scores.new_record = None;
+}
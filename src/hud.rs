@@ -1,6 +1,6 @@
 use bevy::prelude::*;
-use crate::{base::*, part::PartTier, repetitive_code::*};
-use super::{AppState,SimulationState};
+use crate::{base::*, settings::Settings, part::PartTier, repetitive_code::*, building::{PartInventory, BuildMode}, player::Player, weapon::*, enemy::*, health::Health, boss::BossState, wave::*, game_time::SimulationClock};
+use super::{AppState, SimulationState, RunStats};
 use std::f32::consts::PI;
 
 pub struct HudPlugin;
@@ -8,8 +8,6 @@ pub struct HudPlugin;
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
         app
-
-
             // OnEnter Systems
             .add_system(spawn_hud.in_schedule(OnEnter(AppState::Game)))
 
@@ -18,7 +16,15 @@ impl Plugin for HudPlugin {
             .add_systems(
                 (
                     update_parts,
-                    parts_gui
+                    parts_gui,
+                    update_build_hud,
+                    update_weapon_hud,
+                    update_boss_bar,
+                    update_wave_hud,
+                    show_wave_banner,
+                    update_wave_summary,
+                    update_health_hud,
+                    update_low_base_warning
                 )
                 .in_set(OnUpdate(AppState::Game))
                 .in_set(OnUpdate(SimulationState::Running))
@@ -32,6 +38,16 @@ impl Plugin for HudPlugin {
 
 
 
+//how long the wave banner stays up
+pub const WAVE_BANNER_TIME : f32 = 3.0;
+//the summary of a wave shows for the last seconds before the next one
+pub const WAVE_SUMMARY_TIME : f32 = 5.0;
+//the base health bar turns red and the warning flashes below this part of the maximum
+pub const LOW_BASE_HEALTH : f32 = 0.25;
+//flashes of the low base warning per second
+pub const LOW_BASE_WARNING_RATE : f32 = 2.0;
+
+
 //Components
 
 #[derive(Component)]
@@ -40,11 +56,66 @@ pub struct HUD{}
 #[derive(Component)]
 pub struct PartIcon{}
 
+#[derive(Component)]
+pub struct BuildHudText{}
+
+#[derive(Component)]
+pub struct WeaponHudText{}
+
+#[derive(Component)]
+pub struct BossBar{}
+
+#[derive(Component)]
+pub struct BossBarFill{}
+
+#[derive(Component)]
+pub struct BossBarText{}
+
+#[derive(Component)]
+pub struct WaveHudText{}
+
+#[derive(Component)]
+pub struct WaveCountdownFill{}
+
+#[derive(Component)]
+pub struct WaveBanner{
+    pub shown_at : f32
+}
+
+#[derive(Component)]
+pub struct WaveSummaryPanel{}
+
+#[derive(Component)]
+pub struct WaveSummaryText{}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthBarOwner{
+    Base,
+    Player
+}
+
+#[derive(Component)]
+pub struct HealthBarFill(pub HealthBarOwner);
+
+#[derive(Component)]
+pub struct HealthBarText(pub HealthBarOwner);
+
+#[derive(Component)]
+pub struct LowBaseWarning{}
+
 //Layout
 
 
-pub fn spawn_hud(mut commands: Commands) {
+pub fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
     build_hud(&mut commands);
+    build_build_hud(&mut commands, &asset_server);
+    build_weapon_hud(&mut commands, &asset_server);
+    build_boss_bar(&mut commands, &asset_server);
+    build_wave_hud(&mut commands, &asset_server);
+    build_wave_banner(&mut commands, &asset_server);
+    build_wave_summary(&mut commands, &asset_server);
+    build_health_hud(&mut commands, &asset_server);
+    build_low_base_warning(&mut commands, &asset_server);
 }
 
 pub fn despawn_hud(mut commands: Commands, hud_query: Query<Entity, With<HUD>>) {
@@ -80,7 +151,234 @@ pub fn build_hud(commands: &mut Commands) -> Entity {
     hud_entity
 }
 
+pub fn build_build_hud(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    commands.spawn((
+        TextBundle{
+            style : BUILD_HUD_STYLE,
+            text : Text::from_section("", get_build_hud_text_style(asset_server)),
+            ..default()
+        },
+        BuildHudText{},
+        HUD{}
+    ))
+    .id()
+}
+
+pub fn build_weapon_hud(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    commands.spawn((
+        TextBundle{
+            style : WEAPON_HUD_STYLE,
+            text : Text::from_section("", get_build_hud_text_style(asset_server)),
+            ..default()
+        },
+        WeaponHudText{},
+        HUD{}
+    ))
+    .id()
+}
+
 
+pub fn build_boss_bar(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    commands.spawn((
+        NodeBundle{
+            style : BOSS_BAR_STYLE,
+            visibility : Visibility::Hidden,
+            ..default()
+        },
+        BossBar{},
+        HUD{}
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            TextBundle{
+                text : Text::from_section("", get_build_hud_text_style(asset_server)),
+                ..default()
+            },
+            BossBarText{}
+        ));
+        parent.spawn(
+            NodeBundle{
+                style : BOSS_BAR_TRACK_STYLE,
+                background_color : BACKGROUND_COLOR.into(),
+                ..default()
+            }
+        )
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle{
+                    style : BOSS_BAR_FILL_STYLE,
+                    background_color : BOSS_BAR_FILL_COLOR.into(),
+                    ..default()
+                },
+                BossBarFill{}
+            ));
+        });
+    })
+    .id()
+}
+
+//the wave number and a bar filling up until the next wave
+pub fn build_wave_hud(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    commands.spawn((
+        NodeBundle{
+            style : WAVE_HUD_STYLE,
+            ..default()
+        },
+        HUD{}
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            TextBundle{
+                text : Text::from_section("", get_build_hud_text_style(asset_server)),
+                ..default()
+            },
+            WaveHudText{}
+        ));
+        parent.spawn(
+            NodeBundle{
+                style : WAVE_COUNTDOWN_TRACK_STYLE,
+                background_color : BACKGROUND_COLOR.into(),
+                ..default()
+            }
+        )
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle{
+                    style : WAVE_COUNTDOWN_FILL_STYLE,
+                    background_color : WAVE_COUNTDOWN_FILL_COLOR.into(),
+                    ..default()
+                },
+                WaveCountdownFill{}
+            ));
+        });
+    })
+    .id()
+}
+
+pub fn build_wave_banner(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    commands.spawn((
+        NodeBundle{
+            style : WAVE_BANNER_STYLE,
+            visibility : Visibility::Hidden,
+            ..default()
+        },
+        WaveBanner{shown_at : 0.0},
+        HUD{}
+    ))
+    .with_children(|parent| {
+        parent.spawn(TextBundle{
+            text : Text::from_section("", get_wave_banner_text_style(asset_server)).with_alignment(TextAlignment::Center),
+            ..default()
+        });
+    })
+    .id()
+}
+
+pub fn build_wave_summary(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    commands.spawn((
+        NodeBundle{
+            style : WAVE_SUMMARY_STYLE,
+            background_color : BACKGROUND_COLOR.into(),
+            visibility : Visibility::Hidden,
+            ..default()
+        },
+        WaveSummaryPanel{},
+        HUD{}
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            TextBundle{
+                text : Text::from_section("", get_build_hud_text_style(asset_server)),
+                ..default()
+            },
+            WaveSummaryText{}
+        ));
+    })
+    .id()
+}
+
+//the base and player health, under the wave countdown
+pub fn build_health_hud(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    commands.spawn((
+        NodeBundle{
+            style : HEALTH_HUD_STYLE,
+            ..default()
+        },
+        HUD{}
+    ))
+    .with_children(|parent| {
+        for owner in [HealthBarOwner::Base, HealthBarOwner::Player]{
+            parent.spawn((
+                TextBundle{
+                    text : Text::from_section("", get_build_hud_text_style(asset_server)),
+                    ..default()
+                },
+                HealthBarText(owner)
+            ));
+            parent.spawn(
+                NodeBundle{
+                    style : HEALTH_BAR_TRACK_STYLE,
+                    background_color : BACKGROUND_COLOR.into(),
+                    ..default()
+                }
+            )
+            .with_children(|parent| {
+                parent.spawn((
+                    NodeBundle{
+                        style : HEALTH_BAR_FILL_STYLE,
+                        background_color : HEALTH_BAR_FILL_COLOR.into(),
+                        ..default()
+                    },
+                    HealthBarFill(owner)
+                ));
+            });
+        }
+    })
+    .id()
+}
+
+pub fn build_low_base_warning(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    commands.spawn((
+        NodeBundle{
+            style : LOW_BASE_WARNING_STYLE,
+            visibility : Visibility::Hidden,
+            ..default()
+        },
+        LowBaseWarning{},
+        HUD{}
+    ))
+    .with_children(|parent| {
+        parent.spawn(TextBundle{
+            text : Text::from_section("Base critical!", TextStyle{color : LOW_HEALTH_COLOR, ..get_wave_banner_text_style(asset_server)}),
+            ..default()
+        });
+    })
+    .id()
+}
+
+//"Wave 15 incoming" and the enemies it brings for the first time
+pub fn wave_banner_text(wave_started : &WaveStarted) -> String{
+    let mut value = format!("Wave {} incoming", wave_started.wave);
+    if let Some((last, rest)) = wave_started.new_kinds.split_last(){
+        let names : Vec<&str> = rest.iter().map(|kind| kind.plural()).collect();
+        let names = if names.is_empty() {last.plural().to_string()} else {format!("{} and {}", names.join(", "), last.plural())};
+        value.push_str(&format!("\n{} have appeared", names));
+    }
+    if wave_started.boss{
+        value.push_str("\nA boss approaches");
+    }
+    value
+}
+
+pub fn wave_summary_text(wave_stats : &WaveStats, run_stats : &RunStats) -> String{
+    format!(
+        "Wave {} summary\nKills  {}\nParts collected  {}\nBase damage taken  {}",
+        wave_stats.wave,
+        run_stats.kills.saturating_sub(wave_stats.kills_at_start),
+        run_stats.parts.saturating_sub(wave_stats.parts_at_start),
+        wave_stats.base_damage
+    )
+}
 
 
 
@@ -110,4 +408,140 @@ pub fn parts_gui(mut part_icon_query : Query<(&mut BackgroundColor, &mut Transfo
             part_icon_transform.rotation *= Quat::from_rotation_z(PI/360.0)
        }
     }
-}
\ No newline at end of file
+}
+
+pub fn update_build_hud(mut build_hud_query : Query<&mut Text, With<BuildHudText>>, part_inventory : Res<PartInventory>, build_mode : Res<BuildMode>){
+    for mut text in build_hud_query.iter_mut(){
+        let mut value = format!("Parts  Blue {}  Red {}  Green {}", part_inventory.blue, part_inventory.red, part_inventory.green);
+        if build_mode.active{
+            let cost = build_mode.selected.cost();
+            let affordable = if part_inventory.can_afford(&cost) {""} else {"  not enough parts"};
+            value.push_str(&format!("\nBuilding {} ({}){}", build_mode.selected.label(), cost.describe(), affordable));
+        }
+        text.sections[0].value = value;
+    }
+}
+
+//the selected weapon, how far the charge shot is charged and how many weapons the base has unlocked
+pub fn update_weapon_hud(mut weapon_hud_query : Query<&mut Text, With<WeaponHudText>>, player_query : Query<&Weapon, With<Player>>, base_query : Query<&Base>){
+    let (Ok(weapon), Ok(base)) = (player_query.get_single(), base_query.get_single()) else {
+        return;
+    };
+    let unlocked = WeaponKind::ALL.iter().filter(|kind| kind.unlocked(base.level)).count();
+    let mut value = format!("Weapon  {}  ({}/{} unlocked)", weapon.selected.label(), unlocked, WeaponKind::ALL.len());
+    if weapon.charge > 0.0{
+        value.push_str(&format!("  charge {:.0}%", weapon.charge / CHARGE_TIME * 100.0));
+    }
+    for mut text in weapon_hud_query.iter_mut(){
+        text.sections[0].value = value.clone();
+    }
+}
+
+//the health and phase of the boss, hidden while there is none
+pub fn update_boss_bar(enemy_query : Query<(&Enemy, &Health)>, mut boss_bar_query : Query<&mut Visibility, With<BossBar>>, mut boss_bar_fill_query : Query<&mut Style, With<BossBarFill>>, mut boss_bar_text_query : Query<&mut Text, With<BossBarText>>){
+    let boss = enemy_query.iter().find_map(|(enemy, health)| match enemy.variant {
+        EnemyType::Boss(state) => Some((state, health)),
+        _ => None
+    });
+    for mut visibility in boss_bar_query.iter_mut(){
+        *visibility = if boss.is_some() {Visibility::Inherited} else {Visibility::Hidden};
+    }
+    let Some((BossState{phase, ..}, health)) = boss else {
+        return;
+    };
+    for mut style in boss_bar_fill_query.iter_mut(){
+        style.size.width = Val::Percent((health.current as f32 / health.max.max(1) as f32 * 100.0).clamp(0.0, 100.0));
+    }
+    for mut text in boss_bar_text_query.iter_mut(){
+        text.sections[0].value = format!("Boss  phase {}/3  {}", phase.number(), phase.label());
+    }
+}
+
+pub fn update_wave_hud(wave_timer : Res<WaveTimer>, mut wave_hud_query : Query<&mut Text, With<WaveHudText>>, mut countdown_fill_query : Query<&mut Style, With<WaveCountdownFill>>){
+    let remaining = wave_timer.timer.remaining_secs().ceil();
+    let value = if wave_timer.wave == 0 {format!("First wave in {:.0}s", remaining)} else {format!("Wave {}  next in {:.0}s", wave_timer.wave, remaining)};
+    for mut text in wave_hud_query.iter_mut(){
+        text.sections[0].value = value.clone();
+    }
+    for mut style in countdown_fill_query.iter_mut(){
+        style.size.width = Val::Percent(wave_timer.timer.percent() * 100.0);
+    }
+}
+
+//shown when a wave starts and hidden WAVE_BANNER_TIME seconds later
+pub fn show_wave_banner(mut wave_started_reader : EventReader<WaveStarted>, mut banner_query : Query<(&mut WaveBanner, &mut Visibility, &Children)>, mut text_query : Query<&mut Text>, simulation_clock : Res<SimulationClock>){
+    let now = simulation_clock.now();
+    let wave_started = wave_started_reader.iter().last();
+    for (mut banner, mut visibility, children) in banner_query.iter_mut(){
+        if let Some(wave_started) = wave_started{
+            banner.shown_at = now;
+            *visibility = Visibility::Inherited;
+            for child in children.iter(){
+                if let Ok(mut text) = text_query.get_mut(*child){
+                    text.sections[0].value = wave_banner_text(wave_started);
+                }
+            }
+        }
+        else if *visibility != Visibility::Hidden && now - banner.shown_at > WAVE_BANNER_TIME{
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+//what the last wave came to, in the run up to the next one
+pub fn update_wave_summary(settings : Res<Settings>, wave_timer : Res<WaveTimer>, wave_stats : Res<WaveStats>, run_stats : Res<RunStats>, mut panel_query : Query<&mut Visibility, With<WaveSummaryPanel>>, mut summary_text_query : Query<&mut Text, With<WaveSummaryText>>){
+    let shown = settings.wave_summary && wave_stats.wave > 0 && wave_timer.timer.remaining_secs() < WAVE_SUMMARY_TIME;
+    for mut visibility in panel_query.iter_mut(){
+        let wanted = if shown {Visibility::Inherited} else {Visibility::Hidden};
+        if *visibility != wanted{
+            *visibility = wanted;
+        }
+    }
+    if shown{
+        for mut text in summary_text_query.iter_mut(){
+            text.sections[0].value = wave_summary_text(&wave_stats, &run_stats);
+        }
+    }
+}
+
+//the maximum follows the level ups in base_levels
+pub fn update_health_hud(base_query : Query<(&Base, &Health)>, player_query : Query<&Health, With<Player>>, mut fill_query : Query<(&HealthBarFill, &mut Style, &mut BackgroundColor)>, mut text_query : Query<(&HealthBarText, &mut Text)>){
+    let base = base_query.get_single().ok();
+    let player_health = player_query.get_single().ok();
+    let health_of = |owner : HealthBarOwner| match owner {
+        HealthBarOwner::Base => base.map(|(_, health)| health),
+        HealthBarOwner::Player => player_health
+    };
+    for (fill, mut style, mut background_color) in fill_query.iter_mut(){
+        let Some(health) = health_of(fill.0) else {
+            continue;
+        };
+        let fraction = (health.current as f32 / health.max.max(1) as f32).clamp(0.0, 1.0);
+        style.size.width = Val::Percent(fraction * 100.0);
+        let color = if fill.0 == HealthBarOwner::Base && fraction < LOW_BASE_HEALTH {LOW_HEALTH_COLOR} else {HEALTH_BAR_FILL_COLOR};
+        if background_color.0 != color{
+            background_color.0 = color;
+        }
+    }
+    for (owner_text, mut text) in text_query.iter_mut(){
+        let Some(health) = health_of(owner_text.0) else {
+            continue;
+        };
+        text.sections[0].value = match (owner_text.0, base) {
+            (HealthBarOwner::Base, Some((base, _))) => format!("Base  level {}  {}/{}", base.level, health.current.max(0), health.max),
+            _ => format!("Player  {}/{}", health.current.max(0), health.max)
+        };
+    }
+}
+
+//flashes while the base is below LOW_BASE_HEALTH
+pub fn update_low_base_warning(base_query : Query<&Health, With<Base>>, mut warning_query : Query<&mut Visibility, With<LowBaseWarning>>, simulation_clock : Res<SimulationClock>){
+    let low = base_query.get_single().is_ok_and(|health| (health.current as f32) < health.max as f32 * LOW_BASE_HEALTH);
+    let shown = low && (simulation_clock.now() * LOW_BASE_WARNING_RATE).fract() < 0.5;
+    for mut visibility in warning_query.iter_mut(){
+        let wanted = if shown {Visibility::Inherited} else {Visibility::Hidden};
+        if *visibility != wanted{
+            *visibility = wanted;
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use rand::{prelude::*, distributions::WeightedIndex};
+use serde::Deserialize;
+use bevy::{prelude::*, reflect::TypeUuid, asset::{AssetLoader, LoadContext, LoadedAsset}, utils::BoxedFuture, sprite::collide_aabb::collide, ecs::system::SystemParam};
+
+use crate::enemy::*;
+use crate::part::*;
+use crate::health::*;
+use crate::player::Player;
+use crate::bullet::{ProjectileModifier, ProjectileUpgrades};
+use crate::rng::GameRng;
+use super::{AppState, SimulationState, SoundEffect};
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .add_asset::<LootTables>()
+        .init_asset_loader::<LootTablesLoader>()
+        .init_resource::<LootPity>()
+
+        //Startup
+        .add_startup_system(load_loot_tables)
+
+        //every run starts without bad luck
+        .add_system(reset_loot_pity.in_schedule(OnEnter(AppState::Game)))
+
+        .add_systems(
+            (
+                drop_loot,
+                //after the parts, both roll the loot stream
+                drop_upgrade_pickups.after(drop_loot)
+            )
+            .in_set(DeathSet)
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
+        .add_system(
+            collect_upgrade_pickups
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
+
+        //On exit Game Appstate
+        .add_system(despawn_upgrade_pickups.in_schedule(OnExit(AppState::Game)));
+    }
+}
+
+
+
+pub const LOOT_TABLES_PATH : &str = "Data/default.loot.ron";
+//parts dropped together are spread out so they don't stack
+pub const LOOT_SPREAD : f32 = 12.0;
+pub const UPGRADE_PICKUP_SIZE : f32 = 14.0;
+pub const UPGRADE_PICKUP_COLOR : Color = Color::rgb(1.0, 0.85, 0.2);
+//a boss drops one of these, the player's shots keep it for the rest of the run
+pub const UPGRADE_PICKUPS : [ProjectileModifier; 4] = [
+    ProjectileModifier{damage_multiplier : 1.25, speed_multiplier : 1.0, pierce : 0, bounces : 0, knockback : 0.0},
+    ProjectileModifier{damage_multiplier : 1.0, speed_multiplier : 1.25, pierce : 0, bounces : 0, knockback : 0.0},
+    ProjectileModifier{damage_multiplier : 1.0, speed_multiplier : 1.0, pierce : 1, bounces : 0, knockback : 0.0},
+    ProjectileModifier{damage_multiplier : 1.0, speed_multiplier : 1.0, pierce : 0, bounces : 1, knockback : 0.0}
+];
+
+
+//Asset
+
+#[derive(Debug, Clone, Deserialize, TypeUuid)]
+#[uuid = "5b0f0c7e-3f5c-4d0b-9a53-2f7d0c1e6a41"]
+pub struct LootTables{
+    pub tables : Vec<LootTable>
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LootTable{
+    pub enemy : EnemyKind,
+    //only splitters of this split stage, any stage when not set
+    #[serde(default)]
+    pub split_count : Option<usize>,
+    //chance a kill drops anything at all
+    pub chance : f32,
+    pub tiers : Vec<TierWeight>,
+    //parts dropped at once
+    #[serde(default = "default_quantity")]
+    pub quantity : usize,
+    //after this many kills in a row without a drop the next one always drops
+    #[serde(default)]
+    pub pity : Option<u32>
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct TierWeight{
+    pub tier : PartTier,
+    pub weight : f32
+}
+
+fn default_quantity() -> usize{
+    1
+}
+
+impl LootTables{
+    //the table used for an enemy and its index, Neonates and anything without a table drop nothing, bosses drop through boss_loot
+    pub fn find(&self, variant : &EnemyType) -> Option<(usize, &LootTable)>{
+        let (kind, split_count) = match *variant {
+            EnemyType::Pawn => (EnemyKind::Pawn, None),
+            EnemyType::Stinger => (EnemyKind::Stinger, None),
+            EnemyType::Splitter{split_count, ..} => (EnemyKind::Splitter, Some(split_count)),
+            EnemyType::Rogue => (EnemyKind::Rogue, None),
+            EnemyType::Bishop => (EnemyKind::Bishop, None),
+            EnemyType::Propagator => (EnemyKind::Propagator, None),
+            EnemyType::Gunner{..} => (EnemyKind::Gunner, None),
+            EnemyType::Neonate(_) | EnemyType::Boss(_) => return None
+        };
+        self.tables.iter().enumerate().find(|(_, table)| table.enemy == kind && (table.split_count.is_none() || table.split_count == split_count))
+    }
+}
+
+impl LootTable{
+    //the parts one kill drops, misses counts the kills since the last drop for the pity counter
+    pub fn roll(&self, misses : &mut u32, rng : &mut impl Rng) -> Vec<PartTier>{
+        let guaranteed = self.pity.is_some_and(|pity| *misses >= pity);
+        if !guaranteed && rng.gen::<f32>() >= self.chance{
+            *misses += 1;
+            return Vec::new();
+        }
+        *misses = 0;
+        let Ok(weights) = WeightedIndex::new(self.tiers.iter().map(|tier_weight| tier_weight.weight)) else {
+            return Vec::new();
+        };
+        (0..self.quantity).map(|_| self.tiers[weights.sample(rng)].tier).collect()
+    }
+}
+
+
+//Loader
+
+#[derive(Default)]
+pub struct LootTablesLoader;
+
+impl AssetLoader for LootTablesLoader{
+    fn load<'a>(&'a self, bytes: &'a [u8], load_context: &'a mut LoadContext) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let loot_tables = ron::de::from_bytes::<LootTables>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(loot_tables));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["loot.ron"]
+    }
+}
+
+
+//Resources
+
+#[derive(Resource)]
+pub struct LootTablesHandle{
+    pub handle : Handle<LootTables>
+}
+
+//an upgrade lying on the field, waiting to be picked up
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct UpgradePickup{
+    pub modifier : ProjectileModifier
+}
+
+//kills without a drop per loot table, by table index
+#[derive(Resource, Default)]
+pub struct LootPity{
+    pub misses : Vec<u32>
+}
+
+
+//Systems
+
+pub fn load_loot_tables(mut commands: Commands, asset_server : Res<AssetServer>){
+    commands.insert_resource(LootTablesHandle{handle : asset_server.load(LOOT_TABLES_PATH)});
+}
+
+pub fn reset_loot_pity(mut loot_pity : ResMut<LootPity>){
+    *loot_pity = LootPity::default();
+}
+
+//the loot tables once the asset has finished loading
+#[derive(SystemParam)]
+pub struct LoadedLootTables<'w>{
+    loot_tables_handle : Option<Res<'w, LootTablesHandle>>,
+    loot_tables : Res<'w, Assets<LootTables>>
+}
+
+impl LoadedLootTables<'_>{
+    pub fn get(&self) -> Option<&LootTables>{
+        self.loot_tables_handle.as_ref().and_then(|loot_tables_handle| self.loot_tables.get(&loot_tables_handle.handle))
+    }
+}
+
+//enemies shot down roll their table, enemies used up by ramming drop nothing
+pub fn drop_loot(mut death_event_reader : EventReader<DeathEvent>, enemy_query : Query<(&Transform, &Enemy)>, loaded_loot_tables : LoadedLootTables, mut loot_pity : ResMut<LootPity>, mut game_rng : ResMut<GameRng>, mut part_spawner : PartSpawner){
+    let Some(loot_tables) = loaded_loot_tables.get() else {
+        return;
+    };
+    for death_event in death_event_reader.iter(){
+        if death_event.damage_type == DamageType::Collision{
+            continue;
+        }
+        let Ok((enemy_transform, enemy)) = enemy_query.get(death_event.entity) else {
+            continue;
+        };
+        if let Some((index, loot_table)) = loot_tables.find(&enemy.variant){
+            if loot_pity.misses.len() <= index{
+                loot_pity.misses.resize(index + 1, 0);
+            }
+            let part_tiers = loot_table.roll(&mut loot_pity.misses[index], &mut game_rng.loot);
+            for (i, part_tier) in part_tiers.into_iter().enumerate(){
+                let offset = Vec3::new(i as f32 * LOOT_SPREAD, 0.0, 0.0);
+                part_spawner.spawn(part_tier, enemy_transform.translation + offset);
+            }
+        }
+    }
+}
+
+pub fn spawn_upgrade_pickup(commands : &mut Commands, modifier : ProjectileModifier, translation : Vec3) -> Entity{
+    commands.spawn((
+        SpriteBundle{
+            sprite : Sprite{color : UPGRADE_PICKUP_COLOR, custom_size : Some(Vec2::splat(UPGRADE_PICKUP_SIZE)), ..default()},
+            transform : Transform::from_translation(translation),
+            ..default()
+        },
+        UpgradePickup{modifier}
+    )).id()
+}
+
+//bosses shot down leave an upgrade behind
+pub fn drop_upgrade_pickups(mut commands: Commands, mut death_event_reader : EventReader<DeathEvent>, enemy_query : Query<(&Transform, &Enemy)>, mut game_rng : ResMut<GameRng>){
+    for death_event in death_event_reader.iter(){
+        if death_event.damage_type == DamageType::Collision{
+            continue;
+        }
+        let Ok((enemy_transform, enemy)) = enemy_query.get(death_event.entity) else {
+            continue;
+        };
+        if matches!(enemy.variant, EnemyType::Boss(_)){
+            let modifier = UPGRADE_PICKUPS[game_rng.loot.gen_range(0..UPGRADE_PICKUPS.len())];
+            spawn_upgrade_pickup(&mut commands, modifier, enemy_transform.translation);
+        }
+    }
+}
+
+//unlike parts they don't go to the base, they stay with the player's weapons
+pub fn collect_upgrade_pickups(mut commands: Commands, pickup_query : Query<(Entity, &Transform, &UpgradePickup)>, mut player_query: Query<(&Transform, &Player, &mut ProjectileUpgrades)>, mut sound_effects : EventWriter<SoundEffect>){
+    let Ok((player_transform, player, mut upgrades)) = player_query.get_single_mut() else {
+        return;
+    };
+    for (pickup_entity, pickup_transform, pickup) in pickup_query.iter(){
+        if collide(player_transform.translation, player.size, pickup_transform.translation, Vec2::splat(UPGRADE_PICKUP_SIZE)).is_some(){
+            upgrades.modifiers.push(pickup.modifier);
+            sound_effects.send(SoundEffect("Audio/impactMining_001.ogg"));
+            commands.entity(pickup_entity).despawn();
+        }
+    }
+}
+
+pub fn despawn_upgrade_pickups(mut commands : Commands, pickup_query : Query<Entity, With<UpgradePickup>>){
+    for pickup_entity in pickup_query.iter(){
+        commands.entity(pickup_entity).despawn();
+    }
+}
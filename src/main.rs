@@ -3,6 +3,7 @@ use bevy::{app::AppExit, prelude::*, window::PrimaryWindow};
 mod repetitive_code;
 mod player;
 mod wave;
+mod wave_script;
 mod bullet;
 mod enemy;
 mod base;
@@ -12,6 +13,30 @@ mod part;
 mod pause_menu;
 mod game_over;
 mod hud;
+mod rng;
+mod headless;
+mod high_scores;
+mod savegame;
+mod game_time;
+mod controls;
+mod controls_menu;
+mod health;
+mod loot;
+mod spatial;
+mod benchmark;
+mod building;
+mod weapon;
+mod boss;
+mod enemy_projectile;
+mod steering;
+mod status;
+mod director;
+mod health_bar;
+mod feedback;
+mod settings;
+mod settings_menu;
+#[cfg(test)]
+mod tests;
 
 use turret::TurretPlugin;
 use bullet::BulletPlugin;
@@ -19,51 +44,128 @@ use enemy::EnemyPlugin;
 use base::BasePlugin;
 use main_menu::MainMenuPlugin;
 use wave::WavePlugin;
-use player::PlayerPlugin;
+use wave_script::WaveScriptPlugin;
+use player::{PlayerPlugin, PlayerInputSet, action_player_input};
 use pause_menu::PauseMenuPlugin;
 use game_over::GameOverMenuPlugin;
 use hud::HudPlugin;
+use rng::RngPlugin;
+use headless::{HeadlessPlugin, HeadlessConfig};
+use high_scores::{HighScorePlugin, load_high_scores};
+use savegame::{SaveGamePlugin, SaveGameSlot, SAVE_GAME_PATH};
+use game_time::GameTimePlugin;
+use controls::{ControlsPlugin, ActionState, Action, load_input_bindings};
+use controls_menu::ControlsMenuPlugin;
+use health::HealthPlugin;
+use loot::LootPlugin;
+use spatial::SpatialPlugin;
+use building::{BuildingPlugin, BuildPreviewPlugin};
+use weapon::WeaponPlugin;
+use boss::BossPlugin;
+use enemy_projectile::EnemyProjectilePlugin;
+use steering::SteeringPlugin;
+use status::StatusPlugin;
+use director::DirectorPlugin;
+use health_bar::HealthBarPlugin;
+use feedback::FeedbackPlugin;
+use settings::{SettingsPlugin, load_settings};
+use settings_menu::SettingsMenuPlugin;
 fn main() {
-    App::new()
-    .add_plugins(DefaultPlugins)
-    .add_startup_system(spawn_camera)
-
-    //Resources
-    .init_resource::<FinalScore>()
-    //States
-    .add_state::<AppState>()
-    .add_state::<SimulationState>()
-
-    //Events
-    .add_event::<GameOver>()
-
-     // OnEnter Systems
-     .add_system(resume_simulation.in_schedule(OnEnter(AppState::Game)))
-     
-
-    //Plugins
-    .add_plugin(PlayerPlugin)
-    .add_plugin(EnemyPlugin)
-    .add_plugin(TurretPlugin)
-    .add_plugin(WavePlugin)
-    .add_plugin(BasePlugin)
-    .add_plugin(BulletPlugin)
-    .add_plugin(MainMenuPlugin)
-    .add_plugin(PauseMenuPlugin)
-    .add_plugin(GameOverMenuPlugin)
-    .add_plugin(HudPlugin)
-
-    //Systems
-    .add_system(toggle_simulation.run_if(in_state(AppState::Game)))
-    .add_system(transition_to_game_state)
-    .add_system(transition_to_main_menu_state)
-    .add_system(update_final_score)
-    .add_system(exit_game)
-    .add_system(handle_game_over)
-
-    //On Exit Systems
-    //.add_system(pause_simulation.in_schedule(OnExit(AppState::Game)))
-    .run();
+    let mut app = App::new();
+    match HeadlessConfig::from_args(){
+        Some(headless_config) => {
+            app.add_plugin(HeadlessPlugin{config : headless_config});
+        },
+        None => {
+            app
+            .add_plugins(DefaultPlugins.set(AssetPlugin{
+                //hot reload the wave script while playing
+                watch_for_changes : true,
+                ..default()
+            }))
+            .add_plugin(GamePlugin)
+            .add_plugin(BuildPreviewPlugin)
+            .add_startup_system(spawn_camera)
+            .add_startup_system(load_high_scores)
+            .add_startup_system(load_input_bindings)
+            .add_startup_system(load_settings)
+            .insert_resource(SaveGameSlot{path : Some(SAVE_GAME_PATH.into())})
+            .add_system(update_playfield)
+            .add_system(play_sound_effects)
+            .add_system(action_player_input.in_set(PlayerInputSet))
+            .add_system(exit_game);
+        }
+    }
+    app.run();
+}
+
+//everything except the window, rendering and audio, shared with the headless mode
+//needs the AssetPlugin and InputPlugin to be added first
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin{
+    fn build(&self, app: &mut App) {
+        app
+        //Resources
+        .init_resource::<FinalScore>()
+        .init_resource::<RunStats>()
+        .init_resource::<Playfield>()
+        //States
+        .add_state::<AppState>()
+        .add_state::<SimulationState>()
+
+        //Events
+        .add_event::<GameOver>()
+        .add_event::<SoundEffect>()
+
+        // OnEnter Systems
+        .add_system(resume_simulation.in_schedule(OnEnter(AppState::Game)))
+        .add_system(reset_run_stats.in_schedule(OnEnter(AppState::Game)))
+
+
+        //Plugins
+        .add_plugin(RngPlugin)
+        .add_plugin(GameTimePlugin)
+        .add_plugin(ControlsPlugin)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(HealthPlugin)
+        .add_plugin(LootPlugin)
+        .add_plugin(SpatialPlugin)
+        .add_plugin(PlayerPlugin)
+        .add_plugin(WeaponPlugin)
+        .add_plugin(EnemyPlugin)
+        .add_plugin(BossPlugin)
+        .add_plugin(EnemyProjectilePlugin)
+        .add_plugin(SteeringPlugin)
+        .add_plugin(StatusPlugin)
+        .add_plugin(TurretPlugin)
+        .add_plugin(BuildingPlugin)
+        .add_plugin(WaveScriptPlugin)
+        .add_plugin(DirectorPlugin)
+        .add_plugin(WavePlugin)
+        .add_plugin(BasePlugin)
+        .add_plugin(BulletPlugin)
+        .add_plugin(MainMenuPlugin)
+        .add_plugin(ControlsMenuPlugin)
+        .add_plugin(SettingsMenuPlugin)
+        .add_plugin(PauseMenuPlugin)
+        .add_plugin(GameOverMenuPlugin)
+        .add_plugin(HudPlugin)
+        .add_plugin(HealthBarPlugin)
+        .add_plugin(FeedbackPlugin)
+        .add_plugin(HighScorePlugin)
+        .add_plugin(SaveGamePlugin)
+
+        //Systems
+        .add_system(toggle_simulation.run_if(in_state(AppState::Game)))
+        .add_system(transition_to_game_state)
+        .add_system(transition_to_main_menu_state)
+        .add_system(update_final_score)
+        .add_system(handle_game_over);
+
+        //On Exit Systems
+        //.add_system(pause_simulation.in_schedule(OnExit(AppState::Game)))
+    }
 }
 
 
@@ -72,7 +174,9 @@ pub enum AppState{
     #[default]
     MainMenu,
     Game,
-    GameOver
+    GameOver,
+    Controls,
+    Settings
 }
 
 #[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
@@ -85,9 +189,27 @@ pub enum SimulationState {
 
 pub struct GameOver{
     pub time_alive : u64,
-    pub base_level : i64
+    pub base_level : i64,
+    pub wave : usize
+}
+
+//the area the game is played in, follows the window or is fixed when headless
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Playfield{
+    pub width : f32,
+    pub height : f32
+}
+
+impl Default for Playfield{
+    fn default() -> Playfield {
+        Playfield{width : 1280.0, height : 720.0}
+    }
 }
 
+//audio is played from these so gameplay systems don't need the Audio resource
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoundEffect(pub &'static str);
+
 #[derive(Resource)]
 
 pub struct FinalScore{
@@ -101,6 +223,14 @@ impl Default for FinalScore {
     }
 }
 
+//counted during a run, reset when a new one starts
+#[derive(Resource, Default)]
+pub struct RunStats{
+    pub kills : u64,
+    //picked up by the player
+    pub parts : u64
+}
+
 
 pub fn update_final_score(mut game_over_event_reader : EventReader<GameOver>, mut final_scores : ResMut<FinalScore>){
     for event in game_over_event_reader.iter(){
@@ -108,14 +238,34 @@ pub fn update_final_score(mut game_over_event_reader : EventReader<GameOver>, mu
     }
 }
 
-pub fn exit_game(keyboard_input: Res<Input<KeyCode>>, mut app_exit_event_writer: EventWriter<AppExit>){
+pub fn reset_run_stats(mut run_stats : ResMut<RunStats>){
+    *run_stats = RunStats::default();
+}
+
+//unbound by default so a stray key doesn't end the session
+pub fn exit_game(action_state: Res<ActionState>, mut app_exit_event_writer: EventWriter<AppExit>){
     
-    if keyboard_input.just_pressed(KeyCode::Escape){
+    if action_state.just_pressed(Action::Quit){
         app_exit_event_writer.send(AppExit);
     }
 
 }
 
+pub fn update_playfield(window_query : Query<&Window, With<PrimaryWindow>>, mut playfield : ResMut<Playfield>){
+    if let Ok(window) = window_query.get_single(){
+        let window_playfield = Playfield{width : window.width(), height : window.height()};
+        if *playfield != window_playfield{
+            *playfield = window_playfield;
+        }
+    }
+}
+
+pub fn play_sound_effects(mut sound_effect_reader : EventReader<SoundEffect>, asset_server : Res<AssetServer>, audio : Res<Audio>){
+    for sound_effect in sound_effect_reader.iter(){
+        audio.play(asset_server.load(sound_effect.0));
+    }
+}
+
 pub fn spawn_camera(mut commands: Commands, window_query : Query<&Window, With<PrimaryWindow>>){
     let window = window_query.get_single().unwrap();
     commands.spawn(Camera2dBundle{
@@ -133,11 +283,11 @@ pub fn resume_simulation(mut simulation_state_next_state: ResMut<NextState<Simul
 }
 
 pub fn toggle_simulation(
-    keyboard_input: Res<Input<KeyCode>>,
+    action_state: Res<ActionState>,
     simulation_state: Res<State<SimulationState>>,
     mut simulation_state_next_state: ResMut<NextState<SimulationState>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
+    if action_state.just_pressed(Action::Pause) {
         if simulation_state.0 == SimulationState::Running {
             simulation_state_next_state.set(SimulationState::Paused);
         }
@@ -154,32 +304,16 @@ pub fn handle_game_over(mut game_over_event_reader : EventReader<GameOver>, mut
     }
 }
 
-pub fn transition_to_game_state( keyboard_input: Res<Input<KeyCode>>, mut next_app_state : ResMut<NextState<AppState>>, app_state : Res<State<AppState>>) {
-    if keyboard_input.pressed(KeyCode::G){
-        if app_state.0 != AppState::Game{
-            next_app_state.set(AppState::Game);
-        }
+//only from the menus that offer a new run, not while the controls or settings are being changed
+pub fn transition_to_game_state( action_state: Res<ActionState>, mut next_app_state : ResMut<NextState<AppState>>, app_state : Res<State<AppState>>) {
+    if action_state.pressed(Action::StartGame) && matches!(app_state.0, AppState::MainMenu | AppState::GameOver){
+        next_app_state.set(AppState::Game);
     }
-
 }
 
-pub fn transition_to_main_menu_state(keyboard_input: Res<Input<KeyCode>>, app_state : Res<State<AppState>>, mut next_app_state : ResMut<NextState<AppState>>, mut next_simulation_state : ResMut<NextState<SimulationState>>){
-    if keyboard_input.pressed(KeyCode::M){
-        if app_state.0 != AppState::MainMenu{
-            next_app_state.set(AppState::MainMenu);
-            next_simulation_state.set(SimulationState::Paused);
-        };
+pub fn transition_to_main_menu_state(action_state: Res<ActionState>, app_state : Res<State<AppState>>, mut next_app_state : ResMut<NextState<AppState>>, mut next_simulation_state : ResMut<NextState<SimulationState>>){
+    if action_state.pressed(Action::MainMenu) && app_state.0 != AppState::MainMenu{
+        next_app_state.set(AppState::MainMenu);
+        next_simulation_state.set(SimulationState::Paused);
     }
 }
-
-
-
-
-
-
-
-
-
-
-
-
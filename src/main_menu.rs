@@ -1,5 +1,5 @@
 use bevy::{prelude::*, app::AppExit};
-use crate::repetitive_code::*;
+use crate::{repetitive_code::*, rng::GameRng, high_scores::*, savegame::*, director::Difficulty};
 use super::AppState;
 
 pub struct MainMenuPlugin;
@@ -11,7 +11,15 @@ impl Plugin for MainMenuPlugin{
         .add_systems(
             (
                 interact_with_play_button,
-                interact_with_quit_button
+                interact_with_continue_button,
+                interact_with_controls_button,
+                interact_with_settings_button,
+                interact_with_quit_button,
+                interact_with_random_seed_button,
+                interact_with_difficulty_button,
+                type_seed,
+                update_seed_text,
+                update_difficulty_text
             ).in_set(OnUpdate(AppState::MainMenu))
         )
         .add_system(despawn_main_menu.in_schedule(OnExit(AppState::MainMenu)));
@@ -27,16 +35,37 @@ pub struct MainMenu;
 #[derive(Component)]
 pub struct PlayButton;
 
+#[derive(Component)]
+pub struct ContinueButton;
+
+#[derive(Component)]
+pub struct ControlsButton;
+
+#[derive(Component)]
+pub struct SettingsButton;
+
 #[derive(Component)]
 pub struct QuitButton;
 
 #[derive(Component)]
 pub struct MusicJockey;
 
+#[derive(Component)]
+pub struct SeedText;
+
+#[derive(Component)]
+pub struct RandomSeedButton;
+
+#[derive(Component)]
+pub struct DifficultyButton;
+
+#[derive(Component)]
+pub struct DifficultyText;
+
 //Layout
 
-pub fn spawn_main_menu(mut commands : Commands, asset_server: Res<AssetServer>) {
-    build_main_menu(&mut commands, &asset_server);
+pub fn spawn_main_menu(mut commands : Commands, asset_server: Res<AssetServer>, high_scores : Res<HighScores>, save_game_slot : Res<SaveGameSlot>, difficulty : Res<Difficulty>) {
+    build_main_menu(&mut commands, &asset_server, &high_scores, save_game_slot.exists(), *difficulty);
 }
 
 pub fn despawn_main_menu(mut commands : Commands, main_menu_query: Query<Entity, With<MainMenu>>) {
@@ -45,7 +74,7 @@ pub fn despawn_main_menu(mut commands : Commands, main_menu_query: Query<Entity,
     }
 }
 
-pub fn build_main_menu(commands : &mut Commands, asset_server: &Res<AssetServer>) -> Entity{
+pub fn build_main_menu(commands : &mut Commands, asset_server: &Res<AssetServer>, high_scores : &HighScores, has_save_game : bool, difficulty : Difficulty) -> Entity{
     let main_menu_entity = commands
         .spawn((
             NodeBundle{
@@ -81,6 +110,31 @@ pub fn build_main_menu(commands : &mut Commands, asset_server: &Res<AssetServer>
                     ..default()
                 }); 
             });
+            //continue button, only when there is a saved run
+            if has_save_game{
+                parent.spawn((
+                    ButtonBundle{   
+                        style: BUTTON_STYLE,
+                        background_color : NORMAL_BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    ContinueButton{},
+                ))
+                .with_children(|parent|{
+                    parent.spawn(TextBundle{
+                        text : Text{
+                            sections : vec![
+                                TextSection::new(
+                                    "Continue",
+                                    get_button_text_style(asset_server),
+                                )],
+                                alignment: TextAlignment::Center,
+                                ..default()
+                        },
+                        ..default()
+                    });
+                });
+            }
             //playbutton
             parent.spawn((
                 ButtonBundle{   
@@ -104,6 +158,117 @@ pub fn build_main_menu(commands : &mut Commands, asset_server: &Res<AssetServer>
                     ..default()
                 });
             });
+            //difficulty button, cycles through the presets
+            parent.spawn((
+                ButtonBundle{   
+                    style: BUTTON_STYLE,
+                    background_color : NORMAL_BUTTON_COLOR.into(),
+                    ..default()
+                },
+                DifficultyButton{},
+            ))
+            .with_children(|parent|{
+                parent.spawn((
+                    TextBundle{
+                        text : Text{
+                            sections : vec![
+                                TextSection::new(
+                                    difficulty.name(),
+                                    get_button_text_style(asset_server),
+                                )],
+                                alignment: TextAlignment::Center,
+                                ..default()
+                        },
+                        ..default()
+                    },
+                    DifficultyText{},
+                ));
+            });
+            //seed, typed in with the number keys
+            parent.spawn((
+                TextBundle{
+                    text : Text{
+                        sections : vec![
+                            TextSection::new(
+                                "Seed: random",
+                                get_button_text_style(asset_server),
+                            )],
+                            alignment: TextAlignment::Center,
+                            ..default()
+                    },
+                    ..default()
+                },
+                SeedText{},
+            ));
+            //random seed button
+            parent.spawn((
+                ButtonBundle{   
+                    style: BUTTON_STYLE,
+                    background_color : NORMAL_BUTTON_COLOR.into(),
+                    ..default()
+                },
+                RandomSeedButton{},
+            ))
+            .with_children(|parent|{
+                parent.spawn(TextBundle{
+                    text : Text{
+                        sections : vec![
+                            TextSection::new(
+                                "Random",
+                                get_button_text_style(asset_server)
+                            )],
+                            alignment: TextAlignment::Center,
+                            ..default()
+                    },
+                    ..default()
+                });
+            });
+            //controls button, opens the rebind screen
+            parent.spawn((
+                ButtonBundle{   
+                    style: BUTTON_STYLE,
+                    background_color : NORMAL_BUTTON_COLOR.into(),
+                    ..default()
+                },
+                ControlsButton{},
+            ))
+            .with_children(|parent|{
+                parent.spawn(TextBundle{
+                    text : Text{
+                        sections : vec![
+                            TextSection::new(
+                                "Controls",
+                                get_button_text_style(asset_server)
+                            )],
+                            alignment: TextAlignment::Center,
+                            ..default()
+                    },
+                    ..default()
+                });
+            });
+            //settings button, opens the accessibility settings
+            parent.spawn((
+                ButtonBundle{   
+                    style: BUTTON_STYLE,
+                    background_color : NORMAL_BUTTON_COLOR.into(),
+                    ..default()
+                },
+                SettingsButton{},
+            ))
+            .with_children(|parent|{
+                parent.spawn(TextBundle{
+                    text : Text{
+                        sections : vec![
+                            TextSection::new(
+                                "Settings",
+                                get_button_text_style(asset_server)
+                            )],
+                            alignment: TextAlignment::Center,
+                            ..default()
+                    },
+                    ..default()
+                });
+            });
             //quibutton
             parent.spawn((
                 ButtonBundle{   
@@ -127,6 +292,7 @@ pub fn build_main_menu(commands : &mut Commands, asset_server: &Res<AssetServer>
                     ..default()
                 });
             });
+            build_leaderboard(parent, asset_server, high_scores);
         })
         .id();
     main_menu_entity
@@ -148,6 +314,48 @@ pub fn interact_with_play_button(mut button_query : Query<(&Interaction, &mut Ba
     }
 }
 
+pub fn interact_with_continue_button(mut commands : Commands, mut button_query : ButtonInteractionQuery<ContinueButton>, save_game_slot : Res<SaveGameSlot>, mut app_state_next_state : ResMut<NextState<AppState>>){
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut(){
+        match *interaction{
+            Interaction::Clicked => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                if let Some(save_game) = save_game_slot.read(){
+                    commands.insert_resource(PendingSave(save_game));
+                    app_state_next_state.set(AppState::Game)
+                }
+            },
+            Interaction::Hovered => {*background_color = HOVERED_BUTTON_COLOR.into()},
+            Interaction::None =>  {*background_color = NORMAL_BUTTON_COLOR.into()},
+        }
+    }
+}
+
+pub fn interact_with_controls_button(mut button_query : ButtonInteractionQuery<ControlsButton>, mut app_state_next_state : ResMut<NextState<AppState>>){
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut(){
+        match *interaction{
+            Interaction::Clicked => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                app_state_next_state.set(AppState::Controls)
+            },
+            Interaction::Hovered => *background_color = HOVERED_BUTTON_COLOR.into(),
+            Interaction::None =>  *background_color = NORMAL_BUTTON_COLOR.into()
+        }
+    }
+}
+
+pub fn interact_with_settings_button(mut button_query : ButtonInteractionQuery<SettingsButton>, mut app_state_next_state : ResMut<NextState<AppState>>){
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut(){
+        match *interaction{
+            Interaction::Clicked => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                app_state_next_state.set(AppState::Settings)
+            },
+            Interaction::Hovered => *background_color = HOVERED_BUTTON_COLOR.into(),
+            Interaction::None =>  *background_color = NORMAL_BUTTON_COLOR.into()
+        }
+    }
+}
+
 pub fn interact_with_quit_button(mut button_query : Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<QuitButton>)>, mut app_exit_event_writer : EventWriter<AppExit>){
     if let Ok((interaction, mut background_color)) = button_query.get_single_mut(){
         match *interaction{
@@ -162,5 +370,64 @@ pub fn interact_with_quit_button(mut button_query : Query<(&Interaction, &mut Ba
 
 }
 
+pub fn interact_with_random_seed_button(mut button_query : ButtonInteractionQuery<RandomSeedButton>, mut game_rng : ResMut<GameRng>){
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut(){
+        match *interaction{
+            Interaction::Clicked => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                game_rng.fixed_seed = false;
+            },
+            Interaction::Hovered => *background_color = HOVERED_BUTTON_COLOR.into(),
+            Interaction::None =>  *background_color = NORMAL_BUTTON_COLOR.into()
+        }
+    }
+}
+
+pub fn interact_with_difficulty_button(mut button_query : ButtonInteractionQuery<DifficultyButton>, mut difficulty : ResMut<Difficulty>){
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut(){
+        match *interaction{
+            Interaction::Clicked => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                *difficulty = difficulty.next();
+            },
+            Interaction::Hovered => *background_color = HOVERED_BUTTON_COLOR.into(),
+            Interaction::None =>  *background_color = NORMAL_BUTTON_COLOR.into()
+        }
+    }
+}
+
 //other systems
 
+pub fn type_seed(mut received_characters : EventReader<ReceivedCharacter>, keyboard_input: Res<Input<KeyCode>>, mut game_rng : ResMut<GameRng>){
+    for received_character in received_characters.iter(){
+        if let Some(digit) = received_character.char.to_digit(10){
+            let seed = if game_rng.fixed_seed {game_rng.seed} else {0};
+            if let Some(seed) = seed.checked_mul(10).and_then(|seed| seed.checked_add(digit as u64)){
+                game_rng.seed = seed;
+                game_rng.fixed_seed = true;
+            }
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) && game_rng.fixed_seed{
+        game_rng.seed /= 10;
+        if game_rng.seed == 0{
+            game_rng.fixed_seed = false;
+        }
+    }
+}
+
+pub fn update_seed_text(game_rng : Res<GameRng>, mut text_query : Query<&mut Text, With<SeedText>>){
+    if game_rng.is_changed(){
+        for mut text in text_query.iter_mut(){
+            text.sections[0].value = if game_rng.fixed_seed {format!("Seed: {}", game_rng.seed)} else {"Seed: random".to_string()};
+        }
+    }
+}
+
+pub fn update_difficulty_text(difficulty : Res<Difficulty>, mut text_query : Query<&mut Text, With<DifficultyText>>){
+    if difficulty.is_changed(){
+        for mut text in text_query.iter_mut(){
+            text.sections[0].value = difficulty.name().to_string();
+        }
+    }
+}
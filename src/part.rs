@@ -1,18 +1,58 @@
-use std::time::Instant;
-use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use bevy::{prelude::*, ecs::system::SystemParam};
+
+use crate::repetitive_code::get_enemy_transform_0_2;
+use crate::game_time::SimulationClock;
 
 
 #[derive(Component, Clone, Copy, Debug, PartialEq)]
 pub struct Part{
     pub part_tier : PartTier,
     pub size : Vec2,
-    pub instant : Instant
+    //seconds on the SimulationClock
+    pub spawn_time : f32
 }
 
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PartTier {
     Blue,
     Red,
     Green
 }
+
+impl PartTier{
+    pub fn texture(&self) -> &'static str{
+        match self {
+            PartTier::Blue => "Sprites/spaceParts_008.png",
+            PartTier::Red => "Sprites/spaceParts_013.png",
+            PartTier::Green => "Sprites/spaceParts_025.png"
+        }
+    }
+}
+
+//a part lying on the field, waiting to be picked up
+pub fn spawn_part(commands : &mut Commands, asset_server : &Res<AssetServer>, part_tier : PartTier, translation : Vec3, spawn_time : f32) -> Entity{
+    commands.spawn((
+        SpriteBundle{
+            transform : get_enemy_transform_0_2(translation),
+            texture : asset_server.load(part_tier.texture()),
+            ..default()
+        },
+        Part{part_tier, size : Vec2::new(15.0, 15.0), spawn_time}
+    )).id()
+}
+
+//drops parts stamped with the current simulation time
+#[derive(SystemParam)]
+pub struct PartSpawner<'w, 's>{
+    commands : Commands<'w, 's>,
+    asset_server : Res<'w, AssetServer>,
+    simulation_clock : Res<'w, SimulationClock>
+}
+
+impl PartSpawner<'_, '_>{
+    pub fn spawn(&mut self, part_tier : PartTier, translation : Vec3) -> Entity{
+        spawn_part(&mut self.commands, &self.asset_server, part_tier, translation, self.simulation_clock.now())
+    }
+}
@@ -1,5 +1,5 @@
 use bevy::{prelude::*, app::AppExit};
-use crate::repetitive_code::*;
+use crate::{repetitive_code::*, savegame::SaveGameRequest};
 use super::{AppState, SimulationState};
 
 pub struct PauseMenuPlugin;
@@ -13,6 +13,7 @@ impl Plugin for PauseMenuPlugin {
             .add_systems(
                 (
                     interact_with_resume_button,
+                    interact_with_save_button,
                     interact_with_main_menu_button,
                     interact_with_quit_button,
                 )
@@ -32,6 +33,9 @@ pub struct PauseMenu;
 #[derive(Component)]
 pub struct ResumeButton;
 
+#[derive(Component)]
+pub struct SaveButton;
+
 #[derive(Component)]
 pub struct MainMenuButton;
 
@@ -105,6 +109,30 @@ pub fn build_pause_menu(commands: &mut Commands, asset_server: &Res<AssetServer>
                                 ..default()
                             });
                         });
+                    // Save Button
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: BUTTON_STYLE,
+                                background_color: NORMAL_BUTTON_COLOR.into(),
+                                ..default()
+                            },
+                            SaveButton {},
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle {
+                                style: Style { ..default() },
+                                text: Text {
+                                    sections: vec![TextSection::new(
+                                        "Save",
+                                        get_button_text_style(asset_server),
+                                    )],
+                                    alignment: TextAlignment::Center,
+                                    ..default()
+                                },
+                                ..default()
+                            });
+                        });
                     // Main Menu Button
                     parent
                         .spawn((
@@ -180,6 +208,23 @@ pub fn interact_with_resume_button(mut button_query: Query<(&Interaction, &mut B
     }
 }
 
+pub fn interact_with_save_button(mut button_query: ButtonInteractionQuery<SaveButton>, mut save_requests: EventWriter<SaveGameRequest>,) {
+    for (interaction, mut color) in button_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON_COLOR.into();
+                save_requests.send(SaveGameRequest);
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
 pub fn interact_with_main_menu_button(mut button_query: Query<(&Interaction, &mut BackgroundColor),(Changed<Interaction>, With<MainMenuButton>),>,mut app_state_next_state: ResMut<NextState<AppState>>,) {
     for (interaction, mut color) in button_query.iter_mut() {
         match *interaction {
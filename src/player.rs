@@ -1,15 +1,25 @@
-use std::{ f32::consts::PI, time::Instant};
-use rand::prelude::*;
-use bevy::{ prelude::*, window::PrimaryWindow, sprite::collide_aabb::collide};
+use std::f32::consts::PI;
+use bevy::{ prelude::*, window::PrimaryWindow, sprite::collide_aabb::collide, ecs::system::SystemParam};
 
 use crate::part::*;
-use crate::repetitive_code::*;
 use crate::base::*;
 use crate::enemy::*;
 use crate::bullet::*;
-use super::SimulationState;
+use crate::game_time::SimulationClock;
+use crate::controls::{ActionState, Action};
+use crate::health::*;
+use crate::spatial::EnemyGrid;
+use crate::building::PartInventory;
+use crate::weapon::*;
+use crate::status::StatusEvent;
+use crate::feedback::HIT_KNOCKBACK;
+use crate::settings::Settings;
+use super::{SimulationState, SoundEffect, Playfield, RunStats};
 use super::AppState;
 
+#[derive(SystemSet, Debug, Hash, Clone, PartialEq, Eq)]
+pub struct PlayerInputSet;
+
 #[derive(SystemSet, Debug, Hash, Clone, PartialEq, Eq)]
 pub struct PlayerMovementSet;
 
@@ -23,10 +33,11 @@ impl Plugin for PlayerPlugin{
         app
 
         //Ordering 
+        .configure_set(PlayerInputSet.before(PlayerMovementSet))
         .configure_set(PlayerMovementSet.before(ConfinementSet))
 
         //Resource
-        .init_resource::<BlasterCooldownTimer>()
+        .init_resource::<PlayerInput>()
 
         //When entering Game Appstate
         .add_system(spawn_player.in_schedule(OnEnter(AppState::Game)))
@@ -46,10 +57,10 @@ impl Plugin for PlayerPlugin{
         //while in game Appstate
         .add_systems(
             (
-                player_shoot,
-                blaster_timer_ticker,
-                player_shoot_enemy,
-                enemy_hit_player,
+                player_shoot.after(PlayerInputSet),
+                player_shoot_enemy.in_set(DamageSet),
+                enemy_hit_player.in_set(DamageSet),
+                player_death.in_set(DeathSet),
                 base_part_collecting
             )
             .in_set(OnUpdate(AppState::Game))
@@ -59,6 +70,7 @@ impl Plugin for PlayerPlugin{
 
         //on exit Game Appstate
         .add_system(despawn_player.in_schedule(OnExit(AppState::Game)))
+        .add_system(reset_player_input.in_schedule(OnExit(AppState::Game)))
         .add_system(resume_simulation.in_schedule(OnExit(AppState::Game)));
     
     }
@@ -66,38 +78,27 @@ impl Plugin for PlayerPlugin{
 
 
 pub const PLAYER_SIZE :f32 = 32.0;
-pub const BLASTER_COOLDOWN : f32 =  0.5;
+//how far ahead of the player the right stick aims
+pub const STICK_AIM_DISTANCE : f32 = 100.0;
 
 #[derive(Component)]
 pub struct Player{
-    pub health : i64,
     pub speed : f32,
-    pub size : Vec2,
-    pub max_health : i64
+    pub size : Vec2
 }
 
 
-#[derive(Resource)]
-pub struct BlasterCooldownTimer{
-    pub timer: Timer,
-}
-
-impl Default for BlasterCooldownTimer{
-    fn default() -> BlasterCooldownTimer {
-        BlasterCooldownTimer{timer: Timer::from_seconds(BLASTER_COOLDOWN, TimerMode::Repeating)}
-    }
+//what the player wants to do this frame, filled in from the bound actions or by the headless autopilot
+#[derive(Resource, Default)]
+pub struct PlayerInput{
+    pub movement : Vec3,
+    pub aim : Option<Vec2>,
+    pub fire : bool
 }
 
-impl BlasterCooldownTimer{
-    pub fn set_cooldown(&mut self, new_cooldown : f32){
-        self.timer = Timer::from_seconds(new_cooldown, TimerMode::Repeating);
-    }
-}
-
-pub fn despawn_player(mut commands : Commands, player_query: Query<Entity, With<Player>>, mut blaster_timer: ResMut<BlasterCooldownTimer>){
+pub fn despawn_player(mut commands : Commands, player_query: Query<Entity, With<Player>>){
     if let Ok(player_entity) = player_query.get_single(){
         commands.entity(player_entity).despawn();
-        *blaster_timer = BlasterCooldownTimer::default();
     }
 }
 
@@ -110,35 +111,52 @@ pub fn resume_simulation(mut next_simulation_state : ResMut<NextState<Simulation
 
 }
 
-pub fn blaster_timer_ticker(mut blaster_timer: ResMut<BlasterCooldownTimer>, time : Res<Time>){
-    blaster_timer.timer.tick(time.delta());
+pub fn reset_player_input(mut player_input : ResMut<PlayerInput>){
+    *player_input = PlayerInput::default();
+}
 
+//keyboard, mouse and gamepad through the bindings, the right stick aims and fires around the player
+pub fn action_player_input(action_state : Res<ActionState>, window_query : Query<&Window, With<PrimaryWindow>>, player_query : Query<&Transform, With<Player>>, mut player_input : ResMut<PlayerInput>){
+    player_input.movement = action_state.movement.extend(0.0);
+
+    match (action_state.aim_stick, player_query.get_single()) {
+        (Some(aim_direction), Ok(player_transform)) => {
+            player_input.aim = Some(player_transform.translation.truncate() + aim_direction * STICK_AIM_DISTANCE);
+            player_input.fire = true;
+        },
+        _ => {
+            player_input.aim = window_query.get_single().ok().and_then(|window| window.cursor_position());
+            player_input.fire = action_state.pressed(Action::Fire);
+        }
+    }
 }
 
-pub fn spawn_player(mut commands: Commands, asset_server : Res<AssetServer>, window_query : Query<&Window, With<PrimaryWindow>>){
-    let window = window_query.get_single().unwrap();
+pub fn spawn_player(mut commands: Commands, asset_server : Res<AssetServer>, playfield : Res<Playfield>){
     commands.spawn((
             SpriteBundle{
             transform : Transform{
-                translation: Vec3::new(window.width()/2.0, window.height()/2.5, 0.0),
+                translation: Vec3::new(playfield.width/2.0, playfield.height/2.5, 0.0),
                 scale: Vec3::splat(0.2), // Decrease the size by half along all axes
                 ..default()
                 },
             texture : asset_server.load("Sprites/spaceShips_008.png"),
             ..default()
         },
-        Player{health: 100, speed : 250.0, size : Vec2::new(15.0, 15.0), max_health : 100}
+        Player{speed : 250.0, size : Vec2::new(15.0, 15.0)},
+        Health::new(100),
+        Weapon::default(),
+        ProjectileUpgrades::default()
     ));
 }
 
-pub fn player_movement(keyboard_input: Res<Input<KeyCode>>, mut player_query: Query<(&mut Transform, &Player), With<Player>>, time: Res<Time>){
+pub fn player_movement(player_input : Res<PlayerInput>, mut player_query: Query<(&mut Transform, &Player), With<Player>>, time: Res<Time>){
     if let Ok((mut transform, player)) = player_query.get_single_mut(){
-        let mut direction = Vec3::ZERO;
+        let mut direction = player_input.movement;
 
-        if keyboard_input.pressed(KeyCode::W) {direction += Vec3::new(0.0, 1.0, 0.0); transform.rotation = Quat::from_rotation_z(0.0);}
-        if keyboard_input.pressed(KeyCode::A) {direction += Vec3::new(-1.0, 0.0, 0.0); transform.rotation = Quat::from_rotation_z(1.5708);}
-        if keyboard_input.pressed(KeyCode::S) {direction += Vec3::new(0.0, -1.0, 0.0); transform.rotation = Quat::from_rotation_z(3.14159);}
-        if keyboard_input.pressed(KeyCode::D) {direction += Vec3::new(1.0, 0.0, 0.0); transform.rotation = Quat::from_rotation_z(-1.5708);}
+        if direction.y > 0.0 {transform.rotation = Quat::from_rotation_z(0.0);}
+        if direction.x < 0.0 {transform.rotation = Quat::from_rotation_z(1.5708);}
+        if direction.y < 0.0 {transform.rotation = Quat::from_rotation_z(3.14159);}
+        if direction.x > 0.0 {transform.rotation = Quat::from_rotation_z(-1.5708);}
         if direction.length() > 0.0 {
             direction = direction.normalize();}
 
@@ -146,16 +164,15 @@ pub fn player_movement(keyboard_input: Res<Input<KeyCode>>, mut player_query: Qu
     }
 }
 
-pub fn confine_player_movement(mut player_query: Query<&mut Transform, With<Player>>, window_query : Query<&Window, With<PrimaryWindow>>){
+pub fn confine_player_movement(mut player_query: Query<&mut Transform, With<Player>>, playfield : Res<Playfield>){
 
     if let Ok(mut player_transform) = player_query.get_single_mut(){
-        let window = window_query.get_single().unwrap();
 
         let half_player_size = PLAYER_SIZE/2.0;
         let x_lim = half_player_size;
-        let x_max = window.width() - half_player_size;
+        let x_max = playfield.width - half_player_size;
         let y_lim = half_player_size;
-        let y_max = window.height() - half_player_size;
+        let y_max = playfield.height - half_player_size;
 
         let mut translation = player_transform.translation;
         if translation.x < x_lim{translation.x = x_lim;}
@@ -168,282 +185,109 @@ pub fn confine_player_movement(mut player_query: Query<&mut Transform, With<Play
 
 }
 
-pub fn player_shoot(mut commands: Commands, mouse_input : Res<Input<MouseButton>>, player_query: Query<(&Transform, Entity), With<Player>>, asset_server : Res<AssetServer>,  blaster_timer: ResMut<BlasterCooldownTimer>, window_query : Query<&Window, With<PrimaryWindow>>, audio : Res<Audio>){
-    if let Ok((player_transform, player_entity)) = player_query.get_single(){
-        let translation = player_transform.translation;
-         if let Some(cursor_position) = window_query.single().cursor_position(){
-            if mouse_input.pressed(MouseButton::Left) && blaster_timer.timer.just_finished(){
-                let angle = Quat::from_rotation_z((cursor_position.y - translation.y).atan2(cursor_position.x - translation.x) - PI/2.0);
-                commands.spawn((
-                        SpriteBundle{
-                            transform: Transform{
-                                translation: Vec3::new(translation.x, translation.y, 0.0),
-                                scale: Vec3::splat(0.2),
-                                ..default()
-                            },
-                            texture : asset_server.load("Sprites/spaceMissiles_027.png"),
-                            ..default()
-                        },
-                        
-                        Bullet{speed: BULLET_SPEED, direction : Vec2::new(cursor_position.x - translation.x, cursor_position.y-translation.y).normalize(), size : Vec2::new(10.0, 10.0), damage : 50, instant : Instant::now()}
-                    ));
-                commands.entity(player_entity).insert(
-                    Transform{
-                        translation : translation,
-                        rotation : angle,
-                        scale : Vec3::splat(0.2),
-                        ..default()
-                    });
-                    let sound_effect = asset_server.load("Audio/laserSmall_000.ogg");
-                    audio.play(sound_effect); 
-            }
-        }
-         
+//the selected weapon fires at the aim once its own cooldown is over, the charge shot charges while fire is held and fires when it is let go
+pub fn player_shoot(mut weapon_firer : WeaponFirer, player_input : Res<PlayerInput>, mut player_query: Query<(&mut Transform, &mut Weapon, &ProjectileUpgrades), With<Player>>, base_query : Query<&Base>, mut sound_effects : EventWriter<SoundEffect>, time : Res<Time>){
+    let Ok((mut player_transform, mut weapon, upgrades)) = player_query.get_single_mut() else {
+        return;
+    };
+    let Some(cursor_position) = player_input.aim else {
+        return;
+    };
+    let translation = player_transform.translation;
+    let aim = cursor_position - translation.truncate();
+    let kind = weapon.selected;
+    if aim == Vec2::ZERO || !weapon.ready(kind){
+        return;
     }
-
+    let charge = match (kind, player_input.fire) {
+        (WeaponKind::ChargeShot, true) => {
+            weapon.charge = (weapon.charge + time.delta_seconds()).min(CHARGE_TIME);
+            return;
+        },
+        (WeaponKind::ChargeShot, false) if weapon.charge > 0.0 => std::mem::take(&mut weapon.charge) / CHARGE_TIME,
+        (_, true) => 0.0,
+        _ => return
+    };
+    let base_level = base_query.get_single().map_or(1, |base| base.level);
+    let stats = kind.stats(base_level);
+    weapon_firer.fire(kind, &WeaponStats{projectile : player_projectile(kind, base_level, upgrades), ..stats}, translation, aim, charge);
+    weapon.cooldowns[kind.index()] = stats.cooldown;
+    player_transform.rotation = Quat::from_rotation_z(aim.y.atan2(aim.x) - PI/2.0);
+    sound_effects.send(SoundEffect(kind.sound()));
 }
 
-pub fn player_shoot_enemy(mut commands: Commands, mut enemy_query: Query<(Entity, &Transform, &mut Enemy)>, mut bullet_query: Query<(Entity, &Transform, &Bullet), With<Bullet>>, asset_server : Res<AssetServer>, audio : Res<Audio>){
-    
-    for (enemy_entity, enemy_transform, mut enemy) in enemy_query.iter_mut(){
-        for (bullet_entity, bullet_transform, bullet) in bullet_query.iter_mut(){
-            if collide(enemy_transform.translation, enemy.size, bullet_transform.translation, bullet.size).is_some(){
-                commands.entity(bullet_entity).despawn();
-                enemy.health -= bullet.damage;
-                if enemy.health <= 0{
-                    let sound_effect = asset_server.load("Audio/explosionCrunch_004.ogg");
-                    let sound_effect_two = asset_server.load("Audio/lowFrequency_explosion_001.ogg");
-                    match enemy.variant {
-                        EnemyType::Pawn => {
-                            let reward_chance = random::<f32>();
-                            if reward_chance < 0.1{
-                                commands.spawn((SpriteBundle{
-                                    transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                    texture : asset_server.load("Sprites/spaceParts_008.png"),
-                                    ..default()
-                                },
-                                Part{part_tier : PartTier::Blue, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                            ));
-                            }
-                            commands.entity(enemy_entity).despawn();
-                            audio.play(sound_effect);
-                        },
-                        EnemyType::Stinger =>{
-                            let reward_chance = random::<f32>();
-                            if reward_chance < 0.1{
-                                commands.spawn((SpriteBundle{
-                                    transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                    texture : asset_server.load("Sprites/spaceParts_013.png"),
-                                    ..default()
-                                },
-                                Part{part_tier : PartTier::Red, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                            ));
-                            }
-                            commands.entity(enemy_entity).despawn();
-                            audio.play(sound_effect);
-                        },
-                        EnemyType::Rogue => {
-                            let reward_chance = random::<f32>();
-                            if reward_chance < 0.2{
-                                let reward_tier_chance = random::<f32>();
-                                if reward_tier_chance < 0.4{
-                                    commands.spawn((SpriteBundle{
-                                        transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                        texture : asset_server.load("Sprites/spaceParts_013.png"),
-                                        ..default()
-                                    },
-                                    Part{part_tier : PartTier::Red, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                                ));
-                                }
-                                else{
-                                    commands.spawn((SpriteBundle{
-                                        transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                        texture : asset_server.load("Sprites/spaceParts_008.png"),
-                                        ..default()
-                                    },
-                                    Part{part_tier : PartTier::Blue, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                                ));
-                                }
-                            }
-                            commands.entity(enemy_entity).despawn();
-                            audio.play(sound_effect);
-                        },
-                        EnemyType::Splitter{split_count : count, instant : _, direction: _} => {
-                            if count == 0{
-                                for _ in 0..2{
-                                    commands.spawn((SpriteBundle{
-                                        transform : Transform{
-                                            translation : enemy_transform.translation + Vec3::new(10.0, 10.0, 0.0),
-                                            scale : Vec3::splat(0.25),
-                                            ..default()
-                                        },
-                                        texture : asset_server.load("Sprites/splitter.png"),
-                                        ..default()
-    
-                                    },
-                                    Enemy{health : 150, variant: EnemyType::Splitter { split_count: 1, instant: Instant::now(), direction : Vec3::new(rand::thread_rng().gen_range(-1.0..=1.0), rand::thread_rng().gen_range(-1.0..=1.0), 0.0)}, speed : 20.0, size : Vec2::new(10.0, 10.0)}
-                                ));
-                                }     
-                                commands.entity(enemy_entity).despawn();
-                                audio.play(sound_effect_two);
-                            }
-                            else if count == 1{
-                                for _ in 0..2{
-                                    commands.spawn((SpriteBundle{
-                                        transform : Transform{
-                                            translation : enemy_transform.translation + Vec3::new(10.0, 10.0, 0.0),
-                                            scale : Vec3::splat(0.20),
-                                            ..default()
-                                        },
-                                        texture : asset_server.load("Sprites/splitter.png"),
-                                        ..default()
-    
-                                    },
-                                    Enemy{health : 100, variant: EnemyType::Splitter { split_count: 2, instant: Instant::now(), direction : Vec3::new(rand::thread_rng().gen_range(-1.0..=1.0), rand::thread_rng().gen_range(-1.0..=1.0), 0.0)}, speed : 25.0, size : Vec2::new(5.0, 5.0)}
-                                ));
-                                }
-                                let reward_chance = random::<f32>();
-                                if reward_chance < 0.3{
-                                    let reward_tier_chance = random::<f32>();
-                                    if reward_tier_chance < 0.5{
-                                        commands.spawn((SpriteBundle{
-                                            transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                            texture : asset_server.load("Sprites/spaceParts_013.png"),
-                                            ..default()
-                                        },
-                                        Part{part_tier : PartTier::Red, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                                    ));
-                                    }
-                                    
-                                    }else{
-                                    commands.spawn((SpriteBundle{
-                                        transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                        texture : asset_server.load("Sprites/spaceParts_008.png"),
-                                        ..default()
-                                    },
-                                    Part{part_tier : PartTier::Blue, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                                    ));
-                                    }
-                                commands.entity(enemy_entity).despawn();
-                                audio.play(sound_effect_two);
-                            }
-                            else {
-                                commands.entity(enemy_entity).despawn();
-                                audio.play(sound_effect);
-                            }
-                        },
-
-                        EnemyType::Bishop => {
-                            let reward_chance = random::<f32>();
-                            if reward_chance < 0.3{
-                                let reward_tier_chance = random::<f32>();
-                                if reward_tier_chance < 0.25{
-                                    commands.spawn((SpriteBundle{
-                                        transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                        texture : asset_server.load("Sprites/spaceParts_025.png"),
-                                        ..default()
-                                    },
-                                    Part{part_tier : PartTier::Green, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                                ));
-                                }
-                                else if reward_tier_chance < 0.5{
-                                    commands.spawn((SpriteBundle{
-                                        transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                        texture : asset_server.load("Sprites/spaceParts_013.png"),
-                                        ..default()
-                                    },
-                                    Part{part_tier : PartTier::Red, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                                ));
-                                }
-                                else{
-                                    commands.spawn((SpriteBundle{
-                                        transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                        texture : asset_server.load("Sprites/spaceParts_008.png"),
-                                        ..default()
-                                    },
-                                    Part{part_tier : PartTier::Blue, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                                ));      
-                                }
-                            }
-                            commands.entity(enemy_entity).despawn();
-                            audio.play(sound_effect);
-                        },
-                        EnemyType::Propagator => {
-                            let reward_tier_chance = random::<f32>();
-                            if reward_tier_chance < 0.4{
-                                commands.spawn((SpriteBundle{
-                                    transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                    texture : asset_server.load("Sprites/spaceParts_025.png"),
-                                    ..default()
-                                },
-                                Part{part_tier : PartTier::Green, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                            ));
-                            }
-                            else if reward_tier_chance < 0.5{
-                                commands.spawn((SpriteBundle{
-                                    transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                    texture : asset_server.load("Sprites/spaceParts_013.png"),
-                                    ..default()
-                                },
-                                Part{part_tier : PartTier::Red, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                            ));
-                            }
-                            else{
-                                commands.spawn((SpriteBundle{
-                                    transform : get_enemy_transform_0_2(enemy_transform.translation),
-                                    texture : asset_server.load("Sprites/spaceParts_008.png"),
-                                    ..default()
-                                },
-                                Part{part_tier : PartTier::Blue, size : Vec2::new(15.0, 15.0), instant : Instant::now()}
-                            ));      
-                            }
-                            commands.entity(enemy_entity).despawn();
-                            audio.play(sound_effect);
-                        },
-
-                        _ => {
-                            commands.entity(enemy_entity).despawn();
-                            audio.play(sound_effect);
-                        }
-                        
-                    }
-                   
-                    
+//a bullet hits every enemy it touches that it hasn't hit yet and is used up once it has no pierce left
+//every hit pushes the enemy back a little and some weapons push a lot harder, turning knockback off in the settings stops both
+pub fn player_shoot_enemy(mut commands: Commands, enemy_grid : Res<EnemyGrid>, mut bullet_query: Query<(Entity, &Transform, &mut Bullet), Without<Enemy>>, mut enemy_query : Query<&mut Transform, (With<Enemy>, Without<Bullet>)>, mut damage_event_writer : EventWriter<DamageEvent>, mut status_event_writer : EventWriter<StatusEvent>, settings : Res<Settings>){
+    for (bullet_entity, bullet_transform, mut bullet) in bullet_query.iter_mut(){
+        for enemy in enemy_grid.candidates(bullet_transform.translation, bullet.size / 2.0){
+            if bullet.hits.contains(&enemy.entity) || collide(enemy.translation, enemy.size, bullet_transform.translation, bullet.size).is_none(){
+                continue;
+            }
+            damage_event_writer.send(DamageEvent{source : Some(bullet_entity), target : enemy.entity, amount : bullet.damage, damage_type : DamageType::Bullet});
+            if let Some(effect) = bullet.status{
+                status_event_writer.send(StatusEvent{target : enemy.entity, effect});
+            }
+            let knockback = if settings.knockback {bullet.knockback + HIT_KNOCKBACK} else {0.0};
+            if knockback > 0.0{
+                if let Ok(mut enemy_transform) = enemy_query.get_mut(enemy.entity){
+                    enemy_transform.translation += (bullet.direction * knockback).extend(0.0);
                 }
-                
             }
+            bullet.hits.push(enemy.entity);
+            if bullet.pierce == 0{
+                commands.entity(bullet_entity).despawn();
+                break;
+            }
+            bullet.pierce -= 1;
         }
     }
-
 }
 
 
 
 
-pub fn base_part_collecting(mut commands: Commands, mut base_query: Query<&mut Base, (With<Base>, Without<Player>)>, mut part_query : Query<(Entity, &mut Transform, &Part), (With<Part>, Without<Player>)>, player_query: Query<(&Transform, &Player), (With<Player>, Without<Enemy>, Without<Base>)>, asset_server : Res<AssetServer>, audio : Res<Audio>){
+//where a collected part ends up
+#[derive(SystemParam)]
+pub struct PartCollector<'w, 's>{
+    part_inventory : ResMut<'w, PartInventory>,
+    run_stats : ResMut<'w, RunStats>,
+    base_query : Query<'w, 's, &'static mut Base, (With<Base>, Without<Player>)>
+}
+
+impl PartCollector<'_, '_>{
+    //parts go towards the next base level and are also banked to spend in build mode
+    pub fn collect(&mut self, part : Part){
+        self.part_inventory.add(part.part_tier);
+        self.run_stats.parts += 1;
+        for mut base in self.base_query.iter_mut(){
+            base.parts.push(part);
+            println!("{:?}", base.parts_required);
+            println!("{:?}", base.parts);
+            println!("{:?}", base.level);
+        }
+    }
+}
+
+pub fn base_part_collecting(mut commands: Commands, mut part_collector : PartCollector, mut part_query : Query<(Entity, &mut Transform, &Part), (With<Part>, Without<Player>)>, player_query: Query<(&Transform, &Player), (With<Player>, Without<Enemy>, Without<Base>)>, mut sound_effects : EventWriter<SoundEffect>, simulation_clock : Res<SimulationClock>){
     if let Ok((player_transform, player)) = player_query.get_single(){
         for (part_entity, mut part_transform, part) in part_query.iter_mut(){
             if collide(player_transform.translation, player.size, part_transform.translation, part.size).is_some(){
                 match part.part_tier{
                     PartTier::Blue => {
-                        audio.play(asset_server.load("Audio/impactMining_002.ogg"));
+                        sound_effects.send(SoundEffect("Audio/impactMining_002.ogg"));
                     },
                     PartTier::Red => {
-                        audio.play(asset_server.load("Audio/impactMining_003.ogg"));
+                        sound_effects.send(SoundEffect("Audio/impactMining_003.ogg"));
                     }
                     PartTier::Green => {
-                        audio.play(asset_server.load("Audio/impactMining_001.ogg"));
+                        sound_effects.send(SoundEffect("Audio/impactMining_001.ogg"));
                     }
                 }
-                for mut base in base_query.iter_mut(){
-                    base.parts.push(*part);
-                    println!("{:?}", base.parts_required);
-                    println!("{:?}", base.parts);
-                    println!("{:?}", base.level);
-                }
+                part_collector.collect(*part);
                 commands.entity(part_entity).despawn();
             }
-            if part.instant.elapsed().as_secs() > 10{
+            if simulation_clock.elapsed_since(part.spawn_time).as_secs() > 10{
                 commands.entity(part_entity).despawn();
             }
             part_transform.rotation *= Quat::from_rotation_z(-PI/360.0);
@@ -451,25 +295,37 @@ pub fn base_part_collecting(mut commands: Commands, mut base_query: Query<&mut B
     }
 }
 
-pub fn enemy_hit_player(mut commands: Commands, enemy_query: Query<(Entity, &Enemy, &Transform), (With<Enemy>, Without<Player>, Without<Base>)>, mut player_query: Query<(&mut Player, &mut Transform), (With<Player>, Without<Enemy>, Without<Base>)>, asset_server : Res<AssetServer>, base_query : Query<&Transform, (With<Base>, Without<Enemy>, Without<Player>)>, audio: Res<Audio>){
-    if let Ok((mut player,  mut player_transform)) = player_query.get_single_mut(){
-        for (enemy_entity, enemy, enemy_transform) in enemy_query.iter(){
-           if collide(player_transform.translation, player.size, enemy_transform.translation, enemy.size).is_some(){
-                //let sound_effect_enemy = ;
-                player.health -= enemy.health;
-                if player.health <= 0{
-                    //let sound_effect_player = asset_server.load("Audio/explosionCrunch_003.ogg");
-                    audio.play(asset_server.load("Audio/explosionCrunch_003.ogg"));
-                    for base_transform in base_query.iter(){
-                        player_transform.translation = base_transform.translation;
-                        player.health = player.max_health;
-                    }
-                }
-                commands.entity(enemy_entity).despawn();
-                audio.play(asset_server.load("Audio/explosionCrunch_002.ogg"));
-                
-                
+pub fn enemy_hit_player(enemy_grid : Res<EnemyGrid>, enemy_query: Query<(&Enemy, &Health), (Without<Player>, Without<Base>)>, player_query: Query<(Entity, &Player, &Transform), (With<Player>, Without<Enemy>, Without<Base>)>, mut damage_event_writer : EventWriter<DamageEvent>, mut sound_effects : EventWriter<SoundEffect>){
+    if let Ok((player_entity, player, player_transform)) = player_query.get_single(){
+        for enemy in enemy_grid.candidates(player_transform.translation, player.size / 2.0){
+           let Ok((enemy_component, enemy_health)) = enemy_query.get(enemy.entity) else {
+                continue;
+           };
+           //bosses aren't used up, boss_contact hits with them
+           if matches!(enemy_component.variant, EnemyType::Boss(_)){
+                continue;
+           }
+           if collide(player_transform.translation, player.size, enemy.translation, enemy.size).is_some(){
+                //the enemy hits as hard as the health it has left and is used up
+                damage_event_writer.send(DamageEvent{source : Some(enemy.entity), target : player_entity, amount : enemy_health.current, damage_type : DamageType::Collision});
+                damage_event_writer.send(DamageEvent{source : None, target : enemy.entity, amount : enemy_health.current, damage_type : DamageType::Collision});
+                sound_effects.send(SoundEffect("Audio/explosionCrunch_002.ogg"));
            }
         }
     }
-}
\ No newline at end of file
+}
+
+type RespawningPlayerQuery<'w, 's> = Query<'w, 's, (&'static mut Transform, &'static mut Health), (With<Player>, Without<Base>)>;
+
+//the player isn't removed, it respawns at the base with full health
+pub fn player_death(mut death_event_reader : EventReader<DeathEvent>, mut player_query: RespawningPlayerQuery, base_query : Query<&Transform, (With<Base>, Without<Player>)>, mut sound_effects : EventWriter<SoundEffect>){
+    for death_event in death_event_reader.iter(){
+        if let Ok((mut player_transform, mut player_health)) = player_query.get_mut(death_event.entity){
+            sound_effects.send(SoundEffect("Audio/explosionCrunch_003.ogg"));
+            for base_transform in base_query.iter(){
+                player_transform.translation = base_transform.translation;
+                player_health.current = player_health.max;
+            }
+        }
+    }
+}
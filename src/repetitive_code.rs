@@ -23,6 +23,9 @@ pub const BUTTON_STYLE: Style = Style {
     ..Style::DEFAULT
 };
 
+//the query every menu button reacts through
+pub type ButtonInteractionQuery<'w, 's, T> = Query<'w, 's, (&'static Interaction, &'static mut BackgroundColor), (Changed<Interaction>, With<T>)>;
+
 /*pub const  IMAGE_STYLE : Style = Style{
     size: Size::new(Val::Px(64.0), Val::Px(64.0)),
     margin : UiRect::new(Val::Px(8.0), Val::Px(8.0), Val::Px(8.0), Val::Px(8.0)),
@@ -53,7 +56,7 @@ pub const PAUSE_MENU_CONTAINER_STYLE: Style = Style {
     flex_direction: FlexDirection::Column,
     justify_content: JustifyContent::Center,
     align_items: AlignItems::Center,
-    size: Size::new(Val::Px(400.0), Val::Px(400.0)),
+    size: Size::new(Val::Px(400.0), Val::Px(500.0)),
     gap: Size::new(Val::Px(8.0), Val::Px(8.0)),
     ..Style::DEFAULT
 };
@@ -98,6 +101,24 @@ pub fn get_final_score_text_style(asset_server: &Res<AssetServer>) -> TextStyle
     }
 }
 
+pub fn get_leaderboard_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("Fonts/FiraMono-Medium.ttf"),
+        font_size: 18.0,
+        color: Color::rgb(1.0, 1.0, 1.0),
+    }
+}
+
+pub const LEADERBOARD_STYLE: Style = Style {
+    display: Display::Flex,
+    flex_direction: FlexDirection::Column,
+    justify_content: JustifyContent::Center,
+    align_items: AlignItems::Center,
+    padding: UiRect::all(Val::Px(12.0)),
+    gap: Size::new(Val::Px(8.0), Val::Px(8.0)),
+    ..Style::DEFAULT
+};
+
 
 pub const HUD_STYLE: Style = Style {
     display: Display::Flex,
@@ -109,6 +130,137 @@ pub const HUD_STYLE: Style = Style {
     ..Style::DEFAULT
 };
 
+//banked parts and the building selected in build mode, under the part icons
+pub const BUILD_HUD_STYLE: Style = Style {
+    position_type: PositionType::Absolute,
+    position: UiRect { left : Val::Px(20.0), right : Val::Auto, top : Val::Px(200.0), bottom : Val::Auto},
+    ..Style::DEFAULT
+};
+
+//the selected weapon, under the banked parts
+pub const WEAPON_HUD_STYLE: Style = Style {
+    position_type: PositionType::Absolute,
+    position: UiRect { left : Val::Px(20.0), right : Val::Auto, top : Val::Px(250.0), bottom : Val::Auto},
+    ..Style::DEFAULT
+};
+
+//the boss health bar, across the top of the screen while a boss is alive
+pub const BOSS_BAR_STYLE: Style = Style {
+    position_type: PositionType::Absolute,
+    position: UiRect { left : Val::Percent(30.0), right : Val::Auto, top : Val::Px(20.0), bottom : Val::Auto},
+    size: Size::new(Val::Percent(40.0), Val::Auto),
+    flex_direction: FlexDirection::Column,
+    align_items: AlignItems::Center,
+    ..Style::DEFAULT
+};
+
+pub const BOSS_BAR_TRACK_STYLE: Style = Style {
+    size: Size::new(Val::Percent(100.0), Val::Px(14.0)),
+    margin : UiRect::new(Val::Px(0.0), Val::Px(0.0), Val::Px(4.0), Val::Px(0.0)),
+    ..Style::DEFAULT
+};
+
+pub const BOSS_BAR_FILL_STYLE: Style = Style {
+    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+    ..Style::DEFAULT
+};
+
+pub const BOSS_BAR_FILL_COLOR: Color = Color::rgb(0.8, 0.15, 0.15);
+
+//wave number and countdown, in the top left corner
+pub const WAVE_HUD_STYLE: Style = Style {
+    position_type: PositionType::Absolute,
+    position: UiRect { left : Val::Px(20.0), right : Val::Auto, top : Val::Px(20.0), bottom : Val::Auto},
+    size: Size::new(Val::Px(240.0), Val::Auto),
+    flex_direction: FlexDirection::Column,
+    ..Style::DEFAULT
+};
+
+pub const WAVE_COUNTDOWN_TRACK_STYLE: Style = Style {
+    size: Size::new(Val::Percent(100.0), Val::Px(8.0)),
+    margin : UiRect::new(Val::Px(0.0), Val::Px(0.0), Val::Px(4.0), Val::Px(0.0)),
+    ..Style::DEFAULT
+};
+
+pub const WAVE_COUNTDOWN_FILL_STYLE: Style = Style {
+    size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+    ..Style::DEFAULT
+};
+
+pub const WAVE_COUNTDOWN_FILL_COLOR: Color = Color::rgb(0.35, 0.75, 0.35);
+
+//"Wave N incoming", across the middle of the screen above the base
+pub const WAVE_BANNER_STYLE: Style = Style {
+    position_type: PositionType::Absolute,
+    position: UiRect { left : Val::Px(0.0), right : Val::Auto, top : Val::Percent(25.0), bottom : Val::Auto},
+    size: Size::new(Val::Percent(100.0), Val::Auto),
+    justify_content: JustifyContent::Center,
+    ..Style::DEFAULT
+};
+
+//what the last wave came to, on the right before the next one starts
+pub const WAVE_SUMMARY_STYLE: Style = Style {
+    position_type: PositionType::Absolute,
+    position: UiRect { left : Val::Auto, right : Val::Px(20.0), top : Val::Px(150.0), bottom : Val::Auto},
+    padding: UiRect::all(Val::Px(12.0)),
+    ..Style::DEFAULT
+};
+
+//base and player health, under the wave countdown
+pub const HEALTH_HUD_STYLE: Style = Style {
+    position_type: PositionType::Absolute,
+    position: UiRect { left : Val::Px(20.0), right : Val::Auto, top : Val::Px(60.0), bottom : Val::Auto},
+    size: Size::new(Val::Px(240.0), Val::Auto),
+    flex_direction: FlexDirection::Column,
+    ..Style::DEFAULT
+};
+
+pub const HEALTH_BAR_TRACK_STYLE: Style = Style {
+    size: Size::new(Val::Percent(100.0), Val::Px(10.0)),
+    margin : UiRect::new(Val::Px(0.0), Val::Px(0.0), Val::Px(2.0), Val::Px(6.0)),
+    ..Style::DEFAULT
+};
+
+pub const HEALTH_BAR_FILL_STYLE: Style = Style {
+    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+    ..Style::DEFAULT
+};
+
+pub const HEALTH_BAR_FILL_COLOR: Color = Color::rgb(0.3, 0.8, 0.4);
+pub const LOW_HEALTH_COLOR: Color = Color::rgb(0.9, 0.15, 0.15);
+
+//flashes under the boss bar while the base is nearly gone
+pub const LOW_BASE_WARNING_STYLE: Style = Style {
+    position_type: PositionType::Absolute,
+    position: UiRect { left : Val::Px(0.0), right : Val::Auto, top : Val::Px(80.0), bottom : Val::Auto},
+    size: Size::new(Val::Percent(100.0), Val::Auto),
+    justify_content: JustifyContent::Center,
+    ..Style::DEFAULT
+};
+
+pub fn get_wave_banner_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("Fonts/FiraMono-Medium.ttf"),
+        font_size: 40.0,
+        color: Color::rgb(1.0, 1.0, 1.0),
+    }
+}
+
+pub fn get_build_hud_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("Fonts/FiraMono-Medium.ttf"),
+        font_size: 18.0,
+        color: Color::rgb(1.0, 1.0, 1.0),
+    }
+}
+
+pub fn get_damage_number_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("Fonts/FiraMono-Medium.ttf"),
+        font_size: 16.0,
+        color: Color::rgb(1.0, 1.0, 1.0),
+    }
+}
 
 pub const IMAGE_STYLE: Style = Style {
     size: Size::new(Val::Px(25.0), Val::Px(25.0)),
@@ -136,3 +288,25 @@ pub const TITLE_STYLE: Style = Style {
 
 
 
+
+pub const CONTROLS_ROW_STYLE: Style = Style {
+    flex_direction: FlexDirection::Row,
+    justify_content: JustifyContent::SpaceBetween,
+    align_items: AlignItems::Center,
+    size: Size::new(Val::Px(560.0), Val::Px(36.0)),
+    ..Style::DEFAULT
+};
+
+pub const BINDING_BUTTON_STYLE: Style = Style {
+    size: Size::new(Val::Px(340.0), Val::Px(32.0)),
+    justify_content: JustifyContent::Center,
+    align_items: AlignItems::Center,
+    ..Style::DEFAULT
+};
+
+pub const SETTING_BUTTON_STYLE: Style = Style {
+    size: Size::new(Val::Px(120.0), Val::Px(32.0)),
+    justify_content: JustifyContent::Center,
+    align_items: AlignItems::Center,
+    ..Style::DEFAULT
+};
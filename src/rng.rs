@@ -0,0 +1,84 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use bevy::prelude::*;
+
+use super::AppState;
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<GameRng>()
+
+        //every run starts its streams from the seed
+        .add_system(start_run_rng.in_schedule(OnEnter(AppState::Game)));
+    }
+}
+
+
+
+//spread the streams out so they don't share a sequence
+const STREAM_OFFSET : u64 = 0x9E37_79B9_7F4A_7C15;
+
+//One seed drives every stream, so a run can be replayed from the seed alone.
+//Each subsystem draws from its own stream so adding a roll in one place doesn't shift the others.
+//ChaCha gives the same numbers on every platform and rand version, which StdRng doesn't promise.
+#[derive(Resource)]
+pub struct GameRng{
+    pub seed : u64,
+    //set when the player typed a seed in the main menu, otherwise each run rolls a new one
+    pub fixed_seed : bool,
+    pub waves : ChaCha8Rng,
+    pub movement : ChaCha8Rng,
+    pub loot : ChaCha8Rng,
+    pub base : ChaCha8Rng,
+    pub abilities : ChaCha8Rng
+}
+
+impl Default for GameRng{
+    fn default() -> GameRng {
+        GameRng::from_seed(random())
+    }
+}
+
+impl GameRng{
+    pub fn from_seed(seed : u64) -> GameRng{
+        GameRng{
+            seed,
+            fixed_seed : false,
+            waves : stream(seed, 1),
+            movement : stream(seed, 2),
+            loot : stream(seed, 3),
+            base : stream(seed, 4),
+            abilities : stream(seed, 5)
+        }
+    }
+
+    pub fn reseed(&mut self, seed : u64){
+        let fixed_seed = self.fixed_seed;
+        *self = GameRng::from_seed(seed);
+        self.fixed_seed = fixed_seed;
+    }
+
+    //how far each stream has been drawn, saved with a run so a continued run rolls on from where it stopped
+    pub fn positions(&self) -> [u64; 5]{
+        [&self.waves, &self.movement, &self.loot, &self.base, &self.abilities].map(|stream| stream.get_word_pos() as u64)
+    }
+
+    pub fn set_positions(&mut self, positions : [u64; 5]){
+        let streams = [&mut self.waves, &mut self.movement, &mut self.loot, &mut self.base, &mut self.abilities];
+        for (stream, position) in streams.into_iter().zip(positions){
+            stream.set_word_pos(position as u128);
+        }
+    }
+}
+
+fn stream(seed : u64, index : u64) -> ChaCha8Rng{
+    ChaCha8Rng::seed_from_u64(seed.wrapping_add(index.wrapping_mul(STREAM_OFFSET)))
+}
+
+pub fn start_run_rng(mut game_rng : ResMut<GameRng>){
+    let seed = if game_rng.fixed_seed {game_rng.seed} else {random()};
+    game_rng.reseed(seed);
+}
@@ -0,0 +1,710 @@
+use std::{fs, path::PathBuf, time::Duration};
+use serde::{Deserialize, Serialize};
+use bevy::{prelude::*, ecs::system::SystemParam};
+
+use crate::{player::*, base::*, turret::*, enemy::*, part::*, bullet::*, wave::{WaveTimer, WaveStats, TrickleBudget}, rng::GameRng, game_time::SimulationClock, health::Health, building::*, weapon::Weapon, boss::*, enemy_projectile::EnemyProjectile, status::*, director::*, loot::{UpgradePickup, LootPity, spawn_upgrade_pickup}};
+use super::{AppState, RunStats};
+
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .init_resource::<SaveGameSlot>()
+        .add_event::<SaveGameRequest>()
+
+        .add_system(save_game.run_if(in_state(AppState::Game)))
+        //the fresh player and base spawned when entering the game are replaced on the first update
+        .add_system(restore_save_game.in_set(OnUpdate(AppState::Game)).run_if(resource_exists::<PendingSave>()));
+    }
+}
+
+
+
+pub const SAVE_GAME_PATH : &str = "savegame.ron";
+
+
+//Resources and events
+
+//A saved run is a suspended run, restoring it removes the file.
+//Only used when path is set, so headless runs and tests don't touch the player's save.
+#[derive(Resource, Default)]
+pub struct SaveGameSlot{
+    pub path : Option<PathBuf>
+}
+
+//the run to restore once the game state is entered
+#[derive(Resource)]
+pub struct PendingSave(pub SaveGame);
+
+//sent by the pause menu, the run is saved and the game goes back to the main menu
+pub struct SaveGameRequest;
+
+
+//Save file
+//spawn times are stored as ages in seconds, the simulation clock starts over when the run is continued
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame{
+    pub seed : u64,
+    //how far each random stream had been drawn, see GameRng::positions
+    pub rng_positions : [u64; 5],
+    pub kills : u64,
+    pub parts_collected : u64,
+    pub wave_timer : SavedWaveTimer,
+    pub wave_stats : SavedWaveStats,
+    pub trickle_budget : f32,
+    //seconds until the trickle spawner tries again
+    pub trickle_cooldown_left : f32,
+    pub loot_misses : Vec<u32>,
+    pub player : Option<SavedPlayer>,
+    pub base : Option<SavedBase>,
+    pub base_buildings : Vec<SavedSprite>,
+    pub turrets : Vec<SavedTurret>,
+    pub enemies : Vec<SavedEnemy>,
+    pub deacons : Vec<SavedDeacon>,
+    pub parts : Vec<SavedPart>,
+    pub bullets : Vec<SavedBullet>,
+    pub missiles : Vec<SavedMissile>,
+    pub inventory : PartInventory,
+    //placed by the player, placed turrets are saved with the other turrets
+    pub buildings : Vec<SavedBuilding>,
+    pub enemy_projectiles : Vec<SavedEnemyProjectile>,
+    pub upgrade_pickups : Vec<SavedUpgradePickup>,
+    pub director : SavedDirector
+}
+
+//the counts since the last sample start over
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SavedDirector{
+    pub difficulty : Difficulty,
+    pub intensity : f32,
+    pub base_trend : f32,
+    pub edge_weights : [f32; 4]
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSprite{
+    pub texture : Option<String>,
+    pub translation : [f32; 3],
+    pub rotation : [f32; 4],
+    pub scale : [f32; 3],
+    //rgba
+    pub color : [f32; 4],
+    pub custom_size : Option<[f32; 2]>
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedWaveTimer{
+    pub elapsed : f32,
+    pub duration : f32,
+    pub wave : usize,
+    pub enemy_count : i32,
+    pub variants : Vec<EnemyKind>,
+    pub unlocked : Vec<EnemyKind>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SavedWaveStats{
+    pub wave : usize,
+    pub kills_at_start : u64,
+    pub parts_at_start : u64,
+    pub base_damage : i64
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedPlayer{
+    pub sprite : SavedSprite,
+    pub health : i64,
+    pub speed : f32,
+    pub size : [f32; 2],
+    pub max_health : i64,
+    pub weapon : Weapon,
+    pub upgrades : ProjectileUpgrades
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedBase{
+    pub sprite : SavedSprite,
+    pub health : i64,
+    pub max_health : i64,
+    pub level : i64,
+    pub parts : Vec<SavedPartStats>,
+    pub parts_required : Vec<PartTier>,
+    pub max_parts : usize,
+    pub leveled_up : bool,
+    pub size : [f32; 2],
+    pub age : f32
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedPartStats{
+    pub part_tier : PartTier,
+    pub size : [f32; 2],
+    pub age : f32
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedTurret{
+    pub sprite : SavedSprite,
+    pub stats : TurretStats,
+    pub targeting : TargetingMode,
+    pub weapon : TurretWeapon,
+    //seconds until it can fire again
+    pub cooldown_left : f32,
+    //set when the player placed it, so it can still be sold
+    pub building : Option<BuildingKind>
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedBuilding{
+    pub sprite : SavedSprite,
+    pub kind : BuildingKind,
+    pub health : Option<i64>
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedPart{
+    pub sprite : SavedSprite,
+    pub part : SavedPartStats
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SavedEnemyType{
+    Pawn,
+    Stinger,
+    Splitter{split_count : usize, age : f32, direction : [f32; 3]},
+    Rogue,
+    Bishop,
+    Propagator,
+    Neonate{direction : [f32; 3], age : f32},
+    Gunner{shot_age : f32},
+    //charge is the direction and time left of a charge at the base
+    Boss{phase : BossPhase, ability_age : f32, charge : Option<([f32; 3], f32)>, contact_age : f32}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedEnemy{
+    pub sprite : SavedSprite,
+    pub health : i64,
+    pub max_health : i64,
+    pub variant : SavedEnemyType,
+    pub speed : f32,
+    pub size : [f32; 2],
+    pub status_effects : Vec<StatusEffect>
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedDeacon{
+    pub sprite : SavedSprite,
+    pub speed : f32,
+    pub size : [f32; 2],
+    pub direction : [f32; 3],
+    pub age : f32
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedBullet{
+    pub sprite : SavedSprite,
+    pub speed : f32,
+    pub direction : [f32; 2],
+    pub size : [f32; 2],
+    pub damage : i64,
+    pub age : f32,
+    //fired by a turret
+    pub turret : bool,
+    pub pierce : u32,
+    pub bounces : u32,
+    pub knockback : f32,
+    pub status : Option<StatusEffect>
+}
+
+//the target isn't kept, the missile picks the nearest enemy again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedMissile{
+    pub sprite : SavedSprite,
+    pub direction : [f32; 2],
+    pub speed : f32,
+    pub turn_rate : f32,
+    pub damage : i64,
+    pub splash_radius : f32,
+    pub age : f32
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedEnemyProjectile{
+    pub sprite : SavedSprite,
+    pub direction : [f32; 2],
+    pub speed : f32,
+    pub size : [f32; 2],
+    pub damage : i64,
+    pub age : f32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SavedUpgradePickup{
+    pub translation : [f32; 3],
+    pub modifier : ProjectileModifier
+}
+
+
+fn age(spawn_time : f32, now : f32) -> f32{
+    now - spawn_time
+}
+
+fn spawn_time_from_age(age : f32, now : f32) -> f32{
+    now - age
+}
+
+impl SaveGameSlot{
+    pub fn exists(&self) -> bool{
+        self.path.as_ref().is_some_and(|path| path.exists())
+    }
+
+    pub fn write(&self, save_game : &SaveGame){
+        if let Some(path) = &self.path{
+            let result = ron::ser::to_string_pretty(save_game, ron::ser::PrettyConfig::default())
+                .map_err(|error| error.to_string())
+                .and_then(|contents| fs::write(path, contents).map_err(|error| error.to_string()));
+            if let Err(error) = result{
+                println!("Couldn't save {}: {}", path.display(), error);
+            }
+        }
+    }
+
+    //reads the save, the file is only removed once the run is restored
+    pub fn read(&self) -> Option<SaveGame>{
+        let path = self.path.as_ref()?;
+        let contents = fs::read_to_string(path).ok()?;
+        match ron::from_str(&contents) {
+            Ok(save_game) => Some(save_game),
+            Err(error) => {
+                println!("Couldn't read {}: {}", path.display(), error);
+                None
+            }
+        }
+    }
+
+    pub fn remove(&self){
+        if let Some(path) = &self.path{
+            if let Err(error) = fs::remove_file(path){
+                println!("Couldn't remove {}: {}", path.display(), error);
+            }
+        }
+    }
+}
+
+impl SaveGame{
+    //the wave timer becomes a Timer again, which can't run for a negative or NaN time
+    pub fn validate(&self) -> Result<(), String>{
+        let wave_timer = &self.wave_timer;
+        if !(wave_timer.duration.is_finite() && wave_timer.duration >= 0.0 && wave_timer.elapsed.is_finite() && wave_timer.elapsed >= 0.0){
+            return Err(format!("invalid wave timer, {} of {} seconds", wave_timer.elapsed, wave_timer.duration));
+        }
+        Ok(())
+    }
+}
+
+impl SavedSprite{
+    pub fn new(transform : &Transform, sprite : Option<&Sprite>, texture : Option<&Handle<Image>>, asset_server : &AssetServer) -> SavedSprite{
+        SavedSprite{
+            texture : texture.and_then(|texture| asset_server.get_handle_path(texture)).map(|asset_path| asset_path.path().to_string_lossy().into_owned()),
+            translation : transform.translation.to_array(),
+            rotation : transform.rotation.to_array(),
+            scale : transform.scale.to_array(),
+            color : sprite.map_or(Color::WHITE, |sprite| sprite.color).as_rgba_f32(),
+            custom_size : sprite.and_then(|sprite| sprite.custom_size).map(|custom_size| custom_size.to_array())
+        }
+    }
+
+    pub fn bundle(&self, asset_server : &AssetServer) -> SpriteBundle{
+        SpriteBundle{
+            transform : Transform{
+                translation : Vec3::from_array(self.translation),
+                rotation : Quat::from_array(self.rotation),
+                scale : Vec3::from_array(self.scale)
+            },
+            sprite : Sprite{color : Color::rgba(self.color[0], self.color[1], self.color[2], self.color[3]), custom_size : self.custom_size.map(Vec2::from_array), ..default()},
+            texture : self.texture.as_ref().map(|texture| asset_server.load(texture.as_str())).unwrap_or_default(),
+            ..default()
+        }
+    }
+}
+
+impl SavedPartStats{
+    pub fn new(part : &Part, now : f32) -> SavedPartStats{
+        SavedPartStats{part_tier : part.part_tier, size : part.size.to_array(), age : age(part.spawn_time, now)}
+    }
+
+    pub fn part(&self, now : f32) -> Part{
+        Part{part_tier : self.part_tier, size : Vec2::from_array(self.size), spawn_time : spawn_time_from_age(self.age, now)}
+    }
+}
+
+impl SavedEnemyType{
+    pub fn new(enemy_type : &EnemyType, now : f32) -> SavedEnemyType{
+        match *enemy_type {
+            EnemyType::Pawn => SavedEnemyType::Pawn,
+            EnemyType::Stinger => SavedEnemyType::Stinger,
+            EnemyType::Splitter{split_count, spawn_time, direction} => SavedEnemyType::Splitter{split_count, age : age(spawn_time, now), direction : direction.to_array()},
+            EnemyType::Rogue => SavedEnemyType::Rogue,
+            EnemyType::Bishop => SavedEnemyType::Bishop,
+            EnemyType::Propagator => SavedEnemyType::Propagator,
+            EnemyType::Neonate(gestation) => SavedEnemyType::Neonate{direction : gestation.direction.to_array(), age : age(gestation.spawn_time, now)},
+            EnemyType::Gunner{last_shot} => SavedEnemyType::Gunner{shot_age : age(last_shot, now)},
+            EnemyType::Boss(state) => SavedEnemyType::Boss{phase : state.phase, ability_age : age(state.last_ability, now), charge : state.charge.map(|(direction, until)| (direction.to_array(), until - now)), contact_age : age(state.last_contact, now)}
+        }
+    }
+
+    pub fn enemy_type(&self, now : f32) -> EnemyType{
+        match *self {
+            SavedEnemyType::Pawn => EnemyType::Pawn,
+            SavedEnemyType::Stinger => EnemyType::Stinger,
+            SavedEnemyType::Splitter{split_count, age, direction} => EnemyType::Splitter{split_count, spawn_time : spawn_time_from_age(age, now), direction : Vec3::from_array(direction)},
+            SavedEnemyType::Rogue => EnemyType::Rogue,
+            SavedEnemyType::Bishop => EnemyType::Bishop,
+            SavedEnemyType::Propagator => EnemyType::Propagator,
+            SavedEnemyType::Neonate{direction, age} => EnemyType::Neonate(NeonateGestation{direction : Vec3::from_array(direction), spawn_time : spawn_time_from_age(age, now)}),
+            SavedEnemyType::Gunner{shot_age} => EnemyType::Gunner{last_shot : spawn_time_from_age(shot_age, now)},
+            SavedEnemyType::Boss{phase, ability_age, charge, contact_age} => EnemyType::Boss(BossState{phase, last_ability : spawn_time_from_age(ability_age, now), charge : charge.map(|(direction, time_left)| (Vec3::from_array(direction), now + time_left)), last_contact : spawn_time_from_age(contact_age, now)})
+        }
+    }
+}
+
+
+//System params
+
+//the resources a run is saved from
+#[derive(SystemParam)]
+pub struct RunState<'w>{
+    game_rng : Res<'w, GameRng>,
+    run_stats : Res<'w, RunStats>,
+    wave_timer : Res<'w, WaveTimer>,
+    wave_stats : Res<'w, WaveStats>,
+    trickle_budget : Res<'w, TrickleBudget>,
+    enemy_spawn_timer : Res<'w, EnemySpawnCooldownTimer>,
+    loot_pity : Res<'w, LootPity>,
+    simulation_clock : Res<'w, SimulationClock>,
+    part_inventory : Res<'w, PartInventory>,
+    director : Res<'w, Director>
+}
+
+//the resources a run is restored into
+#[derive(SystemParam)]
+pub struct RunStateMut<'w>{
+    game_rng : ResMut<'w, GameRng>,
+    run_stats : ResMut<'w, RunStats>,
+    wave_timer : ResMut<'w, WaveTimer>,
+    wave_stats : ResMut<'w, WaveStats>,
+    trickle_budget : ResMut<'w, TrickleBudget>,
+    enemy_spawn_timer : ResMut<'w, EnemySpawnCooldownTimer>,
+    loot_pity : ResMut<'w, LootPity>,
+    simulation_clock : Res<'w, SimulationClock>,
+    part_inventory : ResMut<'w, PartInventory>,
+    director : ResMut<'w, Director>
+}
+
+//every saved entity keeps its sprite, T is what else is saved of it
+type SpriteQuery<'w, 's, T, F = ()> = Query<'w, 's, (&'static Transform, Option<&'static Sprite>, Option<&'static Handle<Image>>, T), F>;
+
+//the entities a run is saved from
+#[derive(SystemParam)]
+pub struct SavedEntities<'w, 's>{
+    player_query : SpriteQuery<'w, 's, (&'static Player, &'static Health, &'static Weapon, &'static ProjectileUpgrades)>,
+    base_query : SpriteQuery<'w, 's, (&'static Base, &'static Health)>,
+    base_building_query : SpriteQuery<'w, 's, (), With<BaseBuilding>>,
+    turret_query : SpriteQuery<'w, 's, (&'static Turret, Option<&'static PlacedBuilding>)>,
+    building_query : SpriteQuery<'w, 's, (&'static PlacedBuilding, Option<&'static Health>), Without<Turret>>,
+    enemy_query : SpriteQuery<'w, 's, (&'static Enemy, &'static Health, Option<&'static StatusEffects>)>,
+    deacon_query : SpriteQuery<'w, 's, &'static Deacon>,
+    part_query : SpriteQuery<'w, 's, &'static Part>,
+    bullet_query : SpriteQuery<'w, 's, (&'static Bullet, Option<&'static TurretShot>)>,
+    missile_query : SpriteQuery<'w, 's, &'static TurretMissile>,
+    enemy_projectile_query : SpriteQuery<'w, 's, &'static EnemyProjectile>,
+    upgrade_pickup_query : Query<'w, 's, (&'static Transform, &'static UpgradePickup)>
+}
+
+
+//Systems
+
+pub fn save_game(mut save_requests : EventReader<SaveGameRequest>, save_game_slot : Res<SaveGameSlot>, asset_server : Res<AssetServer>, run_state : RunState, saved_entities : SavedEntities, mut next_app_state : ResMut<NextState<AppState>>){
+    if save_requests.iter().count() == 0{
+        return;
+    }
+    let RunState{game_rng, run_stats, wave_timer, wave_stats, trickle_budget, enemy_spawn_timer, loot_pity, simulation_clock, part_inventory, director} = run_state;
+    let SavedEntities{player_query, base_query, base_building_query, turret_query, building_query, enemy_query, deacon_query, part_query, bullet_query, missile_query, enemy_projectile_query, upgrade_pickup_query} = saved_entities;
+    let now = simulation_clock.now();
+    let save_game = SaveGame{
+        seed : game_rng.seed,
+        rng_positions : game_rng.positions(),
+        kills : run_stats.kills,
+        parts_collected : run_stats.parts,
+        wave_timer : SavedWaveTimer{
+            elapsed : wave_timer.timer.elapsed_secs(),
+            duration : wave_timer.timer.duration().as_secs_f32(),
+            wave : wave_timer.wave,
+            enemy_count : wave_timer.enemy_count,
+            variants : wave_timer.variants.clone(),
+            unlocked : wave_timer.unlocked.clone()
+        },
+        wave_stats : SavedWaveStats{
+            wave : wave_stats.wave,
+            kills_at_start : wave_stats.kills_at_start,
+            parts_at_start : wave_stats.parts_at_start,
+            base_damage : wave_stats.base_damage
+        },
+        trickle_budget : trickle_budget.points,
+        trickle_cooldown_left : enemy_spawn_timer.timer.remaining_secs(),
+        loot_misses : loot_pity.misses.clone(),
+        player : player_query.get_single().ok().map(|(transform, sprite, texture, (player, health, weapon, upgrades))| SavedPlayer{
+            sprite : SavedSprite::new(transform, sprite, texture, &asset_server),
+            health : health.current,
+            speed : player.speed,
+            size : player.size.to_array(),
+            max_health : health.max,
+            weapon : weapon.clone(),
+            upgrades : upgrades.clone()
+        }),
+        base : base_query.get_single().ok().map(|(transform, sprite, texture, (base, health))| SavedBase{
+            sprite : SavedSprite::new(transform, sprite, texture, &asset_server),
+            health : health.current,
+            max_health : health.max,
+            level : base.level,
+            parts : base.parts.iter().map(|part| SavedPartStats::new(part, now)).collect(),
+            parts_required : base.parts_required.clone(),
+            max_parts : base.max_parts,
+            leveled_up : base.leveled_up,
+            size : base.size.to_array(),
+            age : age(base.spawn_time, now)
+        }),
+        base_buildings : base_building_query.iter().map(|(transform, sprite, texture, ())| SavedSprite::new(transform, sprite, texture, &asset_server)).collect(),
+        turrets : turret_query.iter().map(|(transform, sprite, texture, (turret, placed_building))| SavedTurret{
+            sprite : SavedSprite::new(transform, sprite, texture, &asset_server),
+            stats : turret.stats,
+            targeting : turret.targeting,
+            weapon : turret.weapon,
+            cooldown_left : turret.cooldown.remaining_secs(),
+            building : placed_building.map(|placed_building| placed_building.kind)
+        }).collect(),
+        enemies : enemy_query.iter().map(|(transform, sprite, texture, (enemy, health, status_effects))| SavedEnemy{
+            sprite : SavedSprite::new(transform, sprite, texture, &asset_server),
+            health : health.current,
+            max_health : health.max,
+            variant : SavedEnemyType::new(&enemy.variant, now),
+            speed : enemy.speed,
+            size : enemy.size.to_array(),
+            status_effects : status_effects.map(|status_effects| status_effects.effects.clone()).unwrap_or_default()
+        }).collect(),
+        deacons : deacon_query.iter().map(|(transform, sprite, texture, deacon)| SavedDeacon{
+            sprite : SavedSprite::new(transform, sprite, texture, &asset_server),
+            speed : deacon.speed,
+            size : deacon.size.to_array(),
+            direction : deacon.direction.to_array(),
+            age : age(deacon.spawn_time, now)
+        }).collect(),
+        parts : part_query.iter().map(|(transform, sprite, texture, part)| SavedPart{
+            sprite : SavedSprite::new(transform, sprite, texture, &asset_server),
+            part : SavedPartStats::new(part, now)
+        }).collect(),
+        bullets : bullet_query.iter().map(|(transform, sprite, texture, (bullet, turret_shot))| SavedBullet{
+            sprite : SavedSprite::new(transform, sprite, texture, &asset_server),
+            speed : bullet.speed,
+            direction : bullet.direction.to_array(),
+            size : bullet.size.to_array(),
+            damage : bullet.damage,
+            age : age(bullet.spawn_time, now),
+            turret : turret_shot.is_some(),
+            pierce : bullet.pierce,
+            bounces : bullet.bounces,
+            knockback : bullet.knockback,
+            status : bullet.status
+        }).collect(),
+        missiles : missile_query.iter().map(|(transform, sprite, texture, missile)| SavedMissile{
+            sprite : SavedSprite::new(transform, sprite, texture, &asset_server),
+            direction : missile.direction.to_array(),
+            speed : missile.speed,
+            turn_rate : missile.turn_rate,
+            damage : missile.damage,
+            splash_radius : missile.splash_radius,
+            age : age(missile.spawn_time, now)
+        }).collect(),
+        inventory : *part_inventory,
+        buildings : building_query.iter().map(|(transform, sprite, texture, (placed_building, health))| SavedBuilding{
+            sprite : SavedSprite::new(transform, sprite, texture, &asset_server),
+            kind : placed_building.kind,
+            health : health.map(|health| health.current)
+        }).collect(),
+        enemy_projectiles : enemy_projectile_query.iter().map(|(transform, sprite, texture, projectile)| SavedEnemyProjectile{
+            sprite : SavedSprite::new(transform, sprite, texture, &asset_server),
+            direction : projectile.direction.to_array(),
+            speed : projectile.speed,
+            size : projectile.size.to_array(),
+            damage : projectile.damage,
+            age : age(projectile.spawn_time, now)
+        }).collect(),
+        upgrade_pickups : upgrade_pickup_query.iter().map(|(transform, pickup)| SavedUpgradePickup{
+            translation : transform.translation.to_array(),
+            modifier : pickup.modifier
+        }).collect(),
+        director : SavedDirector{
+            difficulty : director.difficulty,
+            intensity : director.intensity,
+            base_trend : director.base_trend,
+            edge_weights : director.edge_weights
+        }
+    };
+    save_game_slot.write(&save_game);
+    next_app_state.set(AppState::MainMenu);
+}
+
+//the fresh player and base the saved ones replace
+type ReplacedOnRestore = Or<(With<Player>, With<Base>)>;
+
+//a save that can't be restored is left on disk and the game goes back to the main menu
+pub fn restore_save_game(mut commands : Commands, pending_save : Res<PendingSave>, save_game_slot : Res<SaveGameSlot>, mut next_app_state : ResMut<NextState<AppState>>, asset_server : Res<AssetServer>, run_state : RunStateMut, replaced_query : Query<Entity, ReplacedOnRestore>){
+    let RunStateMut{mut game_rng, mut run_stats, mut wave_timer, mut wave_stats, mut trickle_budget, mut enemy_spawn_timer, mut loot_pity, simulation_clock, mut part_inventory, mut director} = run_state;
+    let save_game = &pending_save.0;
+    let now = simulation_clock.now();
+    commands.remove_resource::<PendingSave>();
+    if let Err(error) = save_game.validate(){
+        println!("Couldn't continue the saved run: {}", error);
+        next_app_state.set(AppState::MainMenu);
+        return;
+    }
+
+    //the random streams go on from where they were when the run was saved
+    game_rng.reseed(save_game.seed);
+    game_rng.set_positions(save_game.rng_positions);
+    run_stats.kills = save_game.kills;
+    run_stats.parts = save_game.parts_collected;
+    wave_timer.timer = Timer::from_seconds(save_game.wave_timer.duration, TimerMode::Repeating);
+    wave_timer.timer.set_elapsed(Duration::from_secs_f32(save_game.wave_timer.elapsed));
+    wave_timer.wave = save_game.wave_timer.wave;
+    wave_timer.enemy_count = save_game.wave_timer.enemy_count;
+    wave_timer.variants = save_game.wave_timer.variants.clone();
+    wave_timer.unlocked = save_game.wave_timer.unlocked.clone();
+    *wave_stats = WaveStats{
+        wave : save_game.wave_stats.wave,
+        kills_at_start : save_game.wave_stats.kills_at_start,
+        parts_at_start : save_game.wave_stats.parts_at_start,
+        base_damage : save_game.wave_stats.base_damage
+    };
+    trickle_budget.points = save_game.trickle_budget;
+    let trickle_cooldown = enemy_spawn_timer.timer.duration().as_secs_f32();
+    enemy_spawn_timer.timer.set_elapsed(Duration::from_secs_f32((trickle_cooldown - save_game.trickle_cooldown_left).max(0.0)));
+    loot_pity.misses = save_game.loot_misses.clone();
+    *part_inventory = save_game.inventory;
+    *director = Director{
+        intensity : save_game.director.intensity,
+        base_trend : save_game.director.base_trend,
+        edge_weights : save_game.director.edge_weights,
+        ..Director::new(save_game.director.difficulty)
+    };
+    director.kills_at_sample = save_game.kills;
+
+    for entity in replaced_query.iter(){
+        commands.entity(entity).despawn();
+    }
+    if let Some(player) = &save_game.player{
+        commands.spawn((
+            player.sprite.bundle(&asset_server),
+            Player{speed : player.speed, size : Vec2::from_array(player.size)},
+            Health{current : player.health, max : player.max_health},
+            player.weapon.clone(),
+            player.upgrades.clone()
+        ));
+    }
+    if let Some(base) = &save_game.base{
+        commands.spawn((
+            base.sprite.bundle(&asset_server),
+            Base{
+                level : base.level,
+                parts : base.parts.iter().map(|part| part.part(now)).collect(),
+                parts_required : base.parts_required.clone(),
+                max_parts : base.max_parts,
+                leveled_up : base.leveled_up,
+                size : Vec2::from_array(base.size),
+                spawn_time : spawn_time_from_age(base.age, now)
+            },
+            Health{current : base.health, max : base.max_health}
+        ));
+    }
+    for base_building in save_game.base_buildings.iter(){
+        commands.spawn((base_building.bundle(&asset_server), BaseBuilding{}));
+    }
+    for turret in save_game.turrets.iter(){
+        //the target is picked again on the next update
+        let mut restored = Turret{weapon : turret.weapon, ..Turret::new(turret.stats, turret.targeting)};
+        let cooldown = restored.cooldown.duration().as_secs_f32();
+        restored.cooldown.set_elapsed(Duration::from_secs_f32((cooldown - turret.cooldown_left).max(0.0)));
+        let mut turret_entity = commands.spawn((turret.sprite.bundle(&asset_server), restored));
+        if let Some(kind) = turret.building{
+            turret_entity.insert(PlacedBuilding{kind});
+        }
+    }
+    for building in save_game.buildings.iter(){
+        let mut building_entity = commands.spawn((building.sprite.bundle(&asset_server), PlacedBuilding{kind : building.kind}));
+        if let (Some(current), Some(max)) = (building.health, building.kind.health()){
+            building_entity.insert(Health{current, max});
+        }
+    }
+    for enemy in save_game.enemies.iter(){
+        let restored = Enemy{variant : enemy.variant.enemy_type(now), speed : enemy.speed, size : Vec2::from_array(enemy.size)};
+        let status_effects = StatusEffects{effects : enemy.status_effects.clone(), ..default()};
+        let mut sprite_bundle = enemy.sprite.bundle(&asset_server);
+        //hit flashes aren't saved, so one caught mid flash gets its tint back
+        sprite_bundle.sprite.color = enemy_tint(&restored, Some(&status_effects));
+        commands.spawn((
+            sprite_bundle,
+            restored,
+            Health{current : enemy.health, max : enemy.max_health},
+            status_effects
+        ));
+    }
+    for deacon in save_game.deacons.iter(){
+        commands.spawn((
+            deacon.sprite.bundle(&asset_server),
+            Deacon{speed : deacon.speed, size : Vec2::from_array(deacon.size), direction : Vec3::from_array(deacon.direction), spawn_time : spawn_time_from_age(deacon.age, now)}
+        ));
+    }
+    for part in save_game.parts.iter(){
+        commands.spawn((part.sprite.bundle(&asset_server), part.part.part(now)));
+    }
+    for bullet in save_game.bullets.iter(){
+        let mut bullet_entity = commands.spawn((
+            bullet.sprite.bundle(&asset_server),
+            Bullet{
+                speed : bullet.speed,
+                direction : Vec2::from_array(bullet.direction),
+                size : Vec2::from_array(bullet.size),
+                damage : bullet.damage,
+                spawn_time : spawn_time_from_age(bullet.age, now),
+                pierce : bullet.pierce,
+                bounces : bullet.bounces,
+                knockback : bullet.knockback,
+                hits : Vec::new(),
+                status : bullet.status
+            }
+        ));
+        if bullet.turret{
+            bullet_entity.insert(TurretShot);
+        }
+    }
+    for missile in save_game.missiles.iter(){
+        commands.spawn((
+            missile.sprite.bundle(&asset_server),
+            TurretMissile{target : None, direction : Vec2::from_array(missile.direction), speed : missile.speed, turn_rate : missile.turn_rate, damage : missile.damage, splash_radius : missile.splash_radius, spawn_time : spawn_time_from_age(missile.age, now)}
+        ));
+    }
+    for projectile in save_game.enemy_projectiles.iter(){
+        commands.spawn((
+            projectile.sprite.bundle(&asset_server),
+            EnemyProjectile{direction : Vec2::from_array(projectile.direction), speed : projectile.speed, size : Vec2::from_array(projectile.size), damage : projectile.damage, spawn_time : spawn_time_from_age(projectile.age, now)}
+        ));
+    }
+    for pickup in save_game.upgrade_pickups.iter(){
+        spawn_upgrade_pickup(&mut commands, pickup.modifier, Vec3::from_array(pickup.translation));
+    }
+    //continued, the run is no longer suspended
+    save_game_slot.remove();
+}
@@ -0,0 +1,146 @@
+use std::{fs, path::PathBuf};
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
+
+use crate::controls::{ActionState, Action};
+use super::AppState;
+
+//What the player can turn on and off for the HUD and the hit feedback, all of it is listed on the settings screen
+//and saved whenever it changes. The wave summary and the enemy health bars also have keys of their own.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        //Resources
+        .init_resource::<Settings>()
+
+        //can be flipped while paused
+        .add_system(toggle_settings.in_set(OnUpdate(AppState::Game)));
+    }
+}
+
+
+
+pub const SETTINGS_PATH : &str = "settings.ron";
+
+
+//Resources
+
+//only read from and written to disk when path is set
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings{
+    pub wave_summary : bool,
+    pub enemy_health_bars : bool,
+    pub damage_numbers : bool,
+    pub hit_flash : bool,
+    pub knockback : bool,
+    pub screen_shake : bool,
+    #[serde(skip)]
+    pub path : Option<PathBuf>
+}
+
+impl Default for Settings{
+    fn default() -> Settings {
+        Settings{wave_summary : true, enemy_health_bars : true, damage_numbers : true, hit_flash : true, knockback : true, screen_shake : true, path : None}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting{
+    WaveSummary,
+    EnemyHealthBars,
+    DamageNumbers,
+    HitFlash,
+    Knockback,
+    ScreenShake
+}
+
+impl Setting{
+    pub const ALL : [Setting; 6] = [
+        Setting::WaveSummary, Setting::EnemyHealthBars,
+        Setting::DamageNumbers, Setting::HitFlash, Setting::Knockback, Setting::ScreenShake
+    ];
+
+    pub fn label(&self) -> &'static str{
+        match self {
+            Setting::WaveSummary => "Wave summary",
+            Setting::EnemyHealthBars => "Enemy health bars",
+            Setting::DamageNumbers => "Damage numbers",
+            Setting::HitFlash => "Hit flash",
+            Setting::Knockback => "Knockback",
+            Setting::ScreenShake => "Screen shake"
+        }
+    }
+}
+
+impl Settings{
+    //a missing or broken file keeps everything on
+    pub fn load(path : PathBuf) -> Settings{
+        let mut settings = Settings::default();
+        if let Ok(contents) = fs::read_to_string(&path){
+            match ron::from_str::<Settings>(&contents) {
+                Ok(loaded) => settings = loaded,
+                Err(error) => println!("Couldn't read {}: {}", path.display(), error)
+            }
+        }
+        settings.path = Some(path);
+        settings
+    }
+
+    pub fn save(&self){
+        if let Some(path) = &self.path{
+            let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(|error| error.to_string())
+                .and_then(|contents| fs::write(path, contents).map_err(|error| error.to_string()));
+            if let Err(error) = result{
+                println!("Couldn't save {}: {}", path.display(), error);
+            }
+        }
+    }
+
+    pub fn get(&self, setting : Setting) -> bool{
+        match setting {
+            Setting::WaveSummary => self.wave_summary,
+            Setting::EnemyHealthBars => self.enemy_health_bars,
+            Setting::DamageNumbers => self.damage_numbers,
+            Setting::HitFlash => self.hit_flash,
+            Setting::Knockback => self.knockback,
+            Setting::ScreenShake => self.screen_shake
+        }
+    }
+
+    pub fn toggle(&mut self, setting : Setting){
+        let value = match setting {
+            Setting::WaveSummary => &mut self.wave_summary,
+            Setting::EnemyHealthBars => &mut self.enemy_health_bars,
+            Setting::DamageNumbers => &mut self.damage_numbers,
+            Setting::HitFlash => &mut self.hit_flash,
+            Setting::Knockback => &mut self.knockback,
+            Setting::ScreenShake => &mut self.screen_shake
+        };
+        *value = !*value;
+    }
+}
+
+
+//Systems
+
+pub fn load_settings(mut settings : ResMut<Settings>){
+    *settings = Settings::load(PathBuf::from(SETTINGS_PATH));
+}
+
+//the keys are saved the same as the settings screen
+pub fn toggle_settings(action_state : Res<ActionState>, mut settings : ResMut<Settings>){
+    let mut changed = false;
+    for (action, setting) in [(Action::WaveSummary, Setting::WaveSummary), (Action::EnemyHealthBars, Setting::EnemyHealthBars)]{
+        if action_state.just_pressed(action){
+            settings.toggle(setting);
+            changed = true;
+        }
+    }
+    if changed{
+        settings.save();
+    }
+}
@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use crate::{repetitive_code::*, settings::*};
+use super::AppState;
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .add_system(spawn_settings_menu.in_schedule(OnEnter(AppState::Settings)))
+        .add_systems(
+            (
+                interact_with_setting_buttons,
+                interact_with_settings_back_button,
+                update_setting_texts
+            ).in_set(OnUpdate(AppState::Settings))
+        )
+        .add_system(despawn_settings_menu.in_schedule(OnExit(AppState::Settings)));
+    }
+}
+
+
+//Components
+
+#[derive(Component)]
+pub struct SettingsMenu;
+
+#[derive(Component)]
+pub struct SettingButton{
+    pub setting : Setting
+}
+
+#[derive(Component)]
+pub struct SettingText{
+    pub setting : Setting
+}
+
+#[derive(Component)]
+pub struct SettingsBackButton;
+
+
+fn describe_setting(setting : Setting, settings : &Settings) -> &'static str{
+    if settings.get(setting) {"On"} else {"Off"}
+}
+
+
+//Layout
+
+pub fn spawn_settings_menu(mut commands : Commands, asset_server: Res<AssetServer>, settings : Res<Settings>) {
+    build_settings_menu(&mut commands, &asset_server, &settings);
+}
+
+pub fn despawn_settings_menu(mut commands : Commands, settings_menu_query: Query<Entity, With<SettingsMenu>>) {
+    if let Ok(settings_menu_entity) = settings_menu_query.get_single(){
+        commands.entity(settings_menu_entity).despawn_recursive();
+    }
+}
+
+pub fn build_settings_menu(commands : &mut Commands, asset_server: &Res<AssetServer>, settings : &Settings) -> Entity{
+    let text_style = get_leaderboard_text_style(asset_server);
+    let settings_menu_entity = commands
+        .spawn((
+            NodeBundle{
+                style : MAIN_MENU_STYLE,
+                ..default()
+            },
+            SettingsMenu{},
+        ))
+        .with_children(|parent|{
+            //title
+            parent.spawn(TextBundle{
+                text : Text{
+                    sections : vec![TextSection::new("Settings", get_title_text_style(asset_server))],
+                    alignment : TextAlignment::Center,
+                    ..default()
+                },
+                ..default()
+            });
+            //one row per setting, click to turn it on or off
+            for setting in Setting::ALL{
+                parent.spawn(NodeBundle{
+                    style : CONTROLS_ROW_STYLE,
+                    ..default()
+                })
+                .with_children(|parent|{
+                    parent.spawn(TextBundle{
+                        text : Text{
+                            sections : vec![TextSection::new(setting.label(), text_style.clone())],
+                            alignment : TextAlignment::Left,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                    parent.spawn((
+                        ButtonBundle{
+                            style : SETTING_BUTTON_STYLE,
+                            background_color : NORMAL_BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        SettingButton{setting},
+                    ))
+                    .with_children(|parent|{
+                        parent.spawn((
+                            TextBundle{
+                                text : Text{
+                                    sections : vec![TextSection::new(describe_setting(setting, settings), text_style.clone())],
+                                    alignment : TextAlignment::Center,
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            SettingText{setting},
+                        ));
+                    });
+                });
+            }
+            //back button
+            parent.spawn((
+                ButtonBundle{
+                    style : BUTTON_STYLE,
+                    background_color : NORMAL_BUTTON_COLOR.into(),
+                    ..default()
+                },
+                SettingsBackButton{},
+            ))
+            .with_children(|parent|{
+                parent.spawn(TextBundle{
+                    text : Text{
+                        sections : vec![TextSection::new("Back", get_button_text_style(asset_server))],
+                        alignment : TextAlignment::Center,
+                        ..default()
+                    },
+                    ..default()
+                });
+            });
+        })
+        .id();
+    settings_menu_entity
+}
+
+
+//Interactions
+
+pub fn interact_with_setting_buttons(mut button_query : Query<(&Interaction, &mut BackgroundColor, &SettingButton), Changed<Interaction>>, mut settings : ResMut<Settings>){
+    for (interaction, mut background_color, setting_button) in button_query.iter_mut(){
+        match *interaction{
+            Interaction::Clicked => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                settings.toggle(setting_button.setting);
+                settings.save();
+            },
+            Interaction::Hovered => *background_color = HOVERED_BUTTON_COLOR.into(),
+            Interaction::None => *background_color = NORMAL_BUTTON_COLOR.into()
+        }
+    }
+}
+
+pub fn interact_with_settings_back_button(mut button_query : ButtonInteractionQuery<SettingsBackButton>, mut app_state_next_state : ResMut<NextState<AppState>>){
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut(){
+        match *interaction{
+            Interaction::Clicked => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                app_state_next_state.set(AppState::MainMenu);
+            },
+            Interaction::Hovered => *background_color = HOVERED_BUTTON_COLOR.into(),
+            Interaction::None => *background_color = NORMAL_BUTTON_COLOR.into()
+        }
+    }
+}
+
+//other systems
+
+pub fn update_setting_texts(settings : Res<Settings>, mut text_query : Query<(&mut Text, &SettingText)>){
+    if settings.is_changed(){
+        for (mut text, setting_text) in text_query.iter_mut(){
+            text.sections[0].value = describe_setting(setting_text.setting, &settings).to_string();
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::enemy::Enemy;
+use crate::health::DamageSet;
+use super::{AppState, SimulationState};
+
+//The enemy grid is rebuilt once a frame in SpatialIndexSet, before the collision and targeting systems read it.
+#[derive(SystemSet, Debug, Hash, Clone, PartialEq, Eq)]
+pub struct SpatialIndexSet;
+
+pub struct SpatialPlugin;
+
+impl Plugin for SpatialPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        //Ordering
+        .configure_set(SpatialIndexSet.before(DamageSet))
+
+        //Resources
+        .init_resource::<EnemyGrid>()
+
+        .add_system(
+            update_enemy_grid
+            .in_set(SpatialIndexSet)
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
+        .add_system(clear_enemy_grid.in_schedule(OnExit(AppState::Game)));
+    }
+}
+
+
+
+//a bit bigger than the biggest enemy, so most lookups only touch a few cells
+pub const GRID_CELL_SIZE : f32 = 64.0;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridEntry{
+    pub entity : Entity,
+    pub translation : Vec3,
+    pub size : Vec2
+}
+
+//Uniform grid, each entry is stored in the cell its center falls in.
+//Lookups widen the searched area by the biggest half size inserted so nothing overlapping is missed.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid{
+    pub cell_size : f32,
+    cells : HashMap<(i32, i32), Vec<GridEntry>>,
+    max_half_size : f32
+}
+
+#[derive(Resource, Debug, Clone, Deref, DerefMut)]
+pub struct EnemyGrid(pub SpatialGrid);
+
+impl Default for EnemyGrid{
+    fn default() -> EnemyGrid {
+        EnemyGrid(SpatialGrid::new(GRID_CELL_SIZE))
+    }
+}
+
+
+impl SpatialGrid{
+    pub fn new(cell_size : f32) -> SpatialGrid{
+        SpatialGrid{cell_size, cells : HashMap::default(), max_half_size : 0.0}
+    }
+
+    fn cell(&self, position : Vec2) -> (i32, i32){
+        ((position.x / self.cell_size).floor() as i32, (position.y / self.cell_size).floor() as i32)
+    }
+
+    //keeps the cell vectors around so rebuilding every frame doesn't allocate
+    pub fn clear(&mut self){
+        for entries in self.cells.values_mut(){
+            entries.clear();
+        }
+        self.max_half_size = 0.0;
+    }
+
+    pub fn insert(&mut self, entity : Entity, translation : Vec3, size : Vec2){
+        let cell = self.cell(translation.truncate());
+        self.cells.entry(cell).or_default().push(GridEntry{entity, translation, size});
+        self.max_half_size = self.max_half_size.max(size.max_element() / 2.0);
+    }
+
+    //everything that may overlap the rectangle around center, callers still do the exact check
+    pub fn candidates(&self, center : Vec3, half_extent : Vec2) -> impl Iterator<Item = &GridEntry>{
+        let reach = half_extent + Vec2::splat(self.max_half_size);
+        let (min_x, min_y) = self.cell(center.truncate() - reach);
+        let (max_x, max_y) = self.cell(center.truncate() + reach);
+        (min_x..=max_x)
+            .flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+    }
+
+    //entries whose center is within radius of center
+    pub fn within(&self, center : Vec3, radius : f32) -> impl Iterator<Item = &GridEntry>{
+        self.candidates(center, Vec2::splat(radius))
+            .filter(move |entry| entry.translation.distance(center) <= radius)
+    }
+
+    pub fn nearest(&self, center : Vec3, radius : f32) -> Option<&GridEntry>{
+        self.within(center, radius)
+            .min_by(|a, b| a.translation.distance(center).total_cmp(&b.translation.distance(center)))
+    }
+}
+
+
+//Systems
+
+pub fn update_enemy_grid(mut enemy_grid : ResMut<EnemyGrid>, enemy_query : Query<(Entity, &Transform, &Enemy)>){
+    enemy_grid.clear();
+    for (enemy_entity, enemy_transform, enemy) in enemy_query.iter(){
+        enemy_grid.insert(enemy_entity, enemy_transform.translation, enemy.size);
+    }
+}
+
+pub fn clear_enemy_grid(mut enemy_grid : ResMut<EnemyGrid>){
+    enemy_grid.clear();
+}
@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use bevy::prelude::*;
+
+use crate::enemy::*;
+use crate::health::*;
+use crate::boss::BOSS_COLOR;
+use crate::feedback::HitFlash;
+use super::{AppState, SimulationState};
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin{
+    fn build(&self, app: &mut App) {
+        app
+
+        //Events
+        .add_event::<StatusEvent>()
+
+        //While in game appstate
+        .add_systems(
+            (
+                add_status_effects.before(DamageSet),
+                tick_status_effects.in_set(DamageSet),
+                apply_status_events.after(DamageSet).before(DeathSet),
+                tint_status_effects.after(DeathSet)
+            )
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        );
+    }
+}
+
+
+
+pub const MAX_BURN_STACKS : usize = 3;
+pub const MAX_SHIELD : f32 = 50.0;
+
+pub const SLOW_COLOR : Color = Color::rgb(0.5, 0.7, 1.0);
+pub const BURN_COLOR : Color = Color::rgb(1.0, 0.55, 0.2);
+pub const STUN_COLOR : Color = Color::rgb(1.0, 1.0, 0.4);
+pub const SHIELD_COLOR : Color = Color::rgb(0.75, 0.5, 1.0);
+pub const HASTE_COLOR : Color = Color::rgb(0.5, 1.0, 0.5);
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusKind{
+    //strength is the part of the speed taken away
+    Slow,
+    //strength is the damage per second
+    Burn,
+    //can't move or use abilities
+    Stun,
+    //strength is the damage it soaks up before the health is touched
+    Shield,
+    //strength is the part of the speed added
+    Haste
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffect{
+    pub kind : StatusKind,
+    pub strength : f32,
+    //seconds left
+    pub duration : f32
+}
+
+//applied to the target once the frame's damage is dealt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusEvent{
+    pub target : Entity,
+    pub effect : StatusEffect
+}
+
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct StatusEffects{
+    pub effects : Vec<StatusEffect>,
+    //burn damage that hasn't added up to a whole point yet
+    pub burn_carry : f32
+}
+
+impl StatusKind{
+    pub fn color(&self) -> Color{
+        match self {
+            StatusKind::Slow => SLOW_COLOR,
+            StatusKind::Burn => BURN_COLOR,
+            StatusKind::Stun => STUN_COLOR,
+            StatusKind::Shield => SHIELD_COLOR,
+            StatusKind::Haste => HASTE_COLOR
+        }
+    }
+}
+
+impl StatusEffect{
+    pub const fn new(kind : StatusKind, strength : f32, duration : f32) -> StatusEffect{
+        StatusEffect{kind, strength, duration}
+    }
+}
+
+impl StatusEffects{
+    //burns stack up to MAX_BURN_STACKS, shields add up to MAX_SHIELD,
+    //slow, stun and haste keep the strongest and longest of what was applied
+    pub fn apply(&mut self, effect : StatusEffect){
+        let existing = self.effects.iter().position(|current| current.kind == effect.kind);
+        match (effect.kind, existing) {
+            (StatusKind::Burn, _) => {
+                let burns = self.effects.iter().filter(|current| current.kind == StatusKind::Burn).count();
+                if burns >= MAX_BURN_STACKS{
+                    //the stack that would run out first is replaced
+                    if let Some(shortest) = self.effects.iter_mut().filter(|current| current.kind == StatusKind::Burn).min_by(|a, b| a.duration.total_cmp(&b.duration)){
+                        *shortest = effect;
+                    }
+                }
+                else{
+                    self.effects.push(effect);
+                }
+            },
+            (StatusKind::Shield, Some(index)) => {
+                let shield = &mut self.effects[index];
+                shield.strength = (shield.strength + effect.strength).min(MAX_SHIELD);
+                shield.duration = shield.duration.max(effect.duration);
+            },
+            (_, Some(index)) => {
+                let current = &mut self.effects[index];
+                current.strength = current.strength.max(effect.strength);
+                current.duration = current.duration.max(effect.duration);
+            },
+            (StatusKind::Shield, None) => {
+                self.effects.push(StatusEffect{strength : effect.strength.min(MAX_SHIELD), ..effect});
+            },
+            (_, None) => {
+                self.effects.push(effect);
+            }
+        }
+    }
+
+    pub fn has(&self, kind : StatusKind) -> bool{
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    fn strength(&self, kind : StatusKind) -> f32{
+        self.effects.iter().filter(|effect| effect.kind == kind).map(|effect| effect.strength).fold(0.0, f32::max)
+    }
+
+    pub fn stunned(&self) -> bool{
+        self.has(StatusKind::Stun)
+    }
+
+    //what enemy_movement multiplies the speed with
+    pub fn speed_multiplier(&self) -> f32{
+        if self.stunned(){
+            return 0.0;
+        }
+        (1.0 - self.strength(StatusKind::Slow)).max(0.0) * (1.0 + self.strength(StatusKind::Haste))
+    }
+
+    //takes what it can of the damage out of the shield and returns the rest
+    pub fn absorb(&mut self, amount : i64) -> i64{
+        let Some(index) = self.effects.iter().position(|effect| effect.kind == StatusKind::Shield) else {
+            return amount;
+        };
+        let shield = &mut self.effects[index];
+        let absorbed = (shield.strength.floor() as i64).min(amount);
+        shield.strength -= absorbed as f32;
+        if shield.strength < 1.0{
+            self.effects.remove(index);
+        }
+        amount - absorbed
+    }
+
+    //counts the effects down and returns the whole points of burn damage dealt
+    pub fn tick(&mut self, delta : f32) -> i64{
+        let burn : f32 = self.effects.iter().filter(|effect| effect.kind == StatusKind::Burn).map(|effect| effect.strength * effect.duration.min(delta)).sum();
+        self.burn_carry += burn;
+        let damage = self.burn_carry.floor();
+        self.burn_carry -= damage;
+        for effect in self.effects.iter_mut(){
+            effect.duration -= delta;
+        }
+        self.effects.retain(|effect| effect.duration > 0.0);
+        damage as i64
+    }
+
+    //the most recently applied effect shows
+    pub fn color(&self) -> Option<Color>{
+        self.effects.last().map(|effect| effect.kind.color())
+    }
+}
+
+
+//Systems
+
+pub fn add_status_effects(mut commands: Commands, enemy_query : Query<Entity, (With<Enemy>, Without<StatusEffects>)>){
+    for entity in enemy_query.iter(){
+        commands.entity(entity).insert(StatusEffects::default());
+    }
+}
+
+pub fn tick_status_effects(mut status_query : Query<(Entity, &mut StatusEffects)>, mut damage_event_writer : EventWriter<DamageEvent>, time : Res<Time>){
+    for (entity, mut status_effects) in status_query.iter_mut(){
+        if status_effects.effects.is_empty(){
+            continue;
+        }
+        let burn_damage = status_effects.tick(time.delta_seconds());
+        if burn_damage > 0{
+            damage_event_writer.send(DamageEvent{source : None, target : entity, amount : burn_damage, damage_type : DamageType::Burn});
+        }
+    }
+}
+
+pub fn apply_status_events(mut status_event_reader : EventReader<StatusEvent>, mut status_query : Query<(&mut StatusEffects, &Health)>){
+    for status_event in status_event_reader.iter(){
+        if let Ok((mut status_effects, health)) = status_query.get_mut(status_event.target){
+            if !health.is_dead(){
+                status_effects.apply(status_event.effect);
+            }
+        }
+    }
+}
+
+//the color of the most recently applied effect, or the enemy's own color without any
+pub fn enemy_tint(enemy : &Enemy, status_effects : Option<&StatusEffects>) -> Color{
+    let untinted = if matches!(enemy.variant, EnemyType::Boss(_)) {BOSS_COLOR} else {Color::WHITE};
+    status_effects.and_then(|status_effects| status_effects.color()).unwrap_or(untinted)
+}
+
+type TintedEnemyQuery<'w, 's> = Query<'w, 's, (&'static StatusEffects, &'static Enemy, &'static mut Sprite), (Changed<StatusEffects>, Without<HitFlash>)>;
+
+//a hit flash wins until end_hit_flashes hands the sprite back
+pub fn tint_status_effects(mut enemy_query : TintedEnemyQuery){
+    for (status_effects, enemy, mut sprite) in enemy_query.iter_mut(){
+        let color = enemy_tint(enemy, Some(status_effects));
+        if sprite.color != color{
+            sprite.color = color;
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::{collections::HashMap, f32::consts::PI};
+use rand::prelude::*;
+use bevy::prelude::*;
+
+use crate::base::{Base, BaseBuilding};
+use crate::building::PlacedBuilding;
+use crate::enemy::*;
+use crate::rng::GameRng;
+use crate::spatial::EnemyGrid;
+use crate::game_time::SimulationClock;
+use super::{AppState, SimulationState};
+
+//Steering is worked out from the enemy grid of the previous frame, before enemy_movement moves anything,
+//and added on top of the direction each enemy type picks for itself.
+pub struct SteeringPlugin;
+
+impl Plugin for SteeringPlugin{
+    fn build(&self, app: &mut App) {
+        app
+
+        //While in game appstate
+        .add_systems(
+            (
+                add_steering,
+                update_steering
+            )
+            .chain()
+            .before(enemy_movement)
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        );
+    }
+}
+
+
+
+//the steering force is capped so an enemy never turns around on the spot
+pub const MAX_STEERING_FORCE : f32 = 1.0;
+//how far outside an obstacle enemies start to steer around it
+pub const AVOIDANCE_MARGIN : f32 = 30.0;
+pub const BASE_BUILDING_RADIUS : f32 = 15.0;
+
+
+//how strongly an enemy type follows each rule, neighbours are the enemies within neighbour_radius
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SteeringProfile{
+    pub separation : f32,
+    pub alignment : f32,
+    pub cohesion : f32,
+    pub avoidance : f32,
+    pub wander : f32,
+    pub neighbour_radius : f32,
+    //enemies trying to ram the base don't steer around it
+    pub avoids_base : bool
+}
+
+impl SteeringProfile{
+    pub const SOLO : SteeringProfile = SteeringProfile{separation : 1.0, alignment : 0.0, cohesion : 0.0, avoidance : 1.5, wander : 0.0, neighbour_radius : 25.0, avoids_base : false};
+
+    //pawns and stingers swarm, the rest only keep out of each other's way, bosses aren't steered
+    pub fn for_enemy(variant : &EnemyType) -> Option<SteeringProfile>{
+        match variant {
+            EnemyType::Pawn => Some(SteeringProfile{separation : 1.5, alignment : 0.4, cohesion : 0.3, avoidance : 1.5, wander : 0.4, neighbour_radius : 30.0, avoids_base : false}),
+            EnemyType::Stinger => Some(SteeringProfile{separation : 1.2, alignment : 0.7, cohesion : 0.5, avoidance : 1.5, wander : 0.5, neighbour_radius : 35.0, avoids_base : false}),
+            EnemyType::Neonate(_) => Some(SteeringProfile{wander : 0.5, ..SteeringProfile::SOLO}),
+            EnemyType::Rogue | EnemyType::Gunner{..} => Some(SteeringProfile{avoids_base : true, ..SteeringProfile::SOLO}),
+            EnemyType::Splitter{..} | EnemyType::Bishop | EnemyType::Propagator => Some(SteeringProfile::SOLO),
+            EnemyType::Boss(_) => None
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Steering{
+    //added to the direction the enemy picked for itself
+    pub force : Vec3,
+    //the direction it moved in last, for its neighbours to line up with
+    pub heading : Vec3,
+    //offsets the wander pattern so neighbours don't wander in step
+    pub wander_phase : f32
+}
+
+//a smooth wobble that only depends on the time, so it looks the same at any frame rate
+pub fn wander(phase : f32, now : f32) -> Vec3{
+    let angle = phase + 2.0 * (now * 0.7 + phase).sin() + (now * 1.9 + 2.0 * phase).sin();
+    Vec3::new(angle.cos(), angle.sin(), 0.0)
+}
+
+//pushes away from an obstacle, harder the further inside the margin the enemy is
+pub fn avoid(translation : Vec3, obstacle : Vec3, radius : f32) -> Vec3{
+    let away = Vec3::new(translation.x - obstacle.x, translation.y - obstacle.y, 0.0);
+    let depth = 1.0 - (away.length() - radius) / AVOIDANCE_MARGIN;
+    away.normalize_or_zero() * depth.clamp(0.0, 1.0)
+}
+
+pub fn add_steering(mut commands: Commands, enemy_query : Query<Entity, (With<Enemy>, Without<Steering>)>, mut game_rng : ResMut<GameRng>){
+    for entity in enemy_query.iter(){
+        commands.entity(entity).insert(Steering{wander_phase : game_rng.movement.gen_range(0.0..2.0 * PI), ..default()});
+    }
+}
+
+//the base, its buildings and whatever the player placed
+type ObstacleQuery<'w, 's> = Query<'w, 's, (&'static Transform, Option<&'static Base>, Option<&'static PlacedBuilding>), Or<(With<Base>, With<BaseBuilding>, With<PlacedBuilding>)>>;
+
+pub fn update_steering(mut steering_query : Query<(Entity, &Transform, &Enemy, &mut Steering)>, obstacle_query : ObstacleQuery, enemy_grid : Res<EnemyGrid>, simulation_clock : Res<SimulationClock>){
+    let headings : HashMap<Entity, Vec3> = steering_query.iter().map(|(entity, _, _, steering)| (entity, steering.heading)).collect();
+    let obstacles : Vec<(Vec3, f32, bool)> = obstacle_query.iter().map(|(transform, base, placed_building)| match (base, placed_building) {
+        (Some(base), _) => (transform.translation, base.size.x / 2.0, true),
+        (None, Some(placed_building)) => (transform.translation, placed_building.kind.size().x / 2.0, false),
+        (None, None) => (transform.translation, BASE_BUILDING_RADIUS, false)
+    }).collect();
+    for (entity, transform, enemy, mut steering) in steering_query.iter_mut(){
+        let Some(profile) = SteeringProfile::for_enemy(&enemy.variant) else {
+            steering.force = Vec3::ZERO;
+            continue;
+        };
+        let translation = Vec3::new(transform.translation.x, transform.translation.y, 0.0);
+        let mut separation = Vec3::ZERO;
+        let mut alignment = Vec3::ZERO;
+        let mut center = Vec3::ZERO;
+        let mut neighbours = 0;
+        for neighbour in enemy_grid.within(translation, profile.neighbour_radius).filter(|neighbour| neighbour.entity != entity){
+            let neighbour_translation = Vec3::new(neighbour.translation.x, neighbour.translation.y, 0.0);
+            let away = translation - neighbour_translation;
+            let closeness = 1.0 - away.length() / profile.neighbour_radius;
+            //stacked on the same pixel, pick a way out that differs per enemy
+            let away = if away.length() > 0.01 {away.normalize()} else {wander(steering.wander_phase, 0.0)};
+            separation += away * closeness;
+            alignment += headings.get(&neighbour.entity).copied().unwrap_or_default();
+            center += neighbour_translation;
+            neighbours += 1;
+        }
+        let mut force = separation * profile.separation;
+        if neighbours > 0{
+            force += alignment.normalize_or_zero() * profile.alignment;
+            force += (center / neighbours as f32 - translation).normalize_or_zero() * profile.cohesion;
+        }
+        for (obstacle, radius, is_base) in obstacles.iter(){
+            if *is_base && !profile.avoids_base{
+                continue;
+            }
+            force += avoid(translation, *obstacle, *radius) * profile.avoidance;
+        }
+        force += wander(steering.wander_phase, simulation_clock.now()) * profile.wander;
+        steering.force = force.clamp_length_max(MAX_STEERING_FORCE);
+    }
+}
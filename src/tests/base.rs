@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+use crate::base::*;
+use crate::part::*;
+use crate::player::Player;
+use super::*;
+
+
+fn spawn_part_on_player(app : &mut App, part_tier : PartTier){
+    let player_translation = app.world.query_filtered::<&Transform, With<Player>>().single(&app.world).translation;
+    app.world.spawn((
+        Transform::from_translation(player_translation),
+        Part{part_tier, size : Vec2::new(15.0, 15.0), spawn_time : 0.0}
+    ));
+}
+
+fn base(app : &mut App) -> &Base{
+    app.world.query::<&Base>().single(&app.world)
+}
+
+
+#[test]
+fn parts_required_stop_at_max_parts(){
+    let mut app = game_app();
+    let mut base = app.world.query::<&mut Base>().single_mut(&mut app.world);
+    base.parts_required.clear();
+    for _ in 0..base.max_parts{
+        assert!(base.push_part_required(PartTier::Blue).is_ok());
+    }
+    assert!(base.push_part_required(PartTier::Red).is_err());
+    assert_eq!(base.parts_required.len(), base.max_parts);
+}
+
+#[test]
+fn first_level_requires_three_blue_parts(){
+    let mut app = game_app();
+    assert_eq!(base(&mut app).parts_required, vec![PartTier::Blue; 3]);
+}
+
+#[test]
+fn collecting_three_matching_parts_levels_up_the_base(){
+    let mut app = game_app();
+    for _ in 0..3{
+        spawn_part_on_player(&mut app, PartTier::Blue);
+    }
+    run_updates(&mut app, 3);
+
+    let base = base(&mut app);
+    assert_eq!(base.level, 2);
+    assert_eq!(base.max_parts, 4);
+    assert_eq!(count::<Part>(&mut app), 0);
+}
+
+#[test]
+fn a_wrong_part_resets_the_requirements(){
+    let mut app = game_app();
+    spawn_part_on_player(&mut app, PartTier::Red);
+    run_updates(&mut app, 3);
+
+    let base = base(&mut app);
+    assert_eq!(base.level, 1);
+    assert!(base.parts.is_empty());
+    //the requirements are rolled again for the same level
+    assert_eq!(base.parts_required, vec![PartTier::Blue; 3]);
+}
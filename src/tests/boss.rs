@@ -0,0 +1,165 @@
+use std::time::Duration;
+use bevy::prelude::*;
+
+use crate::base::Base;
+use crate::boss::*;
+use crate::enemy::*;
+use crate::health::*;
+use crate::hud::{BossBar, BossBarText};
+use crate::part::*;
+use crate::player::Player;
+use crate::savegame::SavedEnemyType;
+use crate::wave::WaveTimer;
+use super::*;
+
+
+fn base_translation(app : &mut App) -> Vec3{
+    app.world.query_filtered::<&Transform, With<Base>>().single(&app.world).translation
+}
+
+//a boss with its ability ready, wounded down to current health
+fn spawn_test_boss(app : &mut App, translation : Vec3, current : i64) -> Entity{
+    let state = BossState{phase : BossPhase::Summoning, last_ability : -BOSS_ABILITY_COOLDOWN, charge : None, last_contact : -BOSS_CONTACT_COOLDOWN};
+    app.world.spawn((
+        Transform::from_translation(translation),
+        Enemy{variant : EnemyType::Boss(state), speed : BOSS_SPEED, size : Vec2::splat(BOSS_SIZE)},
+        Health{current, max : BOSS_HEALTH}
+    )).id()
+}
+
+fn boss_state(app : &App, boss : Entity) -> BossState{
+    match app.world.get::<Enemy>(boss).unwrap().variant {
+        EnemyType::Boss(state) => state,
+        variant => panic!("not a boss: {variant:?}")
+    }
+}
+
+fn count_pawns(app : &mut App) -> usize{
+    app.world.query::<&Enemy>().iter(&app.world).filter(|enemy| enemy.variant == EnemyType::Pawn).count()
+}
+
+
+#[test]
+fn bosses_come_every_interval_and_get_tougher(){
+    assert!(!is_boss_wave(0));
+    assert!(!is_boss_wave(BOSS_WAVE_INTERVAL - 1));
+    assert!(is_boss_wave(BOSS_WAVE_INTERVAL));
+    assert!(is_boss_wave(BOSS_WAVE_INTERVAL * 3));
+    assert_eq!(boss_health(BOSS_WAVE_INTERVAL), BOSS_HEALTH);
+    assert_eq!(boss_health(BOSS_WAVE_INTERVAL * 2), BOSS_HEALTH + BOSS_HEALTH_PER_ENCOUNTER);
+}
+
+#[test]
+fn phases_follow_the_health_left(){
+    assert_eq!(BossPhase::from_health(&Health{current : 900, max : 900}), BossPhase::Summoning);
+    assert_eq!(BossPhase::from_health(&Health{current : 600, max : 900}), BossPhase::Shielding);
+    assert_eq!(BossPhase::from_health(&Health{current : 301, max : 900}), BossPhase::Shielding);
+    assert_eq!(BossPhase::from_health(&Health{current : 300, max : 900}), BossPhase::Charging);
+}
+
+#[test]
+fn a_boss_wave_brings_a_boss(){
+    let mut app = game_app();
+    {
+        let mut wave_timer = app.world.resource_mut::<WaveTimer>();
+        wave_timer.wave = BOSS_WAVE_INTERVAL - 1;
+        let duration = wave_timer.timer.duration();
+        wave_timer.timer.set_elapsed(duration - Duration::from_millis(1));
+    }
+    run_updates(&mut app, 3);
+    let bosses = app.world.query::<(&Enemy, &Health)>().iter(&app.world).filter(|(enemy, _)| matches!(enemy.variant, EnemyType::Boss(_))).map(|(_, health)| health.max).collect::<Vec<_>>();
+    assert_eq!(bosses, vec![boss_health(BOSS_WAVE_INTERVAL)]);
+}
+
+#[test]
+fn a_healthy_boss_summons_pawns(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(300.0, 0.0, 0.0);
+    spawn_test_boss(&mut app, translation, BOSS_HEALTH);
+    run_updates(&mut app, 2);
+    assert_eq!(count_pawns(&mut app), ENEMY_POSITIONS.len());
+
+    //and waits for the cooldown before summoning again
+    run_updates(&mut app, 60);
+    assert_eq!(count_pawns(&mut app), ENEMY_POSITIONS.len());
+}
+
+#[test]
+fn a_wounded_boss_stands_behind_deacons(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(300.0, 0.0, 0.0);
+    let boss = spawn_test_boss(&mut app, translation, BOSS_HEALTH / 2);
+    run_updates(&mut app, 30);
+    assert_eq!(boss_state(&app, boss).phase, BossPhase::Shielding);
+    assert_eq!(count::<Deacon>(&mut app), BOSS_SHIELD_DEACONS);
+    assert_eq!(app.world.get::<Transform>(boss).unwrap().translation, translation);
+}
+
+#[test]
+fn a_badly_wounded_boss_charges_at_the_base(){
+    let mut app = game_app();
+    let base_translation = base_translation(&mut app);
+    let translation = base_translation + Vec3::new(300.0, 0.0, 0.0);
+    let boss = spawn_test_boss(&mut app, translation, BOSS_HEALTH / 5);
+    run_updates(&mut app, 30);
+    assert_eq!(boss_state(&app, boss).phase, BossPhase::Charging);
+    let distance = app.world.get::<Transform>(boss).unwrap().translation.distance(base_translation);
+    assert!(distance < 300.0 - BOSS_CHARGE_SPEED * 0.4, "{distance}");
+}
+
+#[test]
+fn a_shot_down_boss_always_drops_a_green_part(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(300.0, 0.0, 0.0);
+    let boss = spawn_test_boss(&mut app, translation, 10);
+    app.world.send_event(DamageEvent{source : None, target : boss, amount : 10, damage_type : DamageType::Bullet});
+    run_updates(&mut app, 2);
+    assert!(app.world.get_entity(boss).is_none());
+    let parts = app.world.query::<&Part>().iter(&app.world).map(|part| part.part_tier).collect::<Vec<_>>();
+    assert_eq!(parts, vec![PartTier::Green]);
+}
+
+#[test]
+fn the_boss_bar_shows_while_a_boss_is_alive(){
+    let mut app = game_app();
+    let bar_visibility = |app : &mut App| *app.world.query_filtered::<&Visibility, With<BossBar>>().single(&app.world);
+    assert_eq!(bar_visibility(&mut app), Visibility::Hidden);
+
+    let translation = base_translation(&mut app) + Vec3::new(300.0, 0.0, 0.0);
+    spawn_test_boss(&mut app, translation, BOSS_HEALTH / 2);
+    run_updates(&mut app, 2);
+    assert_eq!(bar_visibility(&mut app), Visibility::Inherited);
+    let text = app.world.query_filtered::<&Text, With<BossBarText>>().single(&app.world).sections[0].value.clone();
+    assert!(text.contains("phase 2/3"), "{text}");
+}
+
+#[test]
+fn a_saved_boss_keeps_its_phase_and_charge(){
+    let state = BossState{phase : BossPhase::Charging, last_ability : 8.0, charge : Some((Vec3::X, 9.0)), last_contact : 7.0};
+    let saved = SavedEnemyType::new(&EnemyType::Boss(state), 10.0);
+    //loaded five seconds into a fresh clock
+    assert_eq!(saved.enemy_type(5.0), EnemyType::Boss(BossState{phase : BossPhase::Charging, last_ability : 3.0, charge : Some((Vec3::X, 4.0)), last_contact : 2.0}));
+}
+
+#[test]
+fn a_boss_that_runs_into_the_player_survives(){
+    let mut app = game_app();
+    let player_translation = app.world.query_filtered::<&Transform, With<Player>>().single(&app.world).translation;
+    let boss = spawn_test_boss(&mut app, player_translation, BOSS_HEALTH);
+    run_updates(&mut app, 3);
+    assert_eq!(app.world.get::<Health>(boss).unwrap().current, BOSS_HEALTH);
+    let player_health = *app.world.query_filtered::<&Health, With<Player>>().single(&app.world);
+    assert_eq!(player_health.current, player_health.max - BOSS_CONTACT_DAMAGE);
+}
+
+#[test]
+fn a_boss_on_the_base_wears_it_down_instead_of_wiping_it_out(){
+    let mut app = game_app();
+    let base_translation = base_translation(&mut app);
+    app.world.query_filtered::<&mut Transform, With<Player>>().single_mut(&mut app.world).translation = base_translation + Vec3::new(400.0, 0.0, 0.0);
+    let boss = spawn_test_boss(&mut app, base_translation, BOSS_HEALTH);
+    run_updates(&mut app, 3);
+    assert!(app.world.get_entity(boss).is_some());
+    let base_health = *app.world.query_filtered::<&Health, With<Base>>().single(&app.world);
+    assert_eq!(base_health.current, base_health.max - BOSS_CONTACT_DAMAGE);
+}
@@ -0,0 +1,130 @@
+use bevy::{prelude::*, input::{ButtonState, keyboard::KeyboardInput, mouse::MouseButtonInput}};
+
+use crate::base::Base;
+use crate::building::*;
+use crate::enemy::*;
+use crate::health::Health;
+use crate::part::*;
+use crate::player::{Player, PlayerInput};
+use crate::turret::Turret;
+use super::*;
+
+
+fn press_key(app : &mut App, key_code : KeyCode){
+    app.world.send_event(KeyboardInput{scan_code : 0, key_code : Some(key_code), state : ButtonState::Pressed});
+    app.update();
+}
+
+//released for an update first so every click is a new press
+fn click(app : &mut App){
+    app.world.send_event(MouseButtonInput{button : MouseButton::Left, state : ButtonState::Released});
+    app.update();
+    app.world.send_event(MouseButtonInput{button : MouseButton::Left, state : ButtonState::Pressed});
+    app.update();
+}
+
+fn base_translation(app : &mut App) -> Vec3{
+    app.world.query_filtered::<&Transform, With<Base>>().single(&app.world).translation
+}
+
+//enters build mode with the given parts banked, aiming at translation
+fn build_mode_app(parts : PartInventory, translation : Vec3) -> App{
+    let mut app = game_app();
+    *app.world.resource_mut::<PartInventory>() = parts;
+    app.world.resource_mut::<PlayerInput>().aim = Some(translation.truncate());
+    press_key(&mut app, KeyCode::B);
+    app
+}
+
+fn inventory(app : &App) -> PartInventory{
+    *app.world.resource::<PartInventory>()
+}
+
+
+#[test]
+fn placing_a_turret_spends_its_parts(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(0.0, 150.0, 0.0);
+    let mut app = build_mode_app(PartInventory{blue : 4, red : 0, green : 0}, translation);
+    assert!(app.world.resource::<BuildMode>().placement.unwrap().valid);
+
+    click(&mut app);
+    app.update();
+    assert_eq!(count::<PlacedBuilding>(&mut app), 1);
+    let transform = app.world.query_filtered::<&Transform, (With<PlacedBuilding>, With<Turret>)>().single(&app.world);
+    assert_eq!(transform.translation, translation);
+    assert_eq!(inventory(&app), PartInventory{blue : 1, red : 0, green : 0});
+}
+
+#[test]
+fn buildings_cannot_overlap_the_base_or_each_other(){
+    let mut app = game_app();
+    let base_translation = base_translation(&mut app);
+    let mut app = build_mode_app(PartInventory{blue : 9, red : 0, green : 0}, base_translation);
+    assert!(!app.world.resource::<BuildMode>().placement.unwrap().valid);
+    click(&mut app);
+    assert_eq!(count::<PlacedBuilding>(&mut app), 0);
+
+    //the first one goes down, the second one on top of it doesn't
+    let translation = base_translation + Vec3::new(150.0, 0.0, 0.0);
+    app.world.resource_mut::<PlayerInput>().aim = Some(translation.truncate());
+    app.update();
+    click(&mut app);
+    app.update();
+    app.world.resource_mut::<PlayerInput>().aim = Some(translation.truncate() + Vec2::new(10.0, 0.0));
+    app.update();
+    assert!(!app.world.resource::<BuildMode>().placement.unwrap().valid);
+    assert_eq!(count::<PlacedBuilding>(&mut app), 1);
+    assert_eq!(inventory(&app).blue, 6);
+}
+
+#[test]
+fn nothing_is_built_without_enough_parts(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(0.0, 150.0, 0.0);
+    let mut app = build_mode_app(PartInventory{blue : 2, red : 0, green : 0}, translation);
+    assert!(!app.world.resource::<BuildMode>().placement.unwrap().valid);
+    click(&mut app);
+    app.update();
+    assert_eq!(count::<PlacedBuilding>(&mut app), 0);
+    assert_eq!(inventory(&app).blue, 2);
+}
+
+#[test]
+fn selling_refunds_half_the_cost_rounded_up(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(0.0, 150.0, 0.0);
+    let mut app = build_mode_app(PartInventory{blue : 3, red : 0, green : 0}, translation);
+    click(&mut app);
+    app.update();
+    assert_eq!(count::<PlacedBuilding>(&mut app), 1);
+
+    press_key(&mut app, KeyCode::X);
+    app.update();
+    assert_eq!(count::<PlacedBuilding>(&mut app), 0);
+    assert_eq!(inventory(&app).blue, 2);
+}
+
+#[test]
+fn walls_are_worn_down_by_enemies_running_into_them(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(0.0, 200.0, 0.0);
+    let wall = app.world.spawn((Transform::from_translation(translation), PlacedBuilding{kind : BuildingKind::Wall}, Health::new(400))).id();
+    let (_, _, mut enemy) = EnemyKind::Pawn.template(0.0);
+    enemy.speed = 0.0;
+    let pawn = app.world.spawn((Transform::from_translation(translation), enemy, Health::new(150))).id();
+    app.update();
+    app.update();
+
+    assert!(app.world.get_entity(pawn).is_none());
+    assert_eq!(app.world.get::<Health>(wall).unwrap().current, 250);
+}
+
+#[test]
+fn collected_parts_are_banked(){
+    let mut app = game_app();
+    let player_translation = app.world.query_filtered::<&Transform, With<Player>>().single(&app.world).translation;
+    app.world.spawn((Transform::from_translation(player_translation), Part{part_tier : PartTier::Red, size : Vec2::new(15.0, 15.0), spawn_time : 0.0}));
+    app.update();
+    assert_eq!(inventory(&app), PartInventory{blue : 0, red : 1, green : 0});
+}
@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+
+use crate::bullet::*;
+use crate::enemy::*;
+use crate::health::Health;
+use crate::player::{Player, PlayerInput};
+use crate::feedback::HIT_KNOCKBACK;
+use super::*;
+
+
+//far enough from the player and the base that only the bullet touches it
+const ENEMY_TRANSLATION : Vec3 = Vec3::new(200.0, 200.0, 0.0);
+
+fn spawn_pawn(app : &mut App, translation : Vec3) -> Entity{
+    let (_, _, mut enemy) = EnemyKind::Pawn.template(0.0);
+    enemy.speed = 0.0;
+    app.world.spawn((Transform::from_translation(translation), enemy, Health::new(1000))).id()
+}
+
+fn spawn_bullet(app : &mut App, translation : Vec3, bullet : Bullet) -> Entity{
+    app.world.spawn((Transform::from_translation(translation), bullet)).id()
+}
+
+fn wounded(app : &mut App) -> usize{
+    app.world.query::<&Health>().iter(&app.world).filter(|health| health.current == 900).count()
+}
+
+
+#[test]
+fn modifiers_stack_on_the_shooters_stats(){
+    let stats = ProjectileStats{damage : 50, speed : 300.0, size : 10.0, pierce : 1, bounces : 0, knockback : 0.0, status : None};
+    let modifiers = [
+        ProjectileModifier::base_level(3),
+        ProjectileModifier{damage_multiplier : 1.5, pierce : 1, bounces : 2, ..default()},
+        ProjectileModifier{knockback : 20.0, ..default()}
+    ];
+    assert_eq!(stats.with(&modifiers), ProjectileStats{damage : 105, speed : 400.0, size : 10.0, pierce : 2, bounces : 2, knockback : 20.0, status : None});
+}
+
+#[test]
+fn piercing_bullets_go_through_that_many_enemies(){
+    let mut app = game_app();
+    for _ in 0..3{
+        spawn_pawn(&mut app, ENEMY_TRANSLATION);
+    }
+    let bullet = spawn_bullet(&mut app, ENEMY_TRANSLATION, Bullet{size : Vec2::splat(BULLET_SIZE), damage : 100, pierce : 1, ..default()});
+    run_updates(&mut app, 3);
+
+    assert_eq!(wounded(&mut app), 2);
+    assert!(app.world.get_entity(bullet).is_none());
+}
+
+#[test]
+fn a_bullet_hits_each_enemy_once(){
+    let mut app = game_app();
+    let pawn = spawn_pawn(&mut app, ENEMY_TRANSLATION);
+    let bullet = spawn_bullet(&mut app, ENEMY_TRANSLATION, Bullet{size : Vec2::splat(BULLET_SIZE), damage : 100, pierce : 3, ..default()});
+    run_updates(&mut app, 5);
+
+    assert_eq!(app.world.get::<Health>(pawn).unwrap().current, 900);
+    assert_eq!(app.world.get::<Bullet>(bullet).unwrap().pierce, 2);
+}
+
+#[test]
+fn bullets_bounce_off_the_edge_while_they_have_bounces(){
+    let mut app = game_app();
+    let width = app.world.resource::<crate::Playfield>().width;
+    let near_the_edge = Vec3::new(width - BULLET_SIZE, 200.0, 0.0);
+    let bouncing = spawn_bullet(&mut app, near_the_edge, Bullet{speed : 300.0, direction : Vec2::X, size : Vec2::splat(BULLET_SIZE), bounces : 1, ..default()});
+    let plain = spawn_bullet(&mut app, near_the_edge, Bullet{speed : 300.0, direction : Vec2::X, size : Vec2::splat(BULLET_SIZE), ..default()});
+    run_updates(&mut app, 5);
+
+    assert!(app.world.get_entity(plain).is_none());
+    let bullet = app.world.get::<Bullet>(bouncing).unwrap();
+    assert_eq!((bullet.direction, bullet.bounces), (Vec2::NEG_X, 0));
+}
+
+#[test]
+fn knockback_pushes_the_enemy_along_the_shot(){
+    let mut app = game_app();
+    let pawn = spawn_pawn(&mut app, ENEMY_TRANSLATION);
+    spawn_bullet(&mut app, ENEMY_TRANSLATION, Bullet{direction : Vec2::Y, size : Vec2::splat(BULLET_SIZE), damage : 10, knockback : 30.0, ..default()});
+    app.update();
+
+    assert_eq!(app.world.get::<Transform>(pawn).unwrap().translation, ENEMY_TRANSLATION + Vec3::new(0.0, 30.0 + HIT_KNOCKBACK, 0.0));
+}
+
+#[test]
+fn the_players_upgrades_are_fired_with_every_shot(){
+    let mut app = game_app();
+    let (player_translation, mut upgrades) = app.world.query_filtered::<(&Transform, &mut ProjectileUpgrades), With<Player>>().single_mut(&mut app.world);
+    let player_translation = player_translation.translation;
+    upgrades.modifiers.push(ProjectileModifier{pierce : 2, bounces : 1, ..default()});
+    {
+        let mut player_input = app.world.resource_mut::<PlayerInput>();
+        player_input.aim = Some(player_translation.truncate() + Vec2::new(0.0, -200.0));
+        player_input.fire = true;
+    }
+    run_updates(&mut app, 2);
+
+    let bullet = app.world.query::<&Bullet>().single(&app.world);
+    assert_eq!((bullet.damage, bullet.pierce, bullet.bounces), (50, 2, 1));
+}
@@ -0,0 +1,74 @@
+use bevy::{prelude::*, input::{ButtonState, keyboard::KeyboardInput}};
+
+use crate::controls::*;
+use super::*;
+
+
+fn press_key(app : &mut App, key_code : KeyCode){
+    app.world.send_event(KeyboardInput{scan_code : 0, key_code : Some(key_code), state : ButtonState::Pressed});
+    app.update();
+}
+
+fn simulation_state(app : &App) -> SimulationState{
+    app.world.resource::<State<SimulationState>>().0
+}
+
+
+#[test]
+fn rebinding_keeps_the_other_device(){
+    let mut input_bindings = InputBindings::default();
+    input_bindings.rebind(Action::Fire, Binding::Key(KeyCode::F));
+    assert_eq!(input_bindings.get(Action::Fire), &[Binding::Gamepad(GamepadButtonType::RightTrigger2), Binding::Key(KeyCode::F)]);
+
+    input_bindings.rebind(Action::Fire, Binding::Gamepad(GamepadButtonType::West));
+    assert_eq!(input_bindings.get(Action::Fire), &[Binding::Key(KeyCode::F), Binding::Gamepad(GamepadButtonType::West)]);
+}
+
+#[test]
+fn the_pause_binding_pauses_the_game(){
+    let mut app = game_app();
+    press_key(&mut app, KeyCode::Space);
+    app.update();
+    assert_eq!(simulation_state(&app), SimulationState::Paused);
+}
+
+#[test]
+fn a_rebound_action_ignores_its_old_key(){
+    let mut app = game_app();
+    app.world.resource_mut::<InputBindings>().rebind(Action::Pause, Binding::Key(KeyCode::P));
+
+    press_key(&mut app, KeyCode::Space);
+    app.update();
+    assert_eq!(simulation_state(&app), SimulationState::Running);
+
+    press_key(&mut app, KeyCode::P);
+    app.update();
+    assert_eq!(simulation_state(&app), SimulationState::Paused);
+}
+
+#[test]
+fn escape_can_be_bound_and_waiting_cancels(){
+    let mut app = game_app();
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::Controls);
+    app.update();
+    let now = app.world.resource::<Time>().elapsed_seconds();
+    *app.world.resource_mut::<Rebinding>() = Rebinding{action : Some(Action::Pause), started : now};
+    press_key(&mut app, KeyCode::Escape);
+    assert!(app.world.resource::<InputBindings>().get(Action::Pause).contains(&Binding::Key(KeyCode::Escape)));
+    assert_eq!(app.world.resource::<Rebinding>().action, None);
+
+    *app.world.resource_mut::<Rebinding>() = Rebinding{action : Some(Action::Fire), started : now};
+    run_updates(&mut app, (REBIND_TIMEOUT / HEADLESS_STEP) as usize + 2);
+    assert_eq!(app.world.resource::<Rebinding>().action, None);
+}
+
+#[test]
+fn start_game_does_nothing_on_the_controls_screen(){
+    let mut app = test_app();
+    app.update();
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::Controls);
+    app.update();
+    press_key(&mut app, KeyCode::G);
+    app.update();
+    assert_eq!(app_state(&app), AppState::Controls);
+}
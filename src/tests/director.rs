@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use crate::director::*;
+use crate::health::*;
+use crate::player::Player;
+use crate::wave::ENDLESS_ENEMY_COUNT;
+use crate::wave_script::SpawnEdge;
+use crate::Playfield;
+use super::*;
+
+
+fn intensity(app : &App) -> f32{
+    app.world.resource::<Director>().intensity
+}
+
+
+#[test]
+fn presets_cycle_and_parse(){
+    assert_eq!(Difficulty::Nightmare.next(), Difficulty::Easy);
+    assert_eq!(Difficulty::Easy.next(), Difficulty::Normal);
+    assert_eq!(Difficulty::from_name("hard"), Some(Difficulty::Hard));
+    assert_eq!(Difficulty::from_name("impossible"), None);
+}
+
+#[test]
+fn harder_presets_send_more_enemies(){
+    let count = ENDLESS_ENEMY_COUNT as usize;
+    let counts : Vec<usize> = Difficulty::ALL.iter().map(|difficulty| Director::new(*difficulty).scale_count(count)).collect();
+    assert!(counts.windows(2).all(|pair| pair[0] < pair[1]), "{counts:?}");
+    assert_eq!(Director::new(Difficulty::Normal).scale_count(count), count);
+    assert_eq!(Director::new(Difficulty::Easy).scale_count(1), 1);
+}
+
+#[test]
+fn the_intensity_follows_the_player_and_stays_in_the_preset(){
+    let mut director = Director::new(Difficulty::Normal);
+    director.spawned = 10;
+    let performance = director.performance(10, 3);
+    assert!(performance > 0.0);
+    director.adapt(performance);
+    assert!(director.intensity > 1.0);
+
+    director.player_deaths = 1;
+    director.spawned = 0;
+    for _ in 0..100{
+        director.adapt(director.performance(0, 0));
+    }
+    assert_eq!(director.intensity, Difficulty::Normal.preset().min_intensity);
+}
+
+#[test]
+fn spawn_edges_lean_away_from_a_player_doing_well(){
+    let playfield = Playfield::default();
+    let mut director = Director::new(Difficulty::Hard);
+    director.update_edge_weights(Vec3::new(100.0, 360.0, 0.0), &playfield);
+    assert!(director.edge_weight(SpawnEdge::Right) > director.edge_weight(SpawnEdge::Left));
+
+    director.intensity = 0.7;
+    director.update_edge_weights(Vec3::new(100.0, 360.0, 0.0), &playfield);
+    assert!(director.edge_weight(SpawnEdge::Right) < director.edge_weight(SpawnEdge::Left));
+
+    let mut game_rng = GameRng::from_seed(TEST_SEED);
+    for _ in 0..20{
+        assert_eq!(director.pick_edge(&[SpawnEdge::Top], &mut game_rng.waves), SpawnEdge::Top);
+    }
+}
+
+#[test]
+fn runs_start_on_the_picked_difficulty(){
+    let mut app = test_app();
+    app.update();
+    app.insert_resource(Difficulty::Nightmare);
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::Game);
+    app.update();
+    assert_eq!(app.world.resource::<Director>().difficulty, Difficulty::Nightmare);
+    assert_eq!(intensity(&app), Difficulty::Nightmare.preset().start_intensity);
+}
+
+#[test]
+fn player_deaths_ease_off_the_intensity(){
+    let mut app = game_app();
+    let player = app.world.query_filtered::<Entity, With<Player>>().single(&app.world);
+    app.world.send_event(DamageEvent{source : None, target : player, amount : 10_000, damage_type : DamageType::Collision});
+    run_updates(&mut app, (DIRECTOR_SAMPLE_INTERVAL / crate::headless::HEADLESS_STEP) as usize + 10);
+    assert!(intensity(&app) < 1.0);
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use crate::enemy::*;
+use crate::bullet::*;
+use crate::health::Health;
+use super::*;
+
+
+//far enough from the player and the base that only the bullet touches it
+const ENEMY_TRANSLATION : Vec3 = Vec3::new(200.0, 200.0, 0.0);
+
+fn spawn_enemy_with_health(app : &mut App, kind : EnemyKind, health : i64) -> Entity{
+    let (_, _, enemy) = kind.template(0.0);
+    app.world.spawn((Transform::from_translation(ENEMY_TRANSLATION), enemy, Health::new(health))).id()
+}
+
+fn spawn_bullet(app : &mut App, translation : Vec3, damage : i64){
+    app.world.spawn((
+        Transform::from_translation(translation),
+        Bullet{speed : 0.0, direction : Vec2::ZERO, size : Vec2::splat(BULLET_SIZE), damage, spawn_time : 0.0, ..default()}
+    ));
+}
+
+fn split_counts(app : &mut App) -> Vec<usize>{
+    app.world.query::<&Enemy>().iter(&app.world)
+        .filter_map(|enemy| match enemy.variant {
+            EnemyType::Splitter{split_count, ..} => Some(split_count),
+            _ => None
+        })
+        .collect()
+}
+
+
+#[test]
+fn splitter_splits_into_two_on_death(){
+    let mut app = game_app();
+    let splitter = spawn_enemy_with_health(&mut app, EnemyKind::Splitter, 1);
+    spawn_bullet(&mut app, ENEMY_TRANSLATION, 10);
+    app.update();
+
+    assert!(app.world.get_entity(splitter).is_none());
+    assert_eq!(split_counts(&mut app), vec![1, 1]);
+    assert_eq!(count::<Bullet>(&mut app), 0);
+}
+
+fn spawn_split_splitter(app : &mut App, split_count : usize){
+    let (_, _, mut enemy) = EnemyKind::Splitter.template(0.0);
+    enemy.variant = EnemyType::Splitter{split_count, spawn_time : 0.0, direction : Vec3::X};
+    app.world.spawn((Transform::from_translation(ENEMY_TRANSLATION), enemy, Health::new(1)));
+}
+
+#[test]
+fn split_children_split_once_more(){
+    let mut app = game_app();
+    spawn_split_splitter(&mut app, 1);
+    spawn_bullet(&mut app, ENEMY_TRANSLATION, 10);
+    app.update();
+
+    assert_eq!(split_counts(&mut app), vec![2, 2]);
+}
+
+#[test]
+fn smallest_splitters_do_not_split(){
+    let mut app = game_app();
+    spawn_split_splitter(&mut app, 2);
+    spawn_bullet(&mut app, ENEMY_TRANSLATION, 10);
+    app.update();
+
+    assert!(split_counts(&mut app).is_empty());
+}
+
+#[test]
+fn bullet_damage_below_health_only_wounds(){
+    let mut app = game_app();
+    let pawn = spawn_enemy_with_health(&mut app, EnemyKind::Pawn, 100);
+    spawn_bullet(&mut app, ENEMY_TRANSLATION, 10);
+    app.update();
+
+    //bullets hit as hard as the weapon that fired them made them
+    assert_eq!(app.world.get::<Health>(pawn).unwrap().current, 90);
+}
+
+#[test]
+fn every_enemy_has_a_sprite_that_ships(){
+    let kinds = [EnemyKind::Pawn, EnemyKind::Stinger, EnemyKind::Splitter, EnemyKind::Rogue, EnemyKind::Bishop, EnemyKind::Propagator, EnemyKind::Gunner];
+    for kind in kinds{
+        let (texture, _, _) = kind.template(0.0);
+        assert!(shipped_asset(texture), "{:?} has no sprite", kind);
+    }
+}
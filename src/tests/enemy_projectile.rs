@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+use crate::base::Base;
+use crate::enemy::*;
+use crate::enemy_projectile::*;
+use crate::health::Health;
+use crate::player::Player;
+use super::*;
+
+
+fn translation<T : Component>(app : &mut App) -> Vec3{
+    app.world.query_filtered::<&Transform, With<T>>().single(&app.world).translation
+}
+
+fn health<T : Component>(app : &mut App) -> Health{
+    *app.world.query_filtered::<&Health, With<T>>().single(&app.world)
+}
+
+fn spawn_shot(app : &mut App, translation : Vec3, direction : Vec2) -> Entity{
+    app.world.spawn((
+        Transform::from_translation(translation),
+        EnemyProjectile{direction, speed : ENEMY_PROJECTILE_SPEED, size : Vec2::splat(ENEMY_PROJECTILE_SIZE), damage : ENEMY_PROJECTILE_DAMAGE, spawn_time : 0.0}
+    )).id()
+}
+
+
+#[test]
+fn gunners_stop_short_of_the_base_and_open_fire(){
+    let mut app = game_app();
+    let base_translation = translation::<Base>(&mut app);
+    let (_, _, gunner) = EnemyKind::Gunner.template(-GUNNER_FIRE_COOLDOWN);
+    let gunner = app.world.spawn((Transform::from_translation(base_translation + Vec3::new(0.0, GUNNER_STANDOFF + 30.0, 0.0)), gunner, Health::new(80))).id();
+    run_updates(&mut app, 150);
+
+    let distance = app.world.get::<Transform>(gunner).unwrap().translation.distance(base_translation);
+    assert!((distance - GUNNER_STANDOFF).abs() < 1.0, "{distance}");
+    assert!(health::<Base>(&mut app).current < health::<Base>(&mut app).max);
+}
+
+#[test]
+fn enemy_shots_fly_through_enemies_into_the_base(){
+    let mut app = game_app();
+    let base_translation = translation::<Base>(&mut app);
+    let (_, _, mut pawn) = EnemyKind::Pawn.template(0.0);
+    pawn.speed = 0.0;
+    let pawn = app.world.spawn((Transform::from_translation(base_translation + Vec3::new(60.0, 0.0, 0.0)), pawn, Health::new(50))).id();
+    let shot = spawn_shot(&mut app, base_translation + Vec3::new(120.0, 0.0, 0.0), Vec2::NEG_X);
+    let base_health = health::<Base>(&mut app).current;
+    run_updates(&mut app, 60);
+
+    assert_eq!(app.world.get::<Health>(pawn).unwrap().current, 50);
+    assert_eq!(health::<Base>(&mut app).current, base_health - ENEMY_PROJECTILE_DAMAGE);
+    assert!(app.world.get_entity(shot).is_none());
+}
+
+#[test]
+fn enemy_shots_hurt_the_player(){
+    let mut app = game_app();
+    let player_translation = translation::<Player>(&mut app);
+    let player_health = health::<Player>(&mut app).current;
+    spawn_shot(&mut app, player_translation + Vec3::new(-30.0, 0.0, 0.0), Vec2::X);
+    run_updates(&mut app, 20);
+    assert_eq!(health::<Player>(&mut app).current, player_health - ENEMY_PROJECTILE_DAMAGE);
+    assert_eq!(count::<EnemyProjectile>(&mut app), 0);
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+
+use crate::base::Base;
+use crate::bullet::*;
+use crate::enemy::*;
+use crate::feedback::*;
+use crate::settings::Settings;
+use crate::health::*;
+use super::*;
+
+
+const ENEMY_TRANSLATION : Vec3 = Vec3::new(200.0, 200.0, 0.0);
+
+fn spawn_pawn(app : &mut App) -> Entity{
+    let (_, _, mut pawn) = EnemyKind::Pawn.template(0.0);
+    pawn.speed = 0.0;
+    app.world.spawn((SpriteBundle{transform : Transform::from_translation(ENEMY_TRANSLATION), ..default()}, pawn, Health::new(1000))).id()
+}
+
+fn hurt(app : &mut App, enemy : Entity, amount : i64){
+    app.world.send_event(DamageEvent{source : None, target : enemy, amount, damage_type : DamageType::Bullet});
+}
+
+fn damage_number_texts(app : &mut App) -> Vec<String>{
+    app.world.query_filtered::<&Text, With<DamageNumber>>().iter(&app.world).map(|text| text.sections[0].value.clone()).collect()
+}
+
+fn hit_the_base(app : &mut App){
+    let base = app.world.query_filtered::<Entity, With<Base>>().single(&app.world);
+    app.world.send_event(DamageEvent{source : None, target : base, amount : 1, damage_type : DamageType::Collision});
+}
+
+
+#[test]
+fn hits_float_up_as_one_number_per_frame(){
+    let mut app = game_app();
+    let pawn = spawn_pawn(&mut app);
+    run_updates(&mut app, 2);
+    hurt(&mut app, pawn, 20);
+    hurt(&mut app, pawn, 30);
+    app.update();
+    assert_eq!(damage_number_texts(&mut app), vec!["50".to_string()]);
+    let start = app.world.query_filtered::<&Transform, With<DamageNumber>>().single(&app.world).translation;
+
+    run_updates(&mut app, 10);
+    assert!(app.world.query_filtered::<&Transform, With<DamageNumber>>().single(&app.world).translation.y > start.y);
+    run_updates(&mut app, 60);
+    assert_eq!(count::<DamageNumber>(&mut app), 0);
+}
+
+#[test]
+fn hits_flash_the_enemy_for_a_moment(){
+    let mut app = game_app();
+    let pawn = spawn_pawn(&mut app);
+    run_updates(&mut app, 2);
+    hurt(&mut app, pawn, 20);
+    app.update();
+    assert_eq!(app.world.get::<Sprite>(pawn).unwrap().color, HIT_FLASH_COLOR);
+
+    run_updates(&mut app, 20);
+    assert_eq!(app.world.get::<Sprite>(pawn).unwrap().color, Color::WHITE);
+    assert!(app.world.get::<HitFlash>(pawn).is_none());
+}
+
+#[test]
+fn base_hits_shake_the_camera_back_into_place(){
+    let mut app = game_app();
+    let camera = app.world.spawn(Camera2dBundle::default()).id();
+    hit_the_base(&mut app);
+    app.update();
+    assert!(app.world.resource::<ScreenShake>().trauma > 0.0);
+    assert_ne!(app.world.get::<Transform>(camera).unwrap().translation.truncate(), Vec2::ZERO);
+
+    run_updates(&mut app, 60);
+    assert_eq!(app.world.resource::<ScreenShake>().trauma, 0.0);
+    assert!(app.world.get::<Transform>(camera).unwrap().translation.truncate().length() < 1e-3);
+}
+
+#[test]
+fn everything_can_be_turned_off(){
+    let mut app = game_app();
+    *app.world.resource_mut::<Settings>() = Settings{damage_numbers : false, hit_flash : false, knockback : false, screen_shake : false, ..default()};
+    let pawn = spawn_pawn(&mut app);
+    run_updates(&mut app, 2);
+    app.world.spawn((Transform::from_translation(ENEMY_TRANSLATION), Bullet{direction : Vec2::Y, size : Vec2::splat(BULLET_SIZE), damage : 10, knockback : 30.0, ..default()}));
+    hit_the_base(&mut app);
+    app.update();
+
+    assert_eq!(app.world.get::<Health>(pawn).unwrap().current, 990);
+    assert_eq!(app.world.get::<Transform>(pawn).unwrap().translation, ENEMY_TRANSLATION);
+    assert_eq!(count::<DamageNumber>(&mut app), 0);
+    assert_eq!(app.world.get::<Sprite>(pawn).unwrap().color, Color::WHITE);
+    assert_eq!(app.world.resource::<ScreenShake>().trauma, 0.0);
+}
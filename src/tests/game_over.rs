@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use crate::base::Base;
+use crate::enemy::*;
+use crate::game_over::score_comment;
+use crate::high_scores::HighScores;
+use crate::health::Health;
+use crate::FinalScore;
+use super::*;
+
+
+#[test]
+fn score_comments_follow_time_survived(){
+    assert_eq!(score_comment(0), "Better luck next time");
+    assert_eq!(score_comment(119), "Better luck next time");
+    assert_eq!(score_comment(120), "Nice");
+    assert_eq!(score_comment(239), "Nice");
+    assert_eq!(score_comment(240), "Fantastic score");
+    assert_eq!(score_comment(599), "Fantastic score");
+    assert_eq!(score_comment(600), "You are awesome");
+}
+
+#[test]
+fn destroying_the_base_ends_the_game_once(){
+    let mut app = game_app();
+    let base_translation = {
+        let (transform, mut health) = app.world.query_filtered::<(&Transform, &mut Health), With<Base>>().single_mut(&mut app.world);
+        health.current = 1;
+        transform.translation
+    };
+    //two enemies reach the base in the same update, only one game over is recorded
+    for offset in [Vec3::X, Vec3::NEG_X]{
+        let (_, _, enemy) = EnemyKind::Pawn.template(0.0);
+        app.world.spawn((Transform::from_translation(base_translation + offset * 20.0), enemy, Health::new(EnemyKind::Pawn.health())));
+    }
+    run_updates(&mut app, 3);
+
+    assert_eq!(app_state(&app), AppState::GameOver);
+    assert_eq!(app.world.resource::<FinalScore>().scores, vec![(1, 0)]);
+    assert_eq!(count::<Base>(&mut app), 0);
+
+    let high_scores = app.world.resource::<HighScores>();
+    assert_eq!(high_scores.scores.len(), 1);
+    assert_eq!(high_scores.new_record, Some(0));
+    assert_eq!(high_scores.scores[0].seed, TEST_SEED);
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use crate::game_time::*;
+use crate::part::*;
+use super::*;
+
+
+fn simulated_seconds(app : &App) -> f32{
+    app.world.resource::<SimulationClock>().now()
+}
+
+
+#[test]
+fn the_clock_stops_while_paused(){
+    let mut app = game_app();
+    run_updates(&mut app, 60);
+    let before_pause = simulated_seconds(&app);
+    assert!(before_pause > 0.9);
+
+    app.world.resource_mut::<NextState<SimulationState>>().set(SimulationState::Paused);
+    run_updates(&mut app, 120);
+    assert!(simulated_seconds(&app) - before_pause < 0.05);
+}
+
+#[test]
+fn parts_do_not_expire_while_paused(){
+    let mut app = game_app();
+    let part = app.world.spawn((
+        Transform::from_xyz(50.0, 50.0, 0.0),
+        Part{part_tier : PartTier::Blue, size : Vec2::new(15.0, 15.0), spawn_time : simulated_seconds(&app)}
+    )).id();
+    app.world.resource_mut::<NextState<SimulationState>>().set(SimulationState::Paused);
+    //well past the ten seconds a part lies around for
+    run_updates(&mut app, 60 * 15);
+    app.world.resource_mut::<NextState<SimulationState>>().set(SimulationState::Running);
+    run_updates(&mut app, 2);
+    assert!(app.world.get_entity(part).is_some());
+}
+
+#[test]
+fn game_speed_scales_the_clock(){
+    let mut app = game_app();
+    app.world.resource_mut::<GameSpeed>().speed = 2.0;
+    app.update();
+    let start = simulated_seconds(&app);
+    run_updates(&mut app, 60);
+    let elapsed = simulated_seconds(&app) - start;
+    assert!((elapsed - 2.0).abs() < 0.05, "{}", elapsed);
+}
+
+#[test]
+fn leaving_the_game_resets_the_speed(){
+    let mut app = game_app();
+    app.world.resource_mut::<GameSpeed>().speed = 4.0;
+    app.update();
+    assert_eq!(app.world.resource::<Time>().relative_speed(), 4.0);
+
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::MainMenu);
+    app.update();
+    assert_eq!(app.world.resource::<GameSpeed>().speed, 1.0);
+    assert_eq!(app.world.resource::<Time>().relative_speed(), 1.0);
+}
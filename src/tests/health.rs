@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::base::Base;
+use crate::enemy::*;
+use crate::health::*;
+use crate::player::Player;
+use crate::RunStats;
+use super::*;
+
+
+fn deaths(app : &App) -> Vec<DeathEvent>{
+    let events = app.world.resource::<Events<DeathEvent>>();
+    events.get_reader().iter(events).copied().collect()
+}
+
+
+#[test]
+fn overkill_only_kills_once(){
+    let mut app = game_app();
+    let target = app.world.spawn(Health::new(10)).id();
+    for _ in 0..2{
+        app.world.send_event(DamageEvent{source : None, target, amount : 20, damage_type : DamageType::Bullet});
+    }
+    app.update();
+
+    assert_eq!(deaths(&app), vec![DeathEvent{entity : target, damage_type : DamageType::Bullet}]);
+    assert_eq!(app.world.get::<Health>(target).unwrap().current, -10);
+}
+
+#[test]
+fn the_player_respawns_at_the_base_with_full_health(){
+    let mut app = game_app();
+    let base_translation = app.world.query_filtered::<&Transform, With<Base>>().single(&app.world).translation;
+    let player = app.world.query_filtered::<Entity, With<Player>>().single(&app.world);
+    app.world.get_mut::<Transform>(player).unwrap().translation = base_translation + Vec3::new(150.0, 0.0, 0.0);
+    app.world.send_event(DamageEvent{source : None, target : player, amount : 1000, damage_type : DamageType::Collision});
+    app.update();
+
+    assert_eq!(app.world.get::<Transform>(player).unwrap().translation, base_translation);
+    let health = app.world.get::<Health>(player).unwrap();
+    assert_eq!(health.current, health.max);
+}
+
+#[test]
+fn an_enemy_ramming_the_base_deals_its_health_and_is_no_kill(){
+    let mut app = game_app();
+    let (base_translation, base_health) = {
+        let (transform, health) = app.world.query_filtered::<(&Transform, &Health), With<Base>>().single(&app.world);
+        (transform.translation, health.current)
+    };
+    let (_, _, enemy) = EnemyKind::Rogue.template(0.0);
+    let rogue = app.world.spawn((Transform::from_translation(base_translation + Vec3::X * 20.0), enemy, Health{current : 120, max : 200})).id();
+    app.update();
+
+    assert!(app.world.get_entity(rogue).is_none());
+    assert_eq!(app.world.query_filtered::<&Health, With<Base>>().single(&app.world).current, base_health - 120);
+    assert_eq!(app.world.resource::<RunStats>().kills, 0);
+}
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+use crate::enemy::*;
+use crate::health::*;
+use crate::health_bar::*;
+use crate::settings::Settings;
+use super::*;
+
+
+fn spawn_pawn(app : &mut App) -> Entity{
+    let (_, _, mut pawn) = EnemyKind::Pawn.template(0.0);
+    pawn.speed = 0.0;
+    app.world.spawn((SpriteBundle{transform : Transform::from_xyz(100.0, 100.0, 0.0), ..default()}, pawn, Health::new(100))).id()
+}
+
+fn hurt(app : &mut App, enemy : Entity, amount : i64){
+    app.world.send_event(DamageEvent{source : None, target : enemy, amount, damage_type : DamageType::Bullet});
+    run_updates(app, 2);
+}
+
+fn fill_width(app : &mut App) -> f32{
+    app.world.query_filtered::<&Sprite, With<EnemyHealthBarFill>>().single(&app.world).custom_size.unwrap().x
+}
+
+
+#[test]
+fn hurt_enemies_get_a_bar_that_goes_with_them(){
+    let mut app = game_app();
+    let pawn = spawn_pawn(&mut app);
+    run_updates(&mut app, 2);
+    assert_eq!(count::<EnemyHealthBar>(&mut app), 0);
+
+    hurt(&mut app, pawn, 25);
+    assert_eq!(count::<EnemyHealthBar>(&mut app), 1);
+    let width = enemy_health_bar_width(app.world.get::<Enemy>(pawn).unwrap());
+    assert_eq!(fill_width(&mut app), width * 0.75);
+    let bar_translation = app.world.query_filtered::<&Transform, With<EnemyHealthBar>>().single(&app.world).translation;
+    assert!(bar_translation.y > 100.0);
+
+    hurt(&mut app, pawn, 100);
+    assert_eq!(count::<EnemyHealthBar>(&mut app), 0);
+    assert_eq!(count::<EnemyHealthBarFill>(&mut app), 0);
+}
+
+#[test]
+fn the_bars_can_be_turned_off(){
+    let mut app = game_app();
+    let pawn = spawn_pawn(&mut app);
+    hurt(&mut app, pawn, 25);
+    assert_eq!(count::<EnemyHealthBar>(&mut app), 1);
+
+    app.world.resource_mut::<Settings>().enemy_health_bars = false;
+    hurt(&mut app, pawn, 25);
+    assert_eq!(count::<EnemyHealthBar>(&mut app), 0);
+    assert!(app.world.get::<HasHealthBar>(pawn).is_none());
+}
@@ -0,0 +1,38 @@
+use crate::high_scores::*;
+
+
+fn high_score(waves : usize, base_level : i64, kills : u64) -> HighScore{
+    HighScore{date : 0, seed : 0, waves, base_level, time_alive : 0, kills}
+}
+
+
+#[test]
+fn scores_are_ranked_by_waves_then_level_then_kills(){
+    let mut high_scores = HighScores::default();
+    assert_eq!(high_scores.insert(high_score(5, 2, 10)), Some(0));
+    assert_eq!(high_scores.insert(high_score(8, 1, 0)), Some(0));
+    assert_eq!(high_scores.insert(high_score(5, 3, 0)), Some(1));
+    assert_eq!(high_scores.insert(high_score(5, 2, 20)), Some(2));
+
+    let waves : Vec<(usize, i64, u64)> = high_scores.scores.iter().map(|score| (score.waves, score.base_level, score.kills)).collect();
+    assert_eq!(waves, vec![(8, 1, 0), (5, 3, 0), (5, 2, 20), (5, 2, 10)]);
+}
+
+#[test]
+fn the_table_keeps_the_best_ten(){
+    let mut high_scores = HighScores::default();
+    for waves in 1..=MAX_HIGH_SCORES{
+        high_scores.insert(high_score(waves, 1, 0));
+    }
+    assert_eq!(high_scores.insert(high_score(0, 1, 0)), None);
+    assert_eq!(high_scores.insert(high_score(50, 1, 0)), Some(0));
+    assert_eq!(high_scores.scores.len(), MAX_HIGH_SCORES);
+    assert_eq!(high_scores.scores.last().unwrap().waves, 2);
+}
+
+#[test]
+fn dates_are_formatted_from_unix_time(){
+    assert_eq!(format_date(0), "1970-01-01");
+    assert_eq!(format_date(951_782_400), "2000-02-29");
+    assert_eq!(format_date(1_704_067_199), "2023-12-31");
+}
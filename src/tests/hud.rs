@@ -0,0 +1,99 @@
+use std::time::Duration;
+use bevy::prelude::*;
+
+use crate::base::Base;
+use crate::enemy::EnemyKind;
+use crate::health::Health;
+use crate::hud::*;
+use crate::settings::Settings;
+use crate::wave::*;
+use crate::RunStats;
+use super::*;
+
+
+//the wave timer runs out after the given seconds
+fn next_wave_in(app : &mut App, seconds : f32){
+    let mut wave_timer = app.world.resource_mut::<WaveTimer>();
+    let duration = wave_timer.timer.duration();
+    wave_timer.timer.set_elapsed(duration - Duration::from_secs_f32(seconds));
+}
+
+fn health_bar(app : &mut App, owner : HealthBarOwner) -> (Val, String){
+    let width = app.world.query::<(&HealthBarFill, &Style)>().iter(&app.world).find(|(fill, _)| fill.0 == owner).unwrap().1.size.width;
+    let text = app.world.query::<(&HealthBarText, &Text)>().iter(&app.world).find(|(text, _)| text.0 == owner).unwrap().1.sections[0].value.clone();
+    (width, text)
+}
+
+fn visibility<T : Component>(app : &mut App) -> Visibility{
+    *app.world.query_filtered::<&Visibility, With<T>>().single(&app.world)
+}
+
+
+#[test]
+fn the_banner_names_the_new_enemies(){
+    assert_eq!(wave_banner_text(&WaveStarted{wave : 3, new_kinds : Vec::new(), boss : false}), "Wave 3 incoming");
+    assert_eq!(wave_banner_text(&WaveStarted{wave : 15, new_kinds : vec![EnemyKind::Rogue], boss : false}), "Wave 15 incoming\nRogues have appeared");
+    assert_eq!(
+        wave_banner_text(&WaveStarted{wave : 40, new_kinds : vec![EnemyKind::Bishop, EnemyKind::Gunner, EnemyKind::Propagator], boss : true}),
+        "Wave 40 incoming\nBishops, Gunners and Propagators have appeared\nA boss approaches"
+    );
+}
+
+#[test]
+fn the_banner_shows_when_a_wave_starts(){
+    let mut app = game_app();
+    next_wave_in(&mut app, 0.001);
+    run_updates(&mut app, 2);
+    assert_eq!(visibility::<WaveBanner>(&mut app), Visibility::Inherited);
+    run_updates(&mut app, (WAVE_BANNER_TIME * 60.0) as usize + 10);
+    assert_eq!(visibility::<WaveBanner>(&mut app), Visibility::Hidden);
+}
+
+#[test]
+fn the_summary_shows_before_the_next_wave_unless_turned_off(){
+    let mut app = game_app();
+    app.world.insert_resource(WaveStats{wave : 2, kills_at_start : 3, parts_at_start : 0, base_damage : 40});
+    app.world.resource_mut::<RunStats>().kills = 7;
+    next_wave_in(&mut app, WAVE_SUMMARY_TIME + 2.0);
+    app.update();
+    assert_eq!(visibility::<WaveSummaryPanel>(&mut app), Visibility::Hidden);
+
+    next_wave_in(&mut app, WAVE_SUMMARY_TIME - 1.0);
+    app.update();
+    assert_eq!(visibility::<WaveSummaryPanel>(&mut app), Visibility::Inherited);
+    let text = app.world.query_filtered::<&Text, With<WaveSummaryText>>().single(&app.world).sections[0].value.clone();
+    assert_eq!(text, "Wave 2 summary\nKills  4\nParts collected  0\nBase damage taken  40");
+
+    app.world.resource_mut::<Settings>().wave_summary = false;
+    app.update();
+    assert_eq!(visibility::<WaveSummaryPanel>(&mut app), Visibility::Hidden);
+}
+
+#[test]
+fn health_bars_follow_the_level_ups(){
+    let mut app = game_app();
+    app.update();
+    assert_eq!(health_bar(&mut app, HealthBarOwner::Base), (Val::Percent(100.0), "Base  level 1  500/500".to_string()));
+    assert_eq!(health_bar(&mut app, HealthBarOwner::Player).1, "Player  100/100");
+
+    let mut base_health = app.world.query_filtered::<&mut Health, With<Base>>().single_mut(&mut app.world);
+    base_health.reset(1000);
+    base_health.current = 250;
+    app.update();
+    assert_eq!(health_bar(&mut app, HealthBarOwner::Base), (Val::Percent(25.0), "Base  level 1  250/1000".to_string()));
+}
+
+#[test]
+fn the_warning_flashes_while_the_base_is_low(){
+    let mut app = game_app();
+    run_updates(&mut app, 60);
+    assert_eq!(visibility::<LowBaseWarning>(&mut app), Visibility::Hidden);
+
+    app.world.query_filtered::<&mut Health, With<Base>>().single_mut(&mut app.world).current = 100;
+    let mut shown = Vec::new();
+    for _ in 0..60{
+        app.update();
+        shown.push(visibility::<LowBaseWarning>(&mut app) == Visibility::Inherited);
+    }
+    assert!(shown.contains(&true) && shown.contains(&false));
+}
@@ -0,0 +1,93 @@
+use rand::{rngs::StdRng, SeedableRng};
+use bevy::prelude::*;
+
+use crate::enemy::*;
+use crate::loot::*;
+use crate::part::*;
+use crate::bullet::*;
+use crate::health::Health;
+use crate::boss::*;
+use crate::player::Player;
+use super::*;
+
+
+fn table(chance : f32, tiers : Vec<TierWeight>, quantity : usize, pity : Option<u32>) -> LootTable{
+    LootTable{enemy : EnemyKind::Pawn, split_count : None, chance, tiers, quantity, pity}
+}
+
+
+#[test]
+fn default_loot_tables_parse(){
+    let loot_tables : LootTables = ron::de::from_bytes(include_bytes!("../../assets/Data/default.loot.ron")).unwrap();
+    assert!(loot_tables.find(&EnemyType::Pawn).is_some());
+    //only the middle splitter stage drops parts
+    assert!(loot_tables.find(&EnemyType::Splitter{split_count : 0, spawn_time : 0.0, direction : Vec3::ZERO}).is_none());
+    assert!(loot_tables.find(&EnemyType::Splitter{split_count : 1, spawn_time : 0.0, direction : Vec3::ZERO}).is_some());
+}
+
+#[test]
+fn pity_guarantees_a_drop(){
+    let loot_table = table(0.0, vec![TierWeight{tier : PartTier::Green, weight : 1.0}], 1, Some(3));
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut misses = 0;
+    for _ in 0..3{
+        assert!(loot_table.roll(&mut misses, &mut rng).is_empty());
+    }
+    assert_eq!(loot_table.roll(&mut misses, &mut rng), vec![PartTier::Green]);
+    assert_eq!(misses, 0);
+}
+
+#[test]
+fn quantity_and_weights_decide_the_drop(){
+    let loot_table = table(1.0, vec![TierWeight{tier : PartTier::Blue, weight : 0.0}, TierWeight{tier : PartTier::Red, weight : 1.0}], 3, None);
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut misses = 0;
+    assert_eq!(loot_table.roll(&mut misses, &mut rng), vec![PartTier::Red; 3]);
+}
+
+#[test]
+fn a_shot_propagator_always_drops_a_part(){
+    let mut app = game_app();
+    //the tables are read from disk in the background
+    for _ in 0..200{
+        let handle = app.world.resource::<LootTablesHandle>().handle.clone();
+        if app.world.resource::<Assets<LootTables>>().contains(&handle){
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        app.update();
+    }
+    let translation = Vec3::new(200.0, 200.0, 0.0);
+    let (_, _, propagator) = EnemyKind::Propagator.template(0.0);
+    app.world.spawn((Transform::from_translation(translation), propagator, Health::new(1)));
+    app.world.spawn((
+        Transform::from_translation(translation),
+        Bullet{speed : 0.0, direction : Vec2::ZERO, size : Vec2::splat(BULLET_SIZE), damage : 10, spawn_time : 0.0, ..default()}
+    ));
+    app.update();
+
+    assert_eq!(count::<Part>(&mut app), 1);
+}
+
+#[test]
+fn a_shot_boss_leaves_an_upgrade_for_the_player(){
+    let mut app = game_app();
+    let translation = Vec3::new(200.0, 200.0, 0.0);
+    let state = BossState{phase : BossPhase::Summoning, last_ability : 0.0, charge : None, last_contact : 0.0};
+    app.world.spawn((Transform::from_translation(translation), Enemy{variant : EnemyType::Boss(state), speed : 0.0, size : Vec2::splat(BOSS_SIZE)}, Health::new(1)));
+    app.world.spawn((
+        Transform::from_translation(translation),
+        Bullet{speed : 0.0, direction : Vec2::ZERO, size : Vec2::splat(BULLET_SIZE), damage : 10, spawn_time : 0.0, ..default()}
+    ));
+    app.update();
+    assert_eq!(count::<UpgradePickup>(&mut app), 1);
+
+    //walking over it hands it to the player's shots
+    let player_translation = app.world.query_filtered::<&Transform, With<Player>>().single(&app.world).translation;
+    let pickup = app.world.query_filtered::<Entity, With<UpgradePickup>>().single(&app.world);
+    app.world.get_mut::<Transform>(pickup).unwrap().translation = player_translation;
+    app.update();
+
+    assert_eq!(count::<UpgradePickup>(&mut app), 0);
+    assert_eq!(app.world.query_filtered::<&ProjectileUpgrades, With<Player>>().single(&app.world).modifiers.len(), 1);
+}
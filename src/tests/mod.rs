@@ -0,0 +1,81 @@
+//Gameplay tests, each one builds the game the way the headless mode does and steps it one update at a time
+use bevy::prelude::*;
+
+use crate::headless::{HeadlessGamePlugin, HEADLESS_STEP};
+use crate::rng::GameRng;
+use super::{AppState, SimulationState};
+
+mod base;
+mod boss;
+mod building;
+mod bullet;
+mod controls;
+mod director;
+mod enemy;
+mod enemy_projectile;
+mod feedback;
+mod game_over;
+mod game_time;
+mod health;
+mod health_bar;
+mod high_scores;
+mod hud;
+mod loot;
+mod rng;
+mod savegame;
+mod settings;
+mod spatial;
+mod status;
+mod steering;
+mod turret;
+mod wave;
+mod weapon;
+
+
+pub const TEST_SEED : u64 = 1;
+
+
+pub fn test_app() -> App{
+    let mut app = App::new();
+    app.add_plugin(HeadlessGamePlugin{step : HEADLESS_STEP});
+    app
+}
+
+//builds the app and enters a running game, with the player and base spawned
+pub fn game_app() -> App{
+    let mut app = test_app();
+    app.update();
+    {
+        let mut game_rng = app.world.resource_mut::<GameRng>();
+        game_rng.seed = TEST_SEED;
+        game_rng.fixed_seed = true;
+    }
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::Game);
+    app.update();
+    //entering the game pauses it until space is pressed
+    app.world.resource_mut::<NextState<SimulationState>>().set(SimulationState::Running);
+    app.update();
+    app
+}
+
+//steps the app, each update moves time by one HEADLESS_STEP
+pub fn run_updates(app : &mut App, updates : usize){
+    for _ in 0..updates{
+        app.update();
+    }
+}
+
+pub fn count<T : Component>(app : &mut App) -> usize{
+    app.world.query::<&T>().iter(&app.world).count()
+}
+
+pub fn app_state(app : &App) -> AppState{
+    app.world.resource::<State<AppState>>().0
+}
+
+//the file names in a zip are stored as they are, so finding the path is enough to know it ships
+pub fn shipped_asset(path : &str) -> bool{
+    let archive : &[u8] = include_bytes!("../../Assets.zip");
+    let name = format!("Assets/{}", path);
+    archive.windows(name.len()).any(|window| window == name.as_bytes())
+}
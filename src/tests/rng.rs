@@ -0,0 +1,26 @@
+use rand::prelude::*;
+
+use crate::rng::GameRng;
+use super::*;
+
+
+//seeds are shown on the game over screen and the leaderboard, they have to replay the same run after a dependency bump
+#[test]
+fn a_seed_rolls_the_same_numbers_everywhere(){
+    let mut game_rng = GameRng::from_seed(TEST_SEED);
+    assert_eq!(game_rng.waves.next_u64(), 3965925770804552125);
+    assert_eq!(game_rng.movement.next_u64(), 7972765381561288352);
+    assert_eq!(game_rng.loot.next_u64(), 8532569489115770013);
+}
+
+#[test]
+fn streams_go_on_from_their_saved_positions(){
+    let mut game_rng = GameRng::from_seed(TEST_SEED);
+    for _ in 0..10{
+        game_rng.loot.next_u64();
+    }
+    let mut continued = GameRng::from_seed(TEST_SEED);
+    continued.set_positions(game_rng.positions());
+    assert_eq!(continued.loot.next_u64(), game_rng.loot.next_u64());
+    assert_eq!(continued.waves.next_u64(), game_rng.waves.next_u64());
+}
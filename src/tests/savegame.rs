@@ -0,0 +1,158 @@
+use rand::prelude::*;
+use bevy::prelude::*;
+
+use crate::base::Base;
+use crate::enemy::*;
+use crate::part::*;
+use crate::savegame::*;
+use crate::turret::*;
+use crate::wave::{WaveTimer, WaveStats, TrickleBudget};
+use crate::loot::LootPity;
+use crate::rng::GameRng;
+use crate::player::Player;
+use crate::RunStats;
+use crate::health::Health;
+use crate::building::*;
+use crate::weapon::*;
+use super::*;
+
+
+fn temporary_slot(name : &str) -> SaveGameSlot{
+    SaveGameSlot{path : Some(std::env::temp_dir().join(format!("cellwar-{}-{}.ron", name, std::process::id())))}
+}
+
+
+#[test]
+fn a_saved_run_is_restored(){
+    let mut app = game_app();
+    let slot = temporary_slot("restore");
+    app.insert_resource(SaveGameSlot{path : slot.path.clone()});
+    {
+        let mut base = app.world.query::<&mut Base>().single_mut(&mut app.world);
+        base.level = 3;
+        base.max_parts = 5;
+        base.parts_required = vec![PartTier::Red, PartTier::Blue];
+        base.parts = vec![Part{part_tier : PartTier::Red, size : Vec2::new(15.0, 15.0), spawn_time : 0.0}];
+    }
+    let (_, _, splitter) = EnemyKind::Splitter.template(0.0);
+    app.world.spawn((Transform::from_xyz(100.0, 100.0, 0.0), splitter, Health{current : 40, max : 100}));
+    let turret_stats = TurretStats{range : 300.0, fire_rate : 0.5, damage : 80};
+    app.world.spawn((Transform::from_xyz(50.0, 50.0, 0.0), Turret::new(turret_stats, TargetingMode::Strongest)));
+    app.world.resource_mut::<WaveTimer>().wave = 4;
+    app.world.resource_mut::<RunStats>().kills = 7;
+    app.world.spawn((Transform::from_xyz(200.0, 50.0, 0.0), PlacedBuilding{kind : BuildingKind::Wall}, Health{current : 120, max : 400}));
+    *app.world.resource_mut::<PartInventory>() = PartInventory{blue : 2, red : 1, green : 0};
+    app.world.query::<&mut Weapon>().single_mut(&mut app.world).select(WeaponKind::SpreadShot);
+
+    app.world.send_event(SaveGameRequest);
+    run_updates(&mut app, 2);
+    assert_eq!(app_state(&app), AppState::MainMenu);
+    assert!(slot.exists());
+    assert_eq!(count::<Enemy>(&mut app), 0);
+
+    let save_game = slot.read().unwrap();
+    assert!(slot.exists());
+    app.insert_resource(PendingSave(save_game));
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::Game);
+    run_updates(&mut app, 2);
+    assert!(!slot.exists());
+
+    let base = app.world.query::<&Base>().single(&app.world);
+    assert_eq!(base.level, 3);
+    assert_eq!(base.max_parts, 5);
+    assert_eq!(base.parts_required, vec![PartTier::Red, PartTier::Blue]);
+    assert_eq!(base.parts.len(), 1);
+    assert_eq!(count::<crate::player::Player>(&mut app), 1);
+    let splitters : Vec<Health> = app.world.query::<(&Enemy, &Health)>().iter(&app.world)
+        .filter(|(enemy, _)| matches!(enemy.variant, EnemyType::Splitter{split_count : 0, ..}))
+        .map(|(_, health)| *health)
+        .collect();
+    assert_eq!(splitters, vec![Health{current : 40, max : 100}]);
+    let turrets : Vec<(TurretStats, TargetingMode)> = app.world.query::<&Turret>().iter(&app.world).map(|turret| (turret.stats, turret.targeting)).collect();
+    assert_eq!(turrets, vec![(turret_stats, TargetingMode::Strongest)]);
+    assert_eq!(app.world.resource::<WaveTimer>().wave, 4);
+    assert_eq!(app.world.resource::<RunStats>().kills, 7);
+    let walls : Vec<(PlacedBuilding, Health)> = app.world.query::<(&PlacedBuilding, &Health)>().iter(&app.world).map(|(placed_building, health)| (*placed_building, *health)).collect();
+    assert_eq!(walls, vec![(PlacedBuilding{kind : BuildingKind::Wall}, Health{current : 120, max : 400})]);
+    assert_eq!(*app.world.resource::<PartInventory>(), PartInventory{blue : 2, red : 1, green : 0});
+    assert_eq!(app.world.query::<&Weapon>().single(&app.world).selected, WeaponKind::SpreadShot);
+    assert!(app.world.get_resource::<PendingSave>().is_none());
+}
+
+#[test]
+fn a_continued_run_picks_up_where_it_stopped(){
+    let mut app = game_app();
+    let slot = temporary_slot("continue");
+    app.insert_resource(SaveGameSlot{path : slot.path.clone()});
+    app.world.resource_mut::<RunStats>().parts = 9;
+    *app.world.resource_mut::<WaveStats>() = WaveStats{wave : 3, kills_at_start : 2, parts_at_start : 4, base_damage : 60};
+    app.world.resource_mut::<TrickleBudget>().points = 2.5;
+    app.world.resource_mut::<LootPity>().misses = vec![3, 1];
+    {
+        let mut game_rng = app.world.resource_mut::<GameRng>();
+        for _ in 0..1000{
+            game_rng.waves.next_u64();
+            game_rng.loot.next_u64();
+        }
+    }
+    {
+        let mut sprite = app.world.query_filtered::<&mut Sprite, With<Player>>().single_mut(&mut app.world);
+        sprite.color = Color::rgba(0.2, 0.4, 0.6, 0.8);
+        sprite.custom_size = Some(Vec2::new(30.0, 20.0));
+    }
+
+    app.world.send_event(SaveGameRequest);
+    run_updates(&mut app, 2);
+    let save_game = slot.read().unwrap();
+    app.insert_resource(PendingSave(save_game.clone()));
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::Game);
+    run_updates(&mut app, 2);
+
+    assert_eq!(app.world.resource::<RunStats>().parts, 9);
+    assert_eq!(*app.world.resource::<WaveStats>(), WaveStats{wave : 3, kills_at_start : 2, parts_at_start : 4, base_damage : 60});
+    assert_eq!(app.world.resource::<TrickleBudget>().points, 2.5);
+    assert_eq!(app.world.resource::<LootPity>().misses, vec![3, 1]);
+    //starting the streams over from the seed would put them back near the start
+    let positions = app.world.resource::<GameRng>().positions();
+    assert!(positions[0] >= save_game.rng_positions[0] && positions[2] >= save_game.rng_positions[2]);
+    assert!(save_game.rng_positions[0] >= 2000);
+    let sprite = app.world.query_filtered::<&Sprite, With<Player>>().single(&app.world);
+    assert_eq!(sprite.color, Color::rgba(0.2, 0.4, 0.6, 0.8));
+    assert_eq!(sprite.custom_size, Some(Vec2::new(30.0, 20.0)));
+}
+
+#[test]
+fn nothing_is_written_without_a_save_path(){
+    let mut app = game_app();
+    app.world.send_event(SaveGameRequest);
+    run_updates(&mut app, 2);
+    assert_eq!(app_state(&app), AppState::MainMenu);
+    assert!(!app.world.resource::<SaveGameSlot>().exists());
+}
+
+#[test]
+fn a_broken_save_is_left_in_place(){
+    let slot = temporary_slot("broken");
+    std::fs::write(slot.path.as_ref().unwrap(), "(seed : 1, kills :").unwrap();
+    assert!(slot.read().is_none());
+    assert!(slot.exists());
+    slot.remove();
+}
+
+#[test]
+fn a_save_that_cant_be_restored_is_kept(){
+    let mut app = game_app();
+    let slot = temporary_slot("unrestorable");
+    app.insert_resource(SaveGameSlot{path : slot.path.clone()});
+    app.world.send_event(SaveGameRequest);
+    run_updates(&mut app, 2);
+
+    let mut save_game = slot.read().unwrap();
+    save_game.wave_timer.duration = -1.0;
+    app.insert_resource(PendingSave(save_game));
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::Game);
+    run_updates(&mut app, 3);
+    assert_eq!(app_state(&app), AppState::MainMenu);
+    assert!(slot.exists());
+    slot.remove();
+}
@@ -0,0 +1,40 @@
+use bevy::{prelude::*, input::{ButtonState, keyboard::KeyboardInput}};
+
+use crate::settings::*;
+use super::*;
+
+
+fn temporary_path(name : &str) -> std::path::PathBuf{
+    std::env::temp_dir().join(format!("cellwar-{}-{}.ron", name, std::process::id()))
+}
+
+
+#[test]
+fn saved_settings_are_loaded_back(){
+    let path = temporary_path("settings");
+    let mut settings = Settings{path : Some(path.clone()), ..default()};
+    settings.toggle(Setting::WaveSummary);
+    settings.toggle(Setting::ScreenShake);
+    settings.save();
+
+    let loaded = Settings::load(path.clone());
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(loaded, settings);
+    assert!(!loaded.get(Setting::WaveSummary) && !loaded.get(Setting::ScreenShake));
+    assert!(loaded.get(Setting::EnemyHealthBars) && loaded.get(Setting::Knockback));
+}
+
+#[test]
+fn the_health_bar_key_is_saved_with_the_other_settings(){
+    let mut app = game_app();
+    let path = temporary_path("settings-key");
+    app.world.resource_mut::<Settings>().path = Some(path.clone());
+
+    app.world.send_event(KeyboardInput{scan_code : 0, key_code : Some(KeyCode::H), state : ButtonState::Pressed});
+    app.update();
+    assert!(!app.world.resource::<Settings>().enemy_health_bars);
+
+    let loaded = Settings::load(path.clone());
+    let _ = std::fs::remove_file(&path);
+    assert!(!loaded.enemy_health_bars && loaded.wave_summary);
+}
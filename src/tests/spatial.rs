@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use crate::enemy::*;
+use crate::spatial::*;
+use super::*;
+
+
+fn entity(index : u32) -> Entity{
+    Entity::from_raw(index)
+}
+
+
+#[test]
+fn candidates_include_entries_overlapping_from_a_neighbouring_cell(){
+    let mut grid = SpatialGrid::new(64.0);
+    //centered just across the cell border but reaching into the searched area
+    grid.insert(entity(0), Vec3::new(66.0, 10.0, 0.0), Vec2::new(10.0, 10.0));
+    grid.insert(entity(1), Vec3::new(500.0, 500.0, 0.0), Vec2::new(10.0, 10.0));
+
+    let found : Vec<Entity> = grid.candidates(Vec3::new(60.0, 10.0, 0.0), Vec2::new(2.0, 2.0)).map(|entry| entry.entity).collect();
+    assert_eq!(found, vec![entity(0)]);
+}
+
+#[test]
+fn nearest_only_looks_within_the_radius(){
+    let mut grid = SpatialGrid::new(64.0);
+    grid.insert(entity(0), Vec3::new(100.0, 0.0, 0.0), Vec2::new(10.0, 10.0));
+    grid.insert(entity(1), Vec3::new(-40.0, 0.0, 0.0), Vec2::new(10.0, 10.0));
+    grid.insert(entity(2), Vec3::new(0.0, 300.0, 0.0), Vec2::new(10.0, 10.0));
+
+    assert_eq!(grid.nearest(Vec3::ZERO, 150.0).map(|entry| entry.entity), Some(entity(1)));
+    assert_eq!(grid.nearest(Vec3::ZERO, 20.0), None);
+}
+
+#[test]
+fn clearing_empties_the_grid(){
+    let mut grid = SpatialGrid::new(64.0);
+    grid.insert(entity(0), Vec3::ZERO, Vec2::new(10.0, 10.0));
+    grid.clear();
+    assert_eq!(grid.candidates(Vec3::ZERO, Vec2::splat(100.0)).count(), 0);
+}
+
+#[test]
+fn the_enemy_grid_follows_the_enemies_every_frame(){
+    let mut app = game_app();
+    let enemy_entity = app.world.spawn((
+        Transform::from_translation(Vec3::new(400.0, 300.0, 0.0)),
+        Enemy{variant : EnemyType::Pawn, speed : 0.0, size : Vec2::new(10.0, 10.0)}
+    )).id();
+    app.update();
+    let found : Vec<Entity> = app.world.resource::<EnemyGrid>().within(Vec3::new(400.0, 300.0, 0.0), 5.0).map(|entry| entry.entity).collect();
+    assert_eq!(found, vec![enemy_entity]);
+
+    app.world.despawn(enemy_entity);
+    app.update();
+    assert_eq!(app.world.resource::<EnemyGrid>().within(Vec3::new(400.0, 300.0, 0.0), 5.0).count(), 0);
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use crate::enemy::*;
+use crate::health::*;
+use crate::status::*;
+use super::*;
+
+
+//standing far from everything with plenty of health
+fn spawn_pawn(app : &mut App, effects : Vec<StatusEffect>) -> Entity{
+    let (_, _, pawn) = EnemyKind::Pawn.template(0.0);
+    app.world.spawn((SpriteBundle::default(), pawn, Health::new(1000), StatusEffects{effects, ..default()})).id()
+}
+
+fn damage_taken(app : &App, enemy : Entity) -> i64{
+    1000 - app.world.get::<Health>(enemy).unwrap().current
+}
+
+
+#[test]
+fn effects_stack_by_their_own_rules(){
+    let mut status_effects = StatusEffects::default();
+    status_effects.apply(StatusEffect::new(StatusKind::Slow, 0.5, 1.0));
+    status_effects.apply(StatusEffect::new(StatusKind::Slow, 0.2, 3.0));
+    assert_eq!(status_effects.effects, vec![StatusEffect::new(StatusKind::Slow, 0.5, 3.0)]);
+
+    for duration in 1..=5{
+        status_effects.apply(StatusEffect::new(StatusKind::Burn, 10.0, duration as f32));
+    }
+    let burns : Vec<f32> = status_effects.effects.iter().filter(|effect| effect.kind == StatusKind::Burn).map(|effect| effect.duration).collect();
+    assert_eq!(burns, vec![4.0, 5.0, 3.0]);
+
+    status_effects.apply(StatusEffect::new(StatusKind::Shield, 30.0, 5.0));
+    status_effects.apply(StatusEffect::new(StatusKind::Shield, 30.0, 5.0));
+    assert_eq!(status_effects.absorb(80), 80 - MAX_SHIELD as i64);
+    assert!(!status_effects.has(StatusKind::Shield));
+}
+
+#[test]
+fn slows_haste_and_stuns_change_the_speed(){
+    let mut status_effects = StatusEffects::default();
+    status_effects.apply(StatusEffect::new(StatusKind::Slow, 0.5, 1.0));
+    status_effects.apply(StatusEffect::new(StatusKind::Haste, 0.2, 1.0));
+    assert!((status_effects.speed_multiplier() - 0.6).abs() < 1e-5);
+    status_effects.apply(StatusEffect::new(StatusKind::Stun, 0.0, 1.0));
+    assert_eq!(status_effects.speed_multiplier(), 0.0);
+
+    //everything wears off
+    assert_eq!(status_effects.tick(1.0), 0);
+    assert_eq!(status_effects.speed_multiplier(), 1.0);
+}
+
+#[test]
+fn shields_soak_up_damage_first(){
+    let mut app = game_app();
+    let pawn = spawn_pawn(&mut app, vec![StatusEffect::new(StatusKind::Shield, 30.0, 10.0)]);
+    app.world.send_event(DamageEvent{source : None, target : pawn, amount : 50, damage_type : DamageType::Bullet});
+    app.update();
+    assert_eq!(damage_taken(&app, pawn), 20);
+}
+
+#[test]
+fn burns_deal_damage_over_time(){
+    let mut app = game_app();
+    let pawn = spawn_pawn(&mut app, vec![StatusEffect::new(StatusKind::Burn, 20.0, 1.0)]);
+    run_updates(&mut app, 90);
+    assert_eq!(damage_taken(&app, pawn), 20);
+    assert!(app.world.get::<StatusEffects>(pawn).unwrap().effects.is_empty());
+}
+
+#[test]
+fn stunned_enemies_stand_still(){
+    let mut app = game_app();
+    let pawn = spawn_pawn(&mut app, vec![StatusEffect::new(StatusKind::Stun, 0.0, 1.0)]);
+    run_updates(&mut app, 30);
+    assert_eq!(app.world.get::<Transform>(pawn).unwrap().translation, Vec3::ZERO);
+    run_updates(&mut app, 60);
+    assert_ne!(app.world.get::<Transform>(pawn).unwrap().translation, Vec3::ZERO);
+}
+
+#[test]
+fn effects_tint_the_enemy(){
+    let mut app = game_app();
+    let pawn = spawn_pawn(&mut app, Vec::new());
+    app.world.send_event(StatusEvent{target : pawn, effect : StatusEffect::new(StatusKind::Slow, 0.3, 0.5)});
+    run_updates(&mut app, 2);
+    assert_eq!(app.world.get::<Sprite>(pawn).unwrap().color, SLOW_COLOR);
+    run_updates(&mut app, 40);
+    assert_eq!(app.world.get::<Sprite>(pawn).unwrap().color, Color::WHITE);
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::base::{Base, BaseBuilding};
+use crate::building::*;
+use crate::enemy::*;
+use crate::health::Health;
+use crate::steering::*;
+use super::*;
+
+
+fn base_translation(app : &mut App) -> Vec3{
+    app.world.query_filtered::<&Transform, With<Base>>().single(&app.world).translation
+}
+
+fn spawn_pawn(app : &mut App, translation : Vec3) -> Entity{
+    let (_, _, pawn) = EnemyKind::Pawn.template(0.0);
+    app.world.spawn((Transform::from_translation(translation), pawn, Health::new(1000))).id()
+}
+
+fn translation_of(app : &App, entity : Entity) -> Vec3{
+    app.world.get::<Transform>(entity).unwrap().translation
+}
+
+
+#[test]
+fn only_the_swarming_enemies_flock(){
+    let pawn = SteeringProfile::for_enemy(&EnemyType::Pawn).unwrap();
+    assert!(pawn.alignment > 0.0 && pawn.cohesion > 0.0 && !pawn.avoids_base);
+    let rogue = SteeringProfile::for_enemy(&EnemyType::Rogue).unwrap();
+    assert_eq!((rogue.alignment, rogue.cohesion), (0.0, 0.0));
+    assert!(rogue.avoids_base);
+}
+
+#[test]
+fn stacked_pawns_spread_out(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(-400.0, 0.0, 0.0);
+    let first = spawn_pawn(&mut app, translation);
+    let second = spawn_pawn(&mut app, translation);
+    run_updates(&mut app, 120);
+    assert!(translation_of(&app, first).distance(translation_of(&app, second)) > 10.0);
+}
+
+#[test]
+fn enemies_steer_around_base_buildings(){
+    let mut app = game_app();
+    let building = base_translation(&mut app) + Vec3::new(-200.0, 0.0, 0.0);
+    app.world.spawn((Transform::from_translation(building), BaseBuilding{}));
+    let pawn = spawn_pawn(&mut app, building + Vec3::new(0.0, 20.0, 0.0));
+    run_updates(&mut app, 3);
+    assert!(app.world.get::<Steering>(pawn).unwrap().force.y > 0.0);
+}
+
+#[test]
+fn enemies_steer_around_placed_walls(){
+    let mut app = game_app();
+    let wall = base_translation(&mut app) + Vec3::new(-200.0, 0.0, 0.0);
+    app.world.spawn((Transform::from_translation(wall), PlacedBuilding{kind : BuildingKind::Wall}));
+    //outside the radius of a base building but inside the wall's
+    let pawn = spawn_pawn(&mut app, wall + Vec3::new(0.0, 46.0, 0.0));
+    run_updates(&mut app, 3);
+    assert!(app.world.get::<Steering>(pawn).unwrap().force.y > 0.0);
+}
+
+#[test]
+fn the_wander_only_depends_on_the_time(){
+    assert_eq!(wander(1.0, 3.5), wander(1.0, 3.5));
+    assert!((wander(1.0, 3.5).length() - 1.0).abs() < 1e-5);
+    assert_ne!(wander(1.0, 3.5), wander(2.0, 3.5));
+}
@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+
+use crate::base::Base;
+use crate::enemy::*;
+use crate::health::Health;
+use crate::turret::*;
+use super::*;
+
+
+fn base_translation(app : &mut App) -> Vec3{
+    app.world.query_filtered::<&Transform, With<Base>>().single(&app.world).translation
+}
+
+fn spawn_turret(app : &mut App, translation : Vec3, stats : TurretStats, targeting : TargetingMode) -> Entity{
+    app.world.spawn((Transform::from_translation(translation), Turret::new(stats, targeting))).id()
+}
+
+//standing still so only the turret decides what happens
+fn spawn_pawn(app : &mut App, translation : Vec3, health : i64) -> Entity{
+    let (_, _, mut enemy) = EnemyKind::Pawn.template(0.0);
+    enemy.speed = 0.0;
+    app.world.spawn((Transform::from_translation(translation), enemy, Health::new(health))).id()
+}
+
+fn target(app : &App, turret : Entity) -> Option<Entity>{
+    app.world.get::<Turret>(turret).unwrap().target
+}
+
+
+#[test]
+fn each_mode_picks_its_own_target(){
+    let mut app = game_app();
+    let base_translation = base_translation(&mut app);
+    let turret_translation = base_translation + Vec3::new(0.0, 150.0, 0.0);
+    let near_the_turret = spawn_pawn(&mut app, turret_translation + Vec3::new(30.0, 0.0, 0.0), 50);
+    let strong = spawn_pawn(&mut app, turret_translation + Vec3::new(-120.0, 0.0, 0.0), 400);
+    let near_the_base = spawn_pawn(&mut app, turret_translation + Vec3::new(0.0, -140.0, 0.0), 50);
+
+    let nearest = spawn_turret(&mut app, turret_translation, TurretStats::default(), TargetingMode::Nearest);
+    let strongest = spawn_turret(&mut app, turret_translation, TurretStats::default(), TargetingMode::Strongest);
+    let closest_to_base = spawn_turret(&mut app, turret_translation, TurretStats::default(), TargetingMode::ClosestToBase);
+    app.update();
+
+    assert_eq!(target(&app, nearest), Some(near_the_turret));
+    assert_eq!(target(&app, strongest), Some(strong));
+    assert_eq!(target(&app, closest_to_base), Some(near_the_base));
+}
+
+#[test]
+fn first_in_range_keeps_its_target_until_it_despawns(){
+    let mut app = game_app();
+    let turret_translation = base_translation(&mut app) + Vec3::new(0.0, 150.0, 0.0);
+    let first = spawn_pawn(&mut app, turret_translation + Vec3::new(100.0, 0.0, 0.0), 50);
+    let turret = spawn_turret(&mut app, turret_translation, TurretStats::default(), TargetingMode::FirstInRange);
+    app.update();
+    assert_eq!(target(&app, turret), Some(first));
+
+    //a closer enemy doesn't pull it away
+    let closer = spawn_pawn(&mut app, turret_translation + Vec3::new(20.0, 0.0, 0.0), 50);
+    app.update();
+    assert_eq!(target(&app, turret), Some(first));
+
+    app.world.despawn(first);
+    app.update();
+    assert_eq!(target(&app, turret), Some(closer));
+}
+
+#[test]
+fn targets_that_leave_the_range_are_dropped(){
+    let mut app = game_app();
+    let turret_translation = base_translation(&mut app) + Vec3::new(0.0, 150.0, 0.0);
+    let pawn = spawn_pawn(&mut app, turret_translation + Vec3::new(100.0, 0.0, 0.0), 50);
+    let turret = spawn_turret(&mut app, turret_translation, TurretStats::default(), TargetingMode::FirstInRange);
+    app.update();
+    assert_eq!(target(&app, turret), Some(pawn));
+
+    app.world.get_mut::<Transform>(pawn).unwrap().translation = turret_translation + Vec3::new(TURRET_RADAR + 50.0, 0.0, 0.0);
+    app.update();
+    assert_eq!(target(&app, turret), None);
+}
+
+#[test]
+fn turrets_fire_on_their_own_cooldowns(){
+    let mut app = game_app();
+    let base_translation = base_translation(&mut app);
+    //far enough apart that each turret only sees its own enemy
+    let fast_target = spawn_pawn(&mut app, base_translation + Vec3::new(-300.0, 50.0, 0.0), 1000);
+    let slow_target = spawn_pawn(&mut app, base_translation + Vec3::new(300.0, 50.0, 0.0), 1000);
+    spawn_turret(&mut app, base_translation + Vec3::new(-300.0, 0.0, 0.0), TurretStats{fire_rate : 10.0, ..default()}, TargetingMode::Nearest);
+    spawn_turret(&mut app, base_translation + Vec3::new(300.0, 0.0, 0.0), TurretStats{fire_rate : 1.0, ..default()}, TargetingMode::Nearest);
+
+    //a second and a half, the slow one has fired once and the fast one over ten times
+    run_updates(&mut app, 90);
+    let damage_taken = |app : &App, enemy : Entity| 1000 - app.world.get::<Health>(enemy).unwrap().current;
+    assert_eq!(damage_taken(&app, slow_target), TURRET_DAMAGE);
+    assert!(damage_taken(&app, fast_target) >= 10 * TURRET_DAMAGE);
+}
+
+fn spawn_missile(app : &mut App, translation : Vec3, target : Option<Entity>) -> Entity{
+    app.world.spawn((Transform::from_translation(translation), TurretMissile::new(target, Vec2::X, 100, 0.0))).id()
+}
+
+#[test]
+fn missiles_turn_no_faster_than_their_turn_rate(){
+    let missile = TurretMissile::new(None, Vec2::X, 100, 0.0);
+    let turned = missile.steer(Vec2::NEG_X + Vec2::new(0.0, 0.01), 0.1);
+    assert!((Vec2::X.angle_between(turned).abs() - MISSILE_TURN_RATE * 0.1).abs() < 0.001);
+    //close enough to turn all the way
+    let turned = missile.steer(Vec2::new(1.0, 0.1), 0.1);
+    assert!(turned.angle_between(Vec2::new(1.0, 0.1)).abs() < 0.001);
+}
+
+#[test]
+fn missiles_retarget_when_their_target_dies(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(0.0, 200.0, 0.0);
+    let first = spawn_pawn(&mut app, translation + Vec3::new(150.0, 0.0, 0.0), 1000);
+    let second = spawn_pawn(&mut app, translation + Vec3::new(-150.0, 0.0, 0.0), 1000);
+    let missile = spawn_missile(&mut app, translation, Some(first));
+    app.world.despawn(first);
+    app.update();
+
+    assert_eq!(app.world.get::<TurretMissile>(missile).unwrap().target, Some(second));
+}
+
+#[test]
+fn missiles_explode_on_everything_around_the_impact(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(0.0, 200.0, 0.0);
+    let hit = spawn_pawn(&mut app, translation, 1000);
+    let nearby = spawn_pawn(&mut app, translation + Vec3::new(0.0, MISSILE_SPLASH_RADIUS - 10.0, 0.0), 1000);
+    let far_away = spawn_pawn(&mut app, translation + Vec3::new(0.0, -(MISSILE_SPLASH_RADIUS + 40.0), 0.0), 1000);
+    spawn_missile(&mut app, translation, Some(hit));
+    app.update();
+
+    let health = |app : &App, enemy : Entity| app.world.get::<Health>(enemy).unwrap().current;
+    assert_eq!(health(&app, hit), 900);
+    assert_eq!(health(&app, nearby), 900);
+    assert_eq!(health(&app, far_away), 1000);
+    assert_eq!(count::<TurretMissile>(&mut app), 0);
+}
+
+#[test]
+fn missiles_without_anything_to_chase_self_destruct(){
+    let mut app = game_app();
+    let translation = base_translation(&mut app) + Vec3::new(0.0, 200.0, 0.0);
+    spawn_missile(&mut app, translation, None);
+    app.update();
+
+    assert_eq!(count::<TurretMissile>(&mut app), 0);
+}
+
+#[test]
+fn level_seven_unlocks_the_missile_launcher(){
+    let mut app = game_app();
+    {
+        let mut base = app.world.query::<&mut Base>().single_mut(&mut app.world);
+        base.level = 7;
+        base.leveled_up = true;
+    }
+    app.update();
+
+    let weapons : Vec<TurretWeapon> = app.world.query::<&Turret>().iter(&app.world).map(|turret| turret.weapon).collect();
+    assert_eq!(weapons, vec![TurretWeapon::Missiles]);
+}
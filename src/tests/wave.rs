@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use crate::wave::*;
+use crate::wave_script::*;
+use crate::enemy::{Enemy, EnemyKind, EnemySpawnCooldownTimer};
+use crate::base::Base;
+use crate::health::{DamageEvent, DamageType, Health};
+use super::*;
+
+
+fn start_next_wave(app : &mut App) -> Vec<WaveStarted>{
+    {
+        let mut wave_timer = app.world.resource_mut::<WaveTimer>();
+        let duration = wave_timer.timer.duration();
+        wave_timer.timer.set_elapsed(duration - Duration::from_millis(1));
+    }
+    run_updates(app, 2);
+    let events = app.world.resource::<Events<WaveStarted>>();
+    events.get_reader().iter(events).cloned().collect()
+}
+
+//the trickle timer goes off on the next update
+fn trickle_next_update(app : &mut App){
+    let mut enemy_spawn_timer = app.world.resource_mut::<EnemySpawnCooldownTimer>();
+    let duration = enemy_spawn_timer.timer.duration();
+    enemy_spawn_timer.timer.set_elapsed(duration - Duration::from_millis(1));
+}
+
+
+#[test]
+fn probabilities_sum_to_one_and_favour_the_first_variant(){
+    for elements in 1..=4{
+        let probabilities = calculate_probabilities(elements, 1.0);
+        assert_eq!(probabilities.len(), elements);
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(probabilities.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+}
+
+#[test]
+fn probabilities_are_capped_at_four_variants(){
+    assert_eq!(calculate_probabilities(6, 1.0), calculate_probabilities(4, 1.0));
+    assert!(calculate_probabilities(0, 1.0).is_empty());
+}
+
+#[test]
+fn the_intensity_tilts_the_probabilities(){
+    assert_eq!(calculate_probabilities(3, 1.0)[0], 0.5);
+    let hard = calculate_probabilities(3, 2.0);
+    let easy = calculate_probabilities(3, 0.5);
+    assert!(hard[0] < 0.5 && easy[0] > 0.5);
+    assert!(hard.windows(2).all(|pair| pair[0] > pair[1]));
+    assert!((hard.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn repeated_definitions_cover_consecutive_waves(){
+    let wave_script : WaveScript = ron::from_str("(waves : [(enemies : [(kind : Pawn, count : 3)], repeat : 2), (enemies : [(kind : Rogue, count : 1)], delay : 5.0)])").unwrap();
+
+    assert_eq!(wave_script.wave(1).unwrap().enemies[0].kind, EnemyKind::Pawn);
+    assert_eq!(wave_script.wave(2).unwrap().enemies[0].kind, EnemyKind::Pawn);
+    assert_eq!(wave_script.wave(3).unwrap().enemies[0].kind, EnemyKind::Rogue);
+    assert_eq!(wave_script.wave(3).unwrap().delay, 5.0);
+    assert_eq!(wave_script.wave(1).unwrap().delay, WAVE_COOLDOWN);
+    assert!(wave_script.wave(0).is_none());
+    assert!(wave_script.wave(4).is_none());
+}
+
+#[test]
+fn negative_or_nan_delays_are_rejected(){
+    let wave_script : WaveScript = ron::from_str("(waves : [(enemies : [], delay : 2.0), (enemies : [], delay : -1.0)])").unwrap();
+    assert!(wave_script.validate().is_err());
+    let wave_script : WaveScript = ron::from_str("(waves : [(enemies : [], delay : NaN)])").unwrap();
+    assert!(wave_script.validate().is_err());
+    let wave_script : WaveScript = ron::from_str("(waves : [(enemies : [], delay : 0.0)])").unwrap();
+    assert!(wave_script.validate().is_ok());
+}
+
+#[test]
+fn default_wave_script_parses(){
+    let wave_script : WaveScript = ron::de::from_bytes(include_bytes!("../../assets/Data/default.waves.ron")).unwrap();
+    assert!(wave_script.wave(1).is_some());
+    assert!(wave_script.validate().is_ok());
+}
+
+#[test]
+fn nothing_trickles_in_before_the_first_wave(){
+    let mut app = game_app();
+    trickle_next_update(&mut app);
+    run_updates(&mut app, 2);
+    assert_eq!(count::<Enemy>(&mut app), 0);
+}
+
+#[test]
+fn stragglers_spend_the_budget_of_the_wave(){
+    let mut app = game_app();
+    {
+        let mut wave_timer = app.world.resource_mut::<WaveTimer>();
+        wave_timer.wave = 10;
+        wave_timer.variants = vec![EnemyKind::Pawn];
+    }
+    trickle_next_update(&mut app);
+    run_updates(&mut app, 2);
+    assert_eq!(count::<Enemy>(&mut app), (trickle_budget(10) / EnemyKind::Pawn.cost()) as usize);
+    assert_eq!(app.world.resource::<TrickleBudget>().points, 0.0);
+    assert_eq!(app.world.resource::<EnemySpawnCooldownTimer>().timer.duration().as_secs_f32(), trickle_interval(10));
+}
+
+#[test]
+fn expensive_stragglers_are_saved_up_for(){
+    let mut app = game_app();
+    {
+        let mut wave_timer = app.world.resource_mut::<WaveTimer>();
+        wave_timer.wave = 2;
+        wave_timer.variants = vec![EnemyKind::Propagator];
+    }
+    trickle_next_update(&mut app);
+    run_updates(&mut app, 2);
+    assert_eq!(count::<Enemy>(&mut app), 0);
+    assert_eq!(app.world.resource::<TrickleBudget>().points, trickle_budget(2));
+    assert!(trickle_interval(100) >= MIN_TRICKLE_INTERVAL && trickle_interval(10) < trickle_interval(1));
+}
+
+#[test]
+fn waves_announce_the_enemies_they_bring_first(){
+    let mut app = game_app();
+    let first = start_next_wave(&mut app);
+    assert_eq!(first, vec![WaveStarted{wave : 1, new_kinds : vec![EnemyKind::Pawn], boss : false}]);
+
+    //the first rogues come with wave 15
+    {
+        let mut wave_timer = app.world.resource_mut::<WaveTimer>();
+        wave_timer.wave = 14;
+        wave_timer.unlocked = vec![EnemyKind::Pawn, EnemyKind::Stinger];
+    }
+    let fifteenth = start_next_wave(&mut app);
+    assert_eq!(fifteenth.last().unwrap().new_kinds, vec![EnemyKind::Rogue]);
+    assert_eq!(app.world.resource::<WaveStats>().wave, 15);
+}
+
+#[test]
+fn wave_stats_add_up_the_damage_to_the_base(){
+    let mut app = game_app();
+    let base = app.world.query_filtered::<Entity, With<Base>>().single(&app.world);
+    app.world.send_event(DamageEvent{source : None, target : base, amount : 25, damage_type : DamageType::Collision});
+    app.world.send_event(DamageEvent{source : None, target : base, amount : 10, damage_type : DamageType::Bullet});
+    app.update();
+    assert_eq!(app.world.resource::<WaveStats>().base_damage, 35);
+}
+
+#[test]
+fn wave_stats_only_count_the_health_the_base_lost(){
+    let mut app = game_app();
+    let base = app.world.query_filtered::<Entity, With<Base>>().single(&app.world);
+    //repairs and level ups aren't negative damage
+    app.world.get_mut::<Health>(base).unwrap().current = 400;
+    app.update();
+    app.world.get_mut::<Health>(base).unwrap().reset(1000);
+    app.update();
+    assert_eq!(app.world.resource::<WaveStats>().base_damage, 100);
+    //the hit that destroys the base only takes what was left
+    app.world.send_event(DamageEvent{source : None, target : base, amount : 5000, damage_type : DamageType::Collision});
+    app.update();
+    assert_eq!(app.world.resource::<WaveStats>().base_damage, 1100);
+}
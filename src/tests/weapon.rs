@@ -0,0 +1,181 @@
+use bevy::{prelude::*, input::{ButtonState, keyboard::KeyboardInput}};
+
+use crate::base::Base;
+use crate::bullet::*;
+use crate::enemy::*;
+use crate::health::Health;
+use crate::player::{Player, PlayerInput};
+use crate::weapon::*;
+use super::*;
+
+
+fn press_key(app : &mut App, key_code : KeyCode){
+    app.world.send_event(KeyboardInput{scan_code : 0, key_code : Some(key_code), state : ButtonState::Pressed});
+    app.update();
+}
+
+fn set_base_level(app : &mut App, level : i64){
+    app.world.query::<&mut Base>().single_mut(&mut app.world).level = level;
+}
+
+fn player_translation(app : &mut App) -> Vec3{
+    app.world.query_filtered::<&Transform, With<Player>>().single(&app.world).translation
+}
+
+fn weapon(app : &mut App) -> Weapon{
+    app.world.query_filtered::<&Weapon, With<Player>>().single(&app.world).clone()
+}
+
+fn select(app : &mut App, kind : WeaponKind){
+    app.world.query_filtered::<&mut Weapon, With<Player>>().single_mut(&mut app.world).select(kind);
+}
+
+fn aim(app : &mut App, target : Vec3, fire : bool){
+    let mut player_input = app.world.resource_mut::<PlayerInput>();
+    player_input.aim = Some(target.truncate());
+    player_input.fire = fire;
+}
+
+//standing still with plenty of health so only the damage taken changes
+fn spawn_pawn(app : &mut App, translation : Vec3) -> Entity{
+    let (_, _, mut enemy) = EnemyKind::Pawn.template(0.0);
+    enemy.speed = 0.0;
+    app.world.spawn((Transform::from_translation(translation), enemy, Health::new(1000))).id()
+}
+
+fn damage_taken(app : &App, enemy : Entity) -> i64{
+    1000 - app.world.get::<Health>(enemy).unwrap().current
+}
+
+fn projectile(kind : WeaponKind, base_level : i64) -> ProjectileStats{
+    player_projectile(kind, base_level, &ProjectileUpgrades::default())
+}
+
+
+#[test]
+fn the_blaster_scales_with_the_base_level(){
+    let level_1 = projectile(WeaponKind::Blaster, 1);
+    assert_eq!((level_1.damage, level_1.speed, WeaponKind::Blaster.stats(1).cooldown), (50, 300.0, 0.5));
+    let level_3 = projectile(WeaponKind::Blaster, 3);
+    assert_eq!((level_3.damage, level_3.speed), (70, 400.0));
+    let level_9 = projectile(WeaponKind::Blaster, 9);
+    assert_eq!((level_9.damage, level_9.speed, WeaponKind::Blaster.stats(9).cooldown), (100, 600.0, 0.1));
+}
+
+#[test]
+fn locked_weapons_cannot_be_selected(){
+    let mut app = game_app();
+    press_key(&mut app, KeyCode::Key6);
+    assert_eq!(weapon(&mut app).selected, WeaponKind::Blaster);
+
+    //cycling skips everything the base hasn't unlocked
+    set_base_level(&mut app, 2);
+    press_key(&mut app, KeyCode::E);
+    assert_eq!(weapon(&mut app).selected, WeaponKind::SpreadShot);
+    press_key(&mut app, KeyCode::Q);
+    assert_eq!(weapon(&mut app).selected, WeaponKind::Blaster);
+
+    set_base_level(&mut app, 6);
+    press_key(&mut app, KeyCode::Key5);
+    assert_eq!(weapon(&mut app).selected, WeaponKind::Laser);
+}
+
+#[test]
+fn spread_shot_fans_out_five_bullets(){
+    let mut app = game_app();
+    set_base_level(&mut app, 2);
+    select(&mut app, WeaponKind::SpreadShot);
+    let target = player_translation(&mut app) + Vec3::new(0.0, -200.0, 0.0);
+    aim(&mut app, target, true);
+    app.update();
+    app.update();
+
+    let directions : Vec<Vec2> = app.world.query::<&Bullet>().iter(&app.world).map(|bullet| bullet.direction).collect();
+    assert_eq!(directions.len(), 5);
+    assert!(directions.iter().any(|direction| direction.abs_diff_eq(Vec2::NEG_Y, 0.001)));
+    assert!(directions.iter().any(|direction| direction.x > 0.3) && directions.iter().any(|direction| direction.x < -0.3));
+}
+
+#[test]
+fn each_weapon_keeps_its_own_cooldown(){
+    let mut app = game_app();
+    set_base_level(&mut app, 2);
+    let target = player_translation(&mut app) + Vec3::new(0.0, -200.0, 0.0);
+    aim(&mut app, target, true);
+    app.update();
+    assert!(!weapon(&mut app).ready(WeaponKind::Blaster));
+
+    //switching doesn't wait for the blaster
+    select(&mut app, WeaponKind::SpreadShot);
+    app.update();
+    let weapon = weapon(&mut app);
+    assert!(!weapon.ready(WeaponKind::SpreadShot));
+    assert!(!weapon.ready(WeaponKind::Blaster));
+}
+
+#[test]
+fn the_laser_hits_everything_along_the_beam(){
+    let mut app = game_app();
+    set_base_level(&mut app, 5);
+    select(&mut app, WeaponKind::Laser);
+    let translation = player_translation(&mut app);
+    let near = spawn_pawn(&mut app, translation + Vec3::new(100.0, 0.0, 0.0));
+    let far = spawn_pawn(&mut app, translation + Vec3::new(300.0, 0.0, 0.0));
+    let beside = spawn_pawn(&mut app, translation + Vec3::new(100.0, 80.0, 0.0));
+    aim(&mut app, translation + Vec3::new(200.0, 0.0, 0.0), true);
+    app.update();
+    aim(&mut app, translation + Vec3::new(200.0, 0.0, 0.0), false);
+    run_updates(&mut app, 2);
+
+    let damage = projectile(WeaponKind::Laser, 5).damage;
+    assert_eq!(damage_taken(&app, near), damage);
+    assert_eq!(damage_taken(&app, far), damage);
+    assert_eq!(damage_taken(&app, beside), 0);
+}
+
+#[test]
+fn a_held_charge_shot_hits_harder(){
+    let mut app = game_app();
+    set_base_level(&mut app, 4);
+    select(&mut app, WeaponKind::ChargeShot);
+    let target = player_translation(&mut app) + Vec3::new(0.0, -200.0, 0.0);
+    aim(&mut app, target, true);
+    run_updates(&mut app, 60);
+    assert_eq!(count::<Bullet>(&mut app), 0);
+
+    aim(&mut app, target, false);
+    run_updates(&mut app, 2);
+    let damage = app.world.query::<&Bullet>().single(&app.world).damage;
+    assert!(damage > projectile(WeaponKind::ChargeShot, 4).damage * 2);
+    assert_eq!(weapon(&mut app).charge, 0.0);
+}
+
+#[test]
+fn bombs_explode_where_they_were_aimed(){
+    let mut app = game_app();
+    set_base_level(&mut app, 6);
+    select(&mut app, WeaponKind::Bomb);
+    let target = player_translation(&mut app) + Vec3::new(250.0, 0.0, 0.0);
+    let center = spawn_pawn(&mut app, target);
+    let edge = spawn_pawn(&mut app, target + Vec3::new(0.0, BOMB_SPLASH_RADIUS - 10.0, 0.0));
+    let outside = spawn_pawn(&mut app, target + Vec3::new(0.0, BOMB_SPLASH_RADIUS + 40.0, 0.0));
+    aim(&mut app, target, true);
+    app.update();
+    aim(&mut app, target, false);
+    run_updates(&mut app, 90);
+
+    //the explosion sets everything it hits burning on top of its damage
+    let damage = projectile(WeaponKind::Bomb, 6).damage;
+    assert!(damage_taken(&app, center) > damage);
+    assert_eq!(damage_taken(&app, edge), damage_taken(&app, center));
+    assert_eq!(damage_taken(&app, outside), 0);
+    assert_eq!(count::<PlayerBomb>(&mut app), 0);
+}
+
+#[test]
+fn every_weapon_looks_and_sounds_like_something(){
+    for kind in WeaponKind::ALL{
+        assert!(shipped_asset(kind.texture()), "{:?} has no sprite", kind);
+        assert!(shipped_asset(kind.sound()), "{:?} has no sound", kind);
+    }
+}
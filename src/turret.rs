@@ -1,101 +1,288 @@
-use std::{ f32::consts::PI, time::Instant};
-use bevy::prelude::*;
+use std::f32::consts::PI;
+use serde::{Deserialize, Serialize};
+use bevy::{prelude::*, sprite::collide_aabb::collide};
 use crate::bullet::*;
 use crate::enemy::*;
-use super::{SimulationState, AppState};
+use crate::base::Base;
+use crate::health::*;
+use crate::spatial::{EnemyGrid, SpatialIndexSet};
+use crate::game_time::SimulationClock;
+use crate::status::*;
+use super::{SimulationState, AppState, SoundEffect};
 
 pub struct TurretPlugin;
 
 impl Plugin for TurretPlugin{
     fn build(&self, app: &mut App) {
         app
-        .init_resource::<TurretCooldownTimer>()
-
         .add_systems(
             (
-               turret_timer_ticker,
-                turret_movement
+                turret_cooldown_ticker,
+                turret_targeting.after(SpatialIndexSet),
+                turret_shooting.after(turret_targeting).after(turret_cooldown_ticker),
+                //steers with this frame's grid, hits in DamageSet like every other projectile
+                turret_missile_steering.after(SpatialIndexSet).before(DamageSet),
+                turret_missile_hit.in_set(DamageSet)
             )
             .in_set(OnUpdate(AppState::Game))
             .in_set(OnUpdate(SimulationState::Running))
         )
-        
-        .add_system(despawn_turret.in_schedule(OnExit(AppState::Game)));
+
+        .add_system(despawn_turret.in_schedule(OnExit(AppState::Game)))
+        .add_system(despawn_turret_missiles.in_schedule(OnExit(AppState::Game)));
     }
 }
 
 
 
 pub const TURRET_RADAR :f32 = 200.0;
-pub const TURRET_COOLDOWN: f32 = 0.5;
+//shots per second
+pub const TURRET_FIRE_RATE : f32 = 2.0;
+pub const TURRET_DAMAGE : i64 = 50;
+pub const TURRET_BULLET_SPEED : f32 = 500.0;
 
+pub const MISSILE_SPEED : f32 = 300.0;
+//radians per second, low enough that a missile has to curve around to hit something behind it
+pub const MISSILE_TURN_RATE : f32 = 4.0;
+pub const MISSILE_SIZE : f32 = 12.0;
+pub const MISSILE_SPLASH_RADIUS : f32 = 60.0;
+//how far a missile looks for a new target when its own dies
+pub const MISSILE_RETARGET_RADIUS : f32 = 250.0;
+pub const MISSILE_LIFETIME : f32 = 4.0;
+pub const TURRET_SLOW : StatusEffect = StatusEffect::new(StatusKind::Slow, 0.25, 1.0);
+pub const MISSILE_BURN : StatusEffect = StatusEffect::new(StatusKind::Burn, 20.0, 2.0);
+//the launcher the base unlocks at level 7, also the one the player can build
+pub const MISSILE_LAUNCHER_STATS : TurretStats = TurretStats{range : 300.0, fire_rate : 0.5, damage : 120};
 
-#[derive(Component)]
+
+//how a turret picks what to shoot at out of the enemies in its range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetingMode{
+    Nearest,
+    //most health left
+    Strongest,
+    ClosestToBase,
+    //sticks with the first enemy it locks onto until that one dies or gets away
+    FirstInRange
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TurretStats{
+    pub range : f32,
+    //shots per second
+    pub fire_rate : f32,
+    pub damage : i64
+}
+
+impl Default for TurretStats{
+    fn default() -> TurretStats {
+        TurretStats{range : TURRET_RADAR, fire_rate : TURRET_FIRE_RATE, damage : TURRET_DAMAGE}
+    }
+}
+
+//what a turret fires, the stats damage is per shot either way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TurretWeapon{
+    #[default]
+    Bullets,
+    //homing missiles that explode on impact
+    Missiles
+}
+
+#[derive(Component, Debug, Clone)]
 pub struct Turret{
-    pub target : Option<Entity>
+    pub stats : TurretStats,
+    pub targeting : TargetingMode,
+    pub weapon : TurretWeapon,
+    pub target : Option<Entity>,
+    pub cooldown : Timer
 }
 
+//bullets fired by turrets, they keep their own damage instead of scaling with the base level
 #[derive(Component)]
+pub struct TurretShot;
 
+//flies toward its target turning at most turn_rate, finds a new one if the target dies
+//and damages every enemy within splash_radius when it hits, runs out or has nothing left to chase
+#[derive(Component, Debug, Clone)]
 pub struct TurretMissile{
-    pub target : Option<Entity>
+    pub target : Option<Entity>,
+    pub direction : Vec2,
+    pub speed : f32,
+    pub turn_rate : f32,
+    pub damage : i64,
+    pub splash_radius : f32,
+    pub spawn_time : f32
+}
+
+
+impl Turret{
+    pub fn new(stats : TurretStats, targeting : TargetingMode) -> Turret{
+        Turret{stats, targeting, weapon : TurretWeapon::Bullets, target : None, cooldown : Timer::from_seconds(1.0 / stats.fire_rate, TimerMode::Once)}
+    }
+
+    //turret bullets don't get the base level bonus
+    pub fn projectile(&self) -> ProjectileStats{
+        ProjectileStats{damage : self.stats.damage, speed : TURRET_BULLET_SPEED, size : 10.0, pierce : 0, bounces : 0, knockback : 0.0, status : Some(TURRET_SLOW)}
+    }
 }
 
-#[derive(Resource)]
-pub struct TurretCooldownTimer{
-    pub timer: Timer,
+impl TurretMissile{
+    pub fn new(target : Option<Entity>, direction : Vec2, damage : i64, spawn_time : f32) -> TurretMissile{
+        TurretMissile{target, direction, speed : MISSILE_SPEED, turn_rate : MISSILE_TURN_RATE, damage, splash_radius : MISSILE_SPLASH_RADIUS, spawn_time}
+    }
+
+    //the new heading after turning toward to_target for delta seconds
+    pub fn steer(&self, to_target : Vec2, delta : f32) -> Vec2{
+        if to_target == Vec2::ZERO{
+            return self.direction;
+        }
+        let max_turn = self.turn_rate * delta;
+        let turn = self.direction.angle_between(to_target).clamp(-max_turn, max_turn);
+        Vec2::from_angle(turn).rotate(self.direction)
+    }
 }
 
-impl Default for TurretCooldownTimer{
-    fn default() -> TurretCooldownTimer {
-        TurretCooldownTimer{timer: Timer::from_seconds(TURRET_COOLDOWN, TimerMode::Repeating)}
+//sprite for a missile leaving translation
+pub fn missile_bundle(asset_server : &AssetServer, translation : Vec3, direction : Vec2) -> SpriteBundle{
+    SpriteBundle{
+        transform : Transform{
+            translation,
+            rotation : Quat::from_rotation_z(direction.y.atan2(direction.x) - PI/2.0),
+            scale : Vec3::splat(0.3)
+        },
+        texture : asset_server.load("Sprites/spaceMissiles_037.png"),
+        ..default()
     }
-    
 }
 
+//the best enemy in range for the targeting mode, None when nothing is in range
+pub fn pick_target(targeting : TargetingMode, translation : Vec3, range : f32, enemy_grid : &EnemyGrid, enemy_query : &Query<(&Transform, &Health), With<Enemy>>, base_translation : Option<Vec3>) -> Option<Entity>{
+    let in_range = enemy_grid.within(translation, range);
+    let target = match (targeting, base_translation) {
+        (TargetingMode::Strongest, _) => in_range
+            .filter_map(|enemy| enemy_query.get(enemy.entity).ok().map(|(_, health)| (enemy, health.current)))
+            //the nearer one of two equally strong enemies
+            .max_by(|(a, a_health), (b, b_health)| a_health.cmp(b_health).then(b.translation.distance(translation).total_cmp(&a.translation.distance(translation))))
+            .map(|(enemy, _)| enemy),
+        (TargetingMode::ClosestToBase, Some(base_translation)) => in_range
+            .min_by(|a, b| a.translation.distance(base_translation).total_cmp(&b.translation.distance(base_translation))),
+        _ => in_range
+            .min_by(|a, b| a.translation.distance(translation).total_cmp(&b.translation.distance(translation)))
+    };
+    target.map(|enemy| enemy.entity)
+}
 
-pub fn turret_timer_ticker(mut turret_timer: ResMut<TurretCooldownTimer>, time : Res<Time>){
-    turret_timer.timer.tick(time.delta());
 
+//Systems
+
+pub fn turret_cooldown_ticker(mut turret_query : Query<&mut Turret>, time : Res<Time>){
+    for mut turret in turret_query.iter_mut(){
+        turret.cooldown.tick(time.delta());
+    }
+}
+
+//drops targets that died or left the range and picks a new one
+pub fn turret_targeting(mut turret_query : Query<(&Transform, &mut Turret), Without<Enemy>>, enemy_grid : Res<EnemyGrid>, enemy_query : Query<(&Transform, &Health), With<Enemy>>, base_query : Query<&Transform, (With<Base>, Without<Turret>)>){
+    let base_translation = base_query.get_single().ok().map(|base_transform| base_transform.translation);
+    for (turret_transform, mut turret) in turret_query.iter_mut(){
+        let still_valid = turret.target
+            .and_then(|target| enemy_query.get(target).ok())
+            .is_some_and(|(enemy_transform, health)| !health.is_dead() && enemy_transform.translation.distance(turret_transform.translation) <= turret.stats.range);
+        if !still_valid{
+            turret.target = None;
+        }
+        if turret.target.is_none() || turret.targeting != TargetingMode::FirstInRange{
+            turret.target = pick_target(turret.targeting, turret_transform.translation, turret.stats.range, &enemy_grid, &enemy_query, base_translation);
+        }
+    }
 }
 
-pub fn turret_movement(mut commands: Commands, enemy_query: Query<(Entity, &Transform), (With<Enemy>, Without<Turret>)>, mut turret_query : Query<(&mut Transform, &mut Turret), (With<Turret>, Without<Enemy>)>, asset_server : Res<AssetServer>, turret_timer: Res<TurretCooldownTimer>, audio : Res<Audio>){
+pub fn turret_shooting(mut commands: Commands, mut turret_query : Query<(&mut Transform, &mut Turret), Without<Enemy>>, enemy_query : Query<&Transform, With<Enemy>>, asset_server : Res<AssetServer>, mut sound_effects : EventWriter<SoundEffect>, simulation_clock : Res<SimulationClock>){
     for (mut turret_transform, mut turret) in turret_query.iter_mut(){
-        let mut fired = false;
-        for (enemy_entity, enemy_transform) in enemy_query.iter(){
-            if turret_transform.translation.distance(enemy_transform.translation) < TURRET_RADAR{
-                if turret.target.is_none(){
-                    turret.target = Some(enemy_entity)
-                }
-                if turret.target.is_some() {
-                    if turret_timer.timer.just_finished() && !fired{
-                        commands.spawn((SpriteBundle{
-                            transform : Transform{
-                                translation : Vec3::new(turret_transform.translation.x, turret_transform.translation.y, 0.0),
-                                scale : Vec3::splat(0.2),
-                                ..default()
-                            },
-                            texture : asset_server.load("Sprites/spaceMissiles_027.png"),
+        let Some(enemy_transform) = turret.target.and_then(|target| enemy_query.get(target).ok()) else {
+            continue;
+        };
+        let to_enemy = (enemy_transform.translation - turret_transform.translation).truncate();
+        turret_transform.rotation = Quat::from_rotation_z(to_enemy.y.atan2(to_enemy.x) - PI/2.0);
+        if turret.cooldown.finished(){
+            let translation = Vec3::new(turret_transform.translation.x, turret_transform.translation.y, 0.0);
+            match turret.weapon {
+                TurretWeapon::Bullets => {
+                    commands.spawn((SpriteBundle{
+                        transform : Transform{
+                            translation,
+                            scale : Vec3::splat(0.2),
                             ..default()
                         },
-                        Bullet{speed : BULLET_SPEED, size : Vec2::new(10.0, 10.0), direction : Vec2::new(enemy_transform.translation.x - turret_transform.translation.x, enemy_transform.translation.y - turret_transform.translation.y).normalize(), instant : Instant::now(), damage : 50}
-                        
-                        ));
-                        let sound_effect = asset_server.load("Audio/impactGlass_heavy_001.ogg");
-                        audio.play(sound_effect);
-                        turret_transform.rotation = Quat::from_rotation_z((enemy_transform.translation.y - turret_transform.translation.y).atan2(enemy_transform.translation.x - turret_transform.translation.x) - PI/2.0);
-                        fired = true;
-                    }
-                
+                        texture : asset_server.load("Sprites/spaceMissiles_027.png"),
+                        ..default()
+                    },
+                    Bullet::new(&turret.projectile(), to_enemy.normalize_or_zero(), simulation_clock.now()),
+                    TurretShot
+                    ));
+                    sound_effects.send(SoundEffect("Audio/impactGlass_heavy_001.ogg"));
+                },
+                TurretWeapon::Missiles => {
+                    let direction = to_enemy.normalize_or_zero();
+                    commands.spawn((
+                        missile_bundle(&asset_server, translation, direction),
+                        TurretMissile::new(turret.target, direction, turret.stats.damage, simulation_clock.now())
+                    ));
+                    sound_effects.send(SoundEffect("Audio/laserSmall_000.ogg"));
                 }
             }
+            turret.cooldown.reset();
         }
-    
-   }
+    }
 }
 
 pub fn despawn_turret(mut commands: Commands, turret_query : Query<Entity, With<Turret>>){
     for i in turret_query.iter(){
         commands.entity(i).despawn()
     }
-}
\ No newline at end of file
+}
+
+pub fn despawn_turret_missiles(mut commands: Commands, missile_query : Query<Entity, With<TurretMissile>>){
+    for missile_entity in missile_query.iter(){
+        commands.entity(missile_entity).despawn();
+    }
+}
+
+//turns toward the target, picking the nearest enemy when it is gone, and flies on
+pub fn turret_missile_steering(mut missile_query : Query<(&mut Transform, &mut TurretMissile), Without<Enemy>>, enemy_grid : Res<EnemyGrid>, enemy_query : Query<(&Transform, &Health), With<Enemy>>, time : Res<Time>){
+    for (mut missile_transform, mut missile) in missile_query.iter_mut(){
+        let target_alive = missile.target
+            .and_then(|target| enemy_query.get(target).ok())
+            .is_some_and(|(_, health)| !health.is_dead());
+        if !target_alive{
+            missile.target = enemy_grid.nearest(missile_transform.translation, MISSILE_RETARGET_RADIUS).map(|enemy| enemy.entity);
+        }
+        if let Some((enemy_transform, _)) = missile.target.and_then(|target| enemy_query.get(target).ok()){
+            let to_target = (enemy_transform.translation - missile_transform.translation).truncate();
+            missile.direction = missile.steer(to_target, time.delta_seconds());
+        }
+        let direction = missile.direction;
+        missile_transform.translation += direction.extend(0.0) * missile.speed * time.delta_seconds();
+        missile_transform.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x) - PI/2.0);
+    }
+}
+
+//explodes on the first enemy it touches, and in place once it has nothing left to chase or runs out of fuel
+pub fn turret_missile_hit(mut commands: Commands, missile_query : Query<(Entity, &Transform, &TurretMissile)>, enemy_grid : Res<EnemyGrid>, mut damage_event_writer : EventWriter<DamageEvent>, mut status_event_writer : EventWriter<StatusEvent>, mut sound_effects : EventWriter<SoundEffect>, simulation_clock : Res<SimulationClock>){
+    for (missile_entity, missile_transform, missile) in missile_query.iter(){
+        let translation = missile_transform.translation;
+        let hit = enemy_grid.candidates(translation, Vec2::splat(MISSILE_SIZE / 2.0))
+            .any(|enemy| collide(enemy.translation, enemy.size, translation, Vec2::splat(MISSILE_SIZE)).is_some());
+        let spent = missile.target.is_none() || simulation_clock.elapsed_since(missile.spawn_time).as_secs_f32() > MISSILE_LIFETIME;
+        if !hit && !spent{
+            continue;
+        }
+        for enemy in enemy_grid.within(translation, missile.splash_radius){
+            damage_event_writer.send(DamageEvent{source : Some(missile_entity), target : enemy.entity, amount : missile.damage, damage_type : DamageType::Explosion});
+            status_event_writer.send(StatusEvent{target : enemy.entity, effect : MISSILE_BURN});
+        }
+        sound_effects.send(SoundEffect("Audio/explosionCrunch_002.ogg"));
+        commands.entity(missile_entity).despawn();
+    }
+}
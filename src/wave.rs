@@ -1,8 +1,8 @@
-use std::time::Instant;
+use std::time::Duration;
 use rand::prelude::*;
-use bevy::{ prelude::*, window::PrimaryWindow};
+use bevy::{prelude::*, ecs::system::SystemParam};
 
-use crate::{enemy::*, AppState, SimulationState};
+use crate::{enemy::*, boss::*, director::Director, base::Base, health::{apply_damage, Health}, rng::GameRng, wave_script::*, game_time::SimulationClock, AppState, SimulationState, Playfield, RunStats};
 
 pub struct WavePlugin;
 
@@ -10,15 +10,21 @@ impl Plugin for WavePlugin{
     fn build(&self, app: &mut App) {
         app
         .init_resource::<WaveTimer>()
+        .init_resource::<TrickleBudget>()
+        .init_resource::<WaveStats>()
+        .add_event::<WaveStarted>()
         .add_systems(
             (
                 wave_timer_ticker,
-                wave_spawner
+                wave_spawner,
+                trickle_spawner.after(enemy_spawn_timer_ticker),
+                track_wave_stats.after(wave_spawner).after(apply_damage)
             )
             .in_set(OnUpdate(AppState::Game))
             .in_set(OnUpdate(SimulationState::Running))
         )
     
+        .add_system(start_wave_timer.in_schedule(OnEnter(AppState::Game)))
         .add_system(reset_waves.in_schedule(OnExit(AppState::Game)));
     }
 }
@@ -26,6 +32,14 @@ impl Plugin for WavePlugin{
 
 
 pub const WAVE_COOLDOWN : f32 = 15.0;
+pub const ENDLESS_ENEMY_COUNT : i32 = 11;
+pub const MAX_VARIANTS : usize = 4;
+//budget the trickle spawner gets every time EnemySpawnCooldownTimer goes off, per wave survived
+pub const TRICKLE_BUDGET_PER_WAVE : f32 = 0.5;
+//unspent budget stops piling up here so a late wave can't save up for a swarm
+pub const MAX_TRICKLE_BUDGET : f32 = 12.0;
+pub const MIN_TRICKLE_INTERVAL : f32 = 1.0;
+pub const ALL_EDGES : [SpawnEdge; 4] = [SpawnEdge::Top, SpawnEdge::Bottom, SpawnEdge::Left, SpawnEdge::Right];
 
 
 #[derive(Resource)]
@@ -33,185 +47,220 @@ pub struct WaveTimer{
     pub timer: Timer,
     pub wave: usize,
     pub enemy_count : i32,
-    pub variants : Vec<EnemyType>
+    pub variants : Vec<EnemyKind>,
+    //every kind a wave has brought this run, for the banner to name the new ones
+    pub unlocked : Vec<EnemyKind>
+}
+
+//sent when a wave spawns, new_kinds are the enemies the run hasn't seen before
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveStarted{
+    pub wave : usize,
+    pub new_kinds : Vec<EnemyKind>,
+    pub boss : bool
+}
+
+//what happened since the current wave started, for the summary between the waves
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Default)]
+pub struct WaveStats{
+    pub wave : usize,
+    pub kills_at_start : u64,
+    pub parts_at_start : u64,
+    pub base_damage : i64
+}
+
+//points the trickle spawner has left to spend on stragglers between the waves
+#[derive(Resource, Default)]
+pub struct TrickleBudget{
+    pub points : f32
 }
 
 impl Default for WaveTimer{
     fn default() -> WaveTimer {
-        WaveTimer{timer: Timer::from_seconds(WAVE_COOLDOWN, TimerMode::Repeating), wave : 0, enemy_count : 5, variants : vec![EnemyType::Pawn]}
+        WaveTimer{timer: Timer::from_seconds(WAVE_COOLDOWN, TimerMode::Repeating), wave : 0, enemy_count : 5, variants : vec![EnemyKind::Pawn], unlocked : Vec::new()}
     }
     
 }
 
 
-pub fn calculate_probabilities(elements: usize) -> Vec<f64>{
+//triangular weights favouring the older variants at an intensity of 1.0,
+//flatter above it so the newer ones come up more, steeper below it
+pub fn calculate_probabilities(elements: usize, intensity : f32) -> Vec<f64>{
     let num_elements = elements.min(4);
-    let mut probs: Vec<f64> = Vec::new();
-    for i in 0..num_elements{
-        let prob = (num_elements - i) as f64 / (num_elements * (num_elements + 1) / 2) as f64;
-        probs.push(prob);
-    }
-    probs
+    let exponent = 1.0 / intensity.max(0.1) as f64;
+    let weights : Vec<f64> = (0..num_elements).map(|i| ((num_elements - i) as f64).powf(exponent)).collect();
+    let total : f64 = weights.iter().sum();
+    weights.iter().map(|weight| weight / total).collect()
 }
 
 
-pub fn wave_spawner(mut commands: Commands, asset_server : Res<AssetServer>, mut wave_timer : ResMut<WaveTimer>, window_query : Query<&Window, With<PrimaryWindow>>){
-    let window = window_query.get_single().unwrap();
-    if wave_timer.timer.finished(){
-        wave_timer.wave += 1;
-        match wave_timer.wave {
-            6 => {
-                wave_timer.enemy_count = 6;
-                wave_timer.variants.push(EnemyType::Stinger)
-            },
-            15 => {
-                wave_timer.enemy_count = 7;
-                wave_timer.variants.push(EnemyType::Rogue)
-            },
-            20 => {
-                wave_timer.enemy_count = 8;
-                wave_timer.variants.push(EnemyType::Splitter{split_count : 0, instant : Instant::now(), direction : Vec3::ZERO})
-            },
-            25 => {
-                wave_timer.enemy_count = 9;
-                wave_timer.variants.remove(0);
-                wave_timer.variants.push(EnemyType::Bishop)
-            },
-            30 => {
-                wave_timer.enemy_count = 10;
-                wave_timer.variants.remove(0);
-                wave_timer.variants.push(EnemyType::Propagator)
-            },
-            _ => {
-                wave_timer.enemy_count = 11
-            },
-        
+//everything the spawners need to put enemies on the field, the director picks the edges they come from
+#[derive(SystemParam)]
+pub struct EnemyWaveSpawner<'w, 's>{
+    commands : Commands<'w, 's>,
+    asset_server : Res<'w, AssetServer>,
+    director : Res<'w, Director>,
+    playfield : Res<'w, Playfield>,
+    game_rng : ResMut<'w, GameRng>,
+    simulation_clock : Res<'w, SimulationClock>
+}
+
+impl EnemyWaveSpawner<'_, '_>{
+    pub fn spawn_scripted_wave(&mut self, wave_timer : &mut WaveTimer, wave_definition : &WaveDefinition){
+        let EnemyWaveSpawner{commands, asset_server, director, playfield, game_rng, simulation_clock} = self;
+        let spawn_time = simulation_clock.now();
+        let rng = &mut game_rng.waves;
+        let edges = if wave_definition.edges.is_empty() {ALL_EDGES.to_vec()} else {wave_definition.edges.clone()};
+        wave_timer.enemy_count = 0;
+        for wave_enemy in wave_definition.enemies.iter(){
+            //the endless waves keep spawning the most recently unlocked enemies
+            if !wave_timer.variants.contains(&wave_enemy.kind){
+                wave_timer.variants.push(wave_enemy.kind);
+                if wave_timer.variants.len() > MAX_VARIANTS{
+                    wave_timer.variants.remove(0);
+                }
+            }
+            let count = director.scale_count(wave_enemy.count);
+            for _ in 0..count{
+                let edge = director.pick_edge(&edges, rng);
+                spawn_enemy(commands, asset_server, wave_enemy.kind, edge.random_point(playfield.width, playfield.height, rng), spawn_time);
+            }
+            wave_timer.enemy_count += count as i32;
+        }
+        if let Some(boss) = wave_definition.boss{
+            let edge = director.pick_edge(&edges, rng);
+            spawn_boss_enemy(commands, asset_server, boss.kind, edge.random_point(playfield.width, playfield.height, rng), spawn_time, boss.health, boss.size);
         }
+    }
+
+    pub fn spawn_endless_wave(&mut self, wave_timer : &mut WaveTimer){
+        let EnemyWaveSpawner{commands, asset_server, director, playfield, game_rng, simulation_clock} = self;
+        let spawn_time = simulation_clock.now();
+        let rng = &mut game_rng.waves;
+        wave_timer.enemy_count = director.scale_count(ENDLESS_ENEMY_COUNT as usize) as i32;
         for _ in 0..= wave_timer.enemy_count{
-            let rand_side = random::<usize>() % 4; // Randomly select one of the four sides
-            let (rand_x, rand_y) = match rand_side {
-                0 => (random::<f32>() * window.width(), -20.0), // Top edge
-                1 => (random::<f32>() * window.width(), window.height() + 20.0), // Bottom edge
-                2 => (-20.0, random::<f32>() * window.height()), // Left edge
-                _ => (window.width() + 20.0, random::<f32>() * window.height()), // Right edge
-            };
-            let rand_num : f64 = random();
-            let mut cum_prob : f64 = 0.0;
-            let probs = calculate_probabilities(wave_timer.variants.len());
-            let mut selected_variant = None;
-
-            for (i, &prob) in probs.iter().enumerate(){
-                cum_prob += prob;
-                if rand_num < cum_prob{
-                    selected_variant = Some(&wave_timer.variants[i]);
-                    break;
-                }
+            let edge = director.pick_edge(&ALL_EDGES, rng);
+            if let Some(kind) = pick_variant(&wave_timer.variants, director.intensity, rng){
+                spawn_enemy(commands, asset_server, kind, edge.random_point(playfield.width, playfield.height, rng), spawn_time);
             }
-            match selected_variant.unwrap(){
-              EnemyType::Pawn => {
-                commands.spawn((
-                    SpriteBundle{
-                        transform : Transform{
-                            translation: Vec3::new(rand_x, rand_y, 0.0),
-                            scale: Vec3::splat(0.15), // Decrease the size by half along all axes
-                            ..default()
-                        },
-                        texture : asset_server.load("Sprites/pawn.png"),
-                        ..default()
-                    },
-                    Enemy{health : 50, variant : EnemyType::Pawn, speed : 25.0, size : Vec2::new(10.0, 10.0)}
-                ));
-
-              },
-              EnemyType::Stinger => {
-                commands.spawn((
-                    SpriteBundle{
-                        transform : Transform{
-                            translation: Vec3::new(rand_x, rand_y, 0.0),
-                            scale: Vec3::splat(0.2), // Decrease the size by half along all axes
-                            ..default()
-                        },
-                        texture : asset_server.load("Sprites/stinger.png"),
-                        ..default()
-                    },
-                    Enemy{health : 50, variant : EnemyType::Stinger, speed : 40.0, size : Vec2::new(10.0, 10.0)}
-                ));
-
-              },
-              EnemyType::Splitter{split_count : _, instant : _, direction : _} => {
-                commands.spawn((
-                    SpriteBundle{
-                        transform : Transform{
-                            translation: Vec3::new(rand_x, rand_y, 0.0),
-                            scale: Vec3::splat(0.3), // Decrease the size by half along all axes
-                            ..default()
-                        },
-                        texture : asset_server.load("Sprites/splitter.png"),
-                        ..default()
-                    },
-                    Enemy{health : 100, variant : EnemyType::Splitter{split_count : 0, instant : Instant::now(), direction : Vec3::ZERO}, speed : 20.0, size : Vec2::new(15.0, 15.0)}
-                ));
-
-              },
-              EnemyType::Rogue => {
-                commands.spawn((
-                    SpriteBundle{
-                        transform : Transform{
-                            translation: Vec3::new(rand_x, rand_y, 0.0),
-                            scale: Vec3::splat(0.3), // Decrease the size by half along all axes
-                            ..default()
-                        },
-                        texture : asset_server.load("Sprites/rogue.png"),
-                        ..default()
-                    },
-                    Enemy{health : 200, variant : EnemyType::Rogue, speed : 25.0, size : Vec2::new(15.0, 15.0)}
-                ));
-
-              },
-              EnemyType::Bishop => {
-                commands.spawn((
-                    SpriteBundle{
-                        transform : Transform{
-                            translation: Vec3::new(rand_x, rand_y, 0.0),
-                            scale: Vec3::splat(0.4), // Decrease the size by half along all axes
-                            ..default()
-                        },
-                        texture : asset_server.load("Sprites/bishop.png"),
-                        ..default()
-                    },
-                    Enemy{health : 300, variant : EnemyType::Bishop, speed : 15.0, size : Vec2::new(20.0, 20.0)}
-                ));
-
-              },
-              EnemyType::Propagator => {
-                commands.spawn((
-                    SpriteBundle{
-                        transform : Transform{
-                            translation: Vec3::new(rand_x, rand_y, 0.0),
-                            scale: Vec3::splat(0.5), // Decrease the size by half along all axes
-                            ..default()
-                        },
-                        texture : asset_server.load("Sprites/propogator.png"),
-                        ..default()
-                    },
-                    Enemy{health : 500, variant : EnemyType::Propagator, speed : 5.0, size : Vec2::new(25.0, 25.0)}
-                ));
-
-              },
-              _ => return
+        }
+    }
+
+    pub fn spawn_wave_boss(&mut self, health : i64){
+        let edge = self.director.pick_edge(&ALL_EDGES, &mut self.game_rng.waves);
+        let translation = edge.random_point(self.playfield.width, self.playfield.height, &mut self.game_rng.waves);
+        spawn_boss(&mut self.commands, &self.asset_server, translation, self.simulation_clock.now(), health);
+    }
+
+    //stragglers come from any edge
+    pub fn spawn_trickle_enemy(&mut self, kind : EnemyKind){
+        let edge = self.director.pick_edge(&ALL_EDGES, &mut self.game_rng.waves);
+        let translation = edge.random_point(self.playfield.width, self.playfield.height, &mut self.game_rng.waves);
+        spawn_enemy(&mut self.commands, &self.asset_server, kind, translation, self.simulation_clock.now());
+    }
+}
 
+pub fn wave_spawner(mut spawner : EnemyWaveSpawner, mut wave_timer : ResMut<WaveTimer>, wave_script_handle : Res<WaveScriptHandle>, wave_scripts : Res<Assets<WaveScript>>, mut wave_started_writer : EventWriter<WaveStarted>){
+    if wave_timer.timer.finished(){
+        wave_timer.wave += 1;
+        let wave_script = wave_scripts.get(&wave_script_handle.handle);
+        let wave_definition = wave_script.and_then(|script| script.wave(wave_timer.wave));
+        //endless waves only bring back what the script already unlocked
+        let mut new_kinds = Vec::new();
+        for wave_enemy in wave_definition.iter().flat_map(|wave_definition| wave_definition.enemies.iter()){
+            if !wave_timer.unlocked.contains(&wave_enemy.kind){
+                wave_timer.unlocked.push(wave_enemy.kind);
+                new_kinds.push(wave_enemy.kind);
             }
         }
+        let boss = is_boss_wave(wave_timer.wave) || wave_definition.is_some_and(|wave_definition| wave_definition.boss.is_some());
+        wave_started_writer.send(WaveStarted{wave : wave_timer.wave, new_kinds, boss});
+        match wave_definition{
+            Some(wave_definition) => spawner.spawn_scripted_wave(&mut wave_timer, wave_definition),
+            None => spawner.spawn_endless_wave(&mut wave_timer)
+        }
+        if is_boss_wave(wave_timer.wave){
+            spawner.spawn_wave_boss(boss_health(wave_timer.wave));
+            wave_timer.enemy_count += 1;
+        }
+        let next_delay = wave_script.and_then(|script| script.wave(wave_timer.wave + 1)).map_or(WAVE_COOLDOWN, |wave_definition| wave_definition.delay);
+        wave_timer.timer.set_duration(Duration::from_secs_f32(next_delay));
     }
     
 
 }
 
+//the older variants come up more often unless the intensity is up, see calculate_probabilities
+pub fn pick_variant(variants : &[EnemyKind], intensity : f32, rng : &mut impl Rng) -> Option<EnemyKind>{
+    let rand_num : f64 = rng.gen();
+    let mut cum_prob : f64 = 0.0;
+    for (i, &prob) in calculate_probabilities(variants.len(), intensity).iter().enumerate(){
+        cum_prob += prob;
+        if rand_num < cum_prob{
+            return Some(variants[i]);
+        }
+    }
+    None
+}
+
+//stragglers come faster the further the run gets, down to one every MIN_TRICKLE_INTERVAL seconds
+pub fn trickle_interval(wave : usize) -> f32{
+    (ENEMY_SPAWN_COOLDOWN - 0.1 * wave as f32).max(MIN_TRICKLE_INTERVAL)
+}
+
+pub fn trickle_budget(wave : usize) -> f32{
+    wave as f32 * TRICKLE_BUDGET_PER_WAVE
+}
+
+//spends the budget on the variants the waves have unlocked, saving up when the pick is too expensive
+pub fn trickle_spawner(mut spawner : EnemyWaveSpawner, mut enemy_spawn_timer : ResMut<EnemySpawnCooldownTimer>, mut trickle_budget_points : ResMut<TrickleBudget>, wave_timer : Res<WaveTimer>){
+    if !enemy_spawn_timer.timer.just_finished() || wave_timer.wave == 0{
+        return;
+    }
+    enemy_spawn_timer.timer.set_duration(Duration::from_secs_f32(trickle_interval(wave_timer.wave)));
+    trickle_budget_points.points = (trickle_budget_points.points + trickle_budget(wave_timer.wave) * spawner.director.enemy_multiplier()).min(MAX_TRICKLE_BUDGET);
+    while let Some(kind) = pick_variant(&wave_timer.variants, spawner.director.intensity, &mut spawner.game_rng.waves){
+        if kind.cost() > trickle_budget_points.points{
+            break;
+        }
+        trickle_budget_points.points -= kind.cost();
+        spawner.spawn_trickle_enemy(kind);
+    }
+}
+
+//starts over with every wave, the base damage is the health the base actually lost since the last frame,
+//shields and overkill don't count and neither do heals or level ups
+pub fn track_wave_stats(mut wave_stats : ResMut<WaveStats>, mut wave_started_reader : EventReader<WaveStarted>, base_query : Query<(Entity, &Health), With<Base>>, run_stats : Res<RunStats>, mut last_base_health : Local<Option<(Entity, i64)>>){
+    for wave_started in wave_started_reader.iter(){
+        *wave_stats = WaveStats{wave : wave_started.wave, kills_at_start : run_stats.kills, parts_at_start : run_stats.parts, base_damage : 0};
+    }
+    let Ok((base_entity, base_health)) = base_query.get_single() else {
+        return;
+    };
+    let current = base_health.current.max(0);
+    //a base spawned or restored since the last frame hasn't lost anything yet
+    if let Some((last_entity, last)) = *last_base_health{
+        if last_entity == base_entity{
+            wave_stats.base_damage += (last - current).max(0);
+        }
+    }
+    *last_base_health = Some((base_entity, current));
+}
+
+pub fn start_wave_timer(mut wave_timer : ResMut<WaveTimer>, wave_script_handle : Res<WaveScriptHandle>, wave_scripts : Res<Assets<WaveScript>>){
+    if let Some(wave_definition) = wave_scripts.get(&wave_script_handle.handle).and_then(|script| script.wave(1)){
+        wave_timer.timer.set_duration(Duration::from_secs_f32(wave_definition.delay));
+    }
+}
+
 pub fn wave_timer_ticker(mut wave_timer: ResMut<WaveTimer>, time : Res<Time>){
     wave_timer.timer.tick(time.delta());
     
 }
 
-pub fn reset_waves(mut wave_timer : ResMut<WaveTimer>){
+pub fn reset_waves(mut wave_timer : ResMut<WaveTimer>, mut trickle_budget : ResMut<TrickleBudget>, mut wave_stats : ResMut<WaveStats>, mut enemy_spawn_timer : ResMut<EnemySpawnCooldownTimer>){
     *wave_timer = WaveTimer::default();
+    *trickle_budget = TrickleBudget::default();
+    *wave_stats = WaveStats::default();
+    *enemy_spawn_timer = EnemySpawnCooldownTimer::default();
 }
\ No newline at end of file
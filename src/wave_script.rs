@@ -0,0 +1,160 @@
+use rand::prelude::*;
+use serde::Deserialize;
+use bevy::{prelude::*, reflect::TypeUuid, asset::{AssetLoader, LoadContext, LoadedAsset}, utils::BoxedFuture};
+
+use crate::enemy::EnemyKind;
+use crate::wave::WAVE_COOLDOWN;
+
+pub struct WaveScriptPlugin;
+
+impl Plugin for WaveScriptPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .add_asset::<WaveScript>()
+        .init_asset_loader::<WaveScriptLoader>()
+
+        //Startup
+        .add_startup_system(load_wave_script);
+    }
+}
+
+
+
+pub const WAVE_SCRIPT_PATH : &str = "Data/default.waves.ron";
+
+
+//Asset
+
+#[derive(Debug, Clone, Deserialize, TypeUuid)]
+#[uuid = "73c0ab34-af51-47c2-9464-a13168fba9bc"]
+pub struct WaveScript{
+    pub waves : Vec<WaveDefinition>
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveDefinition{
+    pub enemies : Vec<WaveEnemy>,
+    #[serde(default = "all_edges")]
+    pub edges : Vec<SpawnEdge>,
+    //seconds between the previous wave and this one
+    #[serde(default = "default_delay")]
+    pub delay : f32,
+    #[serde(default)]
+    pub boss : Option<WaveBoss>,
+    //how many waves in a row use this definition
+    #[serde(default = "default_repeat")]
+    pub repeat : usize
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct WaveEnemy{
+    pub kind : EnemyKind,
+    pub count : usize
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct WaveBoss{
+    pub kind : EnemyKind,
+    #[serde(default = "default_multiplier")]
+    pub health : f32,
+    #[serde(default = "default_multiplier")]
+    pub size : f32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum SpawnEdge{
+    Top,
+    Bottom,
+    Left,
+    Right
+}
+
+fn all_edges() -> Vec<SpawnEdge>{
+    vec![SpawnEdge::Top, SpawnEdge::Bottom, SpawnEdge::Left, SpawnEdge::Right]
+}
+
+fn default_delay() -> f32{
+    WAVE_COOLDOWN
+}
+
+fn default_repeat() -> usize{
+    1
+}
+
+fn default_multiplier() -> f32{
+    1.0
+}
+
+impl WaveScript{
+    //waves are counted from 1, like WaveTimer.wave
+    pub fn wave(&self, wave : usize) -> Option<&WaveDefinition>{
+        let mut first_wave = 1;
+        for definition in self.waves.iter(){
+            let last_wave = first_wave + definition.repeat.max(1);
+            if wave >= first_wave && wave < last_wave{
+                return Some(definition);
+            }
+            first_wave = last_wave;
+        }
+        None
+    }
+
+    //the delays become timer durations, which can't be negative or NaN
+    pub fn validate(&self) -> Result<(), String>{
+        for (index, definition) in self.waves.iter().enumerate(){
+            if !definition.delay.is_finite() || definition.delay < 0.0{
+                return Err(format!("wave definition {} has an invalid delay of {}", index + 1, definition.delay));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SpawnEdge{
+    //a point just outside the playfield on this edge
+    pub fn random_point(&self, width : f32, height : f32, rng : &mut impl Rng) -> Vec3{
+        match self {
+            SpawnEdge::Top => Vec3::new(rng.gen::<f32>() * width, height + 20.0, 0.0),
+            SpawnEdge::Bottom => Vec3::new(rng.gen::<f32>() * width, -20.0, 0.0),
+            SpawnEdge::Left => Vec3::new(-20.0, rng.gen::<f32>() * height, 0.0),
+            SpawnEdge::Right => Vec3::new(width + 20.0, rng.gen::<f32>() * height, 0.0),
+        }
+    }
+}
+
+
+//Loader
+
+#[derive(Default)]
+pub struct WaveScriptLoader;
+
+impl AssetLoader for WaveScriptLoader{
+    fn load<'a>(&'a self, bytes: &'a [u8], load_context: &'a mut LoadContext) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let wave_script = ron::de::from_bytes::<WaveScript>(bytes)?;
+            //a broken hot reload keeps the waves that were loaded before
+            wave_script.validate().map_err(bevy::asset::Error::msg)?;
+            load_context.set_default_asset(LoadedAsset::new(wave_script));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["waves.ron"]
+    }
+}
+
+
+//Resources
+
+#[derive(Resource)]
+pub struct WaveScriptHandle{
+    pub handle : Handle<WaveScript>
+}
+
+
+//Systems
+
+pub fn load_wave_script(mut commands: Commands, asset_server : Res<AssetServer>){
+    commands.insert_resource(WaveScriptHandle{handle : asset_server.load(WAVE_SCRIPT_PATH)});
+}
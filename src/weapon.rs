@@ -0,0 +1,394 @@
+use std::f32::consts::PI;
+use serde::{Deserialize, Serialize};
+use bevy::{prelude::*, sprite::collide_aabb::collide, ecs::system::SystemParam};
+
+use crate::base::Base;
+use crate::bullet::*;
+use crate::player::*;
+use crate::health::*;
+use crate::controls::{ActionState, Action};
+use crate::spatial::EnemyGrid;
+use crate::game_time::SimulationClock;
+use crate::status::*;
+use super::{AppState, SimulationState, SoundEffect};
+
+//The player's weapons, player_shoot fires whichever one is selected.
+//Weapons unlock with the base level and every one keeps its own cooldown.
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin{
+    fn build(&self, app: &mut App) {
+        app
+        .add_systems(
+            (
+                weapon_cooldown_ticker.before(player_shoot),
+                switch_weapon.after(PlayerInputSet).before(player_shoot),
+                //hits in DamageSet like every other projectile
+                bomb_movement.before(DamageSet),
+                bomb_hit.in_set(DamageSet),
+                laser_hit.in_set(DamageSet),
+                fade_laser_beams
+            )
+            .in_set(OnUpdate(AppState::Game))
+            .in_set(OnUpdate(SimulationState::Running))
+        )
+
+        .add_system(despawn_weapon_shots.in_schedule(OnExit(AppState::Game)));
+    }
+}
+
+
+
+//seconds the charge shot has to be held to reach full power
+pub const CHARGE_TIME : f32 = 1.5;
+pub const CHARGE_DAMAGE_MULTIPLIER : f32 = 4.0;
+pub const CHARGE_SIZE_MULTIPLIER : f32 = 2.0;
+pub const LASER_RANGE : f32 = 500.0;
+pub const LASER_WIDTH : f32 = 6.0;
+//how long the beam stays on screen, it only hits once
+pub const LASER_FLASH : f32 = 0.1;
+pub const BOMB_SIZE : f32 = 14.0;
+pub const BOMB_SPLASH_RADIUS : f32 = 80.0;
+pub const CHARGE_SHOT_STUN : StatusEffect = StatusEffect::new(StatusKind::Stun, 0.0, 0.6);
+pub const LASER_SLOW : StatusEffect = StatusEffect::new(StatusKind::Slow, 0.4, 1.5);
+pub const BOMB_BURN : StatusEffect = StatusEffect::new(StatusKind::Burn, 15.0, 3.0);
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeaponKind{
+    Blaster,
+    SpreadShot,
+    RapidFire,
+    ChargeShot,
+    //hits everything along the beam at once
+    Laser,
+    //flies to where the player aimed and explodes there
+    Bomb
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeaponStats{
+    //seconds between shots
+    pub cooldown : f32,
+    //fired at once, fanned out by spread radians
+    pub projectiles : usize,
+    pub spread : f32,
+    //before the base level bonus and upgrades
+    pub projectile : ProjectileStats
+}
+
+#[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Weapon{
+    pub selected : WeaponKind,
+    //seconds until each weapon can fire again, by WeaponKind::index
+    pub cooldowns : [f32; 6],
+    //seconds the charge shot has been held
+    pub charge : f32
+}
+
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct LaserBeam{
+    pub start : Vec3,
+    pub end : Vec3,
+    pub damage : i64,
+    pub fired : bool,
+    pub spawn_time : f32,
+    pub status : Option<StatusEffect>
+}
+
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct PlayerBomb{
+    pub destination : Vec3,
+    pub speed : f32,
+    pub damage : i64,
+    pub splash_radius : f32,
+    pub status : Option<StatusEffect>
+}
+
+
+impl WeaponKind{
+    pub const ALL : [WeaponKind; 6] = [WeaponKind::Blaster, WeaponKind::SpreadShot, WeaponKind::RapidFire, WeaponKind::ChargeShot, WeaponKind::Laser, WeaponKind::Bomb];
+
+    pub fn index(&self) -> usize{
+        WeaponKind::ALL.iter().position(|kind| kind == self).unwrap_or(0)
+    }
+
+    pub fn label(&self) -> &'static str{
+        match self {
+            WeaponKind::Blaster => "Blaster",
+            WeaponKind::SpreadShot => "Spread shot",
+            WeaponKind::RapidFire => "Rapid fire",
+            WeaponKind::ChargeShot => "Charge shot",
+            WeaponKind::Laser => "Laser",
+            WeaponKind::Bomb => "Bomb"
+        }
+    }
+
+    pub fn unlock_level(&self) -> i64{
+        match self {
+            WeaponKind::Blaster => 1,
+            WeaponKind::SpreadShot => 2,
+            WeaponKind::RapidFire => 3,
+            WeaponKind::ChargeShot => 4,
+            WeaponKind::Laser => 5,
+            WeaponKind::Bomb => 6
+        }
+    }
+
+    pub fn unlocked(&self, base_level : i64) -> bool{
+        base_level >= self.unlock_level()
+    }
+
+    //only the blaster fires faster with the base level, the projectiles of every weapon get the base level bonus when fired
+    pub fn stats(&self, base_level : i64) -> WeaponStats{
+        let blaster = WeaponStats{
+            cooldown : (0.5 - 0.1 * (base_level - 1) as f32).max(0.1),
+            projectiles : 1,
+            spread : 0.0,
+            projectile : ProjectileStats{damage : 50, speed : 300.0, size : 10.0, pierce : 0, bounces : 0, knockback : 0.0, status : None}
+        };
+        let projectile = blaster.projectile;
+        match self {
+            WeaponKind::Blaster => blaster,
+            WeaponKind::SpreadShot => WeaponStats{cooldown : 0.6, projectiles : 5, spread : 0.2, projectile : ProjectileStats{damage : 30, bounces : 1, ..projectile}},
+            WeaponKind::RapidFire => WeaponStats{cooldown : 0.08, projectile : ProjectileStats{damage : 18, speed : 360.0, size : 8.0, ..projectile}, ..blaster},
+            WeaponKind::ChargeShot => WeaponStats{cooldown : 0.8, projectile : ProjectileStats{speed : 240.0, size : 16.0, pierce : 2, knockback : 30.0, status : Some(CHARGE_SHOT_STUN), ..projectile}, ..blaster},
+            WeaponKind::Laser => WeaponStats{cooldown : 0.7, projectile : ProjectileStats{damage : 75, speed : 0.0, size : LASER_WIDTH, status : Some(LASER_SLOW), ..projectile}, ..blaster},
+            WeaponKind::Bomb => WeaponStats{cooldown : 1.5, projectile : ProjectileStats{damage : 100, speed : 250.0, size : BOMB_SIZE, status : Some(BOMB_BURN), ..projectile}, ..blaster}
+        }
+    }
+
+    pub fn texture(&self) -> &'static str{
+        match self {
+            WeaponKind::Bomb => "Sprites/spaceMissiles_037.png",
+            //the bullet weapons are told apart by their size
+            _ => "Sprites/spaceMissiles_027.png"
+        }
+    }
+
+    pub fn sound(&self) -> &'static str{
+        match self {
+            WeaponKind::Blaster | WeaponKind::SpreadShot | WeaponKind::RapidFire => "Audio/laserSmall_000.ogg",
+            WeaponKind::ChargeShot | WeaponKind::Laser | WeaponKind::Bomb => "Audio/thrusterFire_000.ogg"
+        }
+    }
+}
+
+impl Default for Weapon{
+    fn default() -> Weapon {
+        Weapon{selected : WeaponKind::Blaster, cooldowns : [0.0; 6], charge : 0.0}
+    }
+}
+
+impl Weapon{
+    pub fn ready(&self, kind : WeaponKind) -> bool{
+        self.cooldowns[kind.index()] <= 0.0
+    }
+
+    //the next unlocked weapon in either direction, wrapping around
+    pub fn cycle(&self, step : isize, base_level : i64) -> WeaponKind{
+        let count = WeaponKind::ALL.len() as isize;
+        (1..=count)
+            .map(|offset| WeaponKind::ALL[(self.selected.index() as isize + step * offset).rem_euclid(count) as usize])
+            .find(|kind| kind.unlocked(base_level))
+            .unwrap_or(self.selected)
+    }
+
+    pub fn select(&mut self, kind : WeaponKind){
+        if kind != self.selected{
+            self.selected = kind;
+            self.charge = 0.0;
+        }
+    }
+}
+
+//what the player's shots with this weapon are fired with
+pub fn player_projectile(kind : WeaponKind, base_level : i64, upgrades : &ProjectileUpgrades) -> ProjectileStats{
+    kind.stats(base_level).projectile.with(std::iter::once(&ProjectileModifier::base_level(base_level)).chain(upgrades.modifiers.iter()))
+}
+
+fn rotation_towards(direction : Vec2) -> Quat{
+    Quat::from_rotation_z(direction.y.atan2(direction.x) - PI/2.0)
+}
+
+//spawns the shots of whichever weapon is fired
+#[derive(SystemParam)]
+pub struct WeaponFirer<'w, 's>{
+    commands : Commands<'w, 's>,
+    asset_server : Res<'w, AssetServer>,
+    simulation_clock : Res<'w, SimulationClock>
+}
+
+impl WeaponFirer<'_, '_>{
+    //charge is how far the charge shot was charged, from 0 to 1, and doesn't matter for the other weapons
+    pub fn fire(&mut self, kind : WeaponKind, stats : &WeaponStats, translation : Vec3, aim : Vec2, charge : f32){
+        let now = self.simulation_clock.now();
+        let commands = &mut self.commands;
+        let asset_server = &self.asset_server;
+        let projectile = &stats.projectile;
+        let direction = aim.normalize();
+        match kind {
+            WeaponKind::Laser => {
+                let end = translation + (direction * LASER_RANGE).extend(0.0);
+                commands.spawn((
+                    SpriteBundle{
+                        transform : Transform{
+                            translation : (translation + end) / 2.0,
+                            rotation : rotation_towards(direction),
+                            ..default()
+                        },
+                        sprite : Sprite{color : Color::rgba(0.5, 1.0, 1.0, 0.8), custom_size : Some(Vec2::new(LASER_WIDTH, LASER_RANGE)), ..default()},
+                        ..default()
+                    },
+                    LaserBeam{start : translation, end, damage : projectile.damage, fired : false, spawn_time : now, status : projectile.status}
+                ));
+            },
+            WeaponKind::Bomb => {
+                commands.spawn((
+                    SpriteBundle{
+                        transform : Transform{
+                            translation,
+                            rotation : rotation_towards(direction),
+                            scale : Vec3::splat(0.3),
+                        },
+                        texture : asset_server.load(kind.texture()),
+                        ..default()
+                    },
+                    PlayerBomb{destination : translation + aim.extend(0.0), speed : projectile.speed, damage : projectile.damage, splash_radius : BOMB_SPLASH_RADIUS, status : projectile.status}
+                ));
+            },
+            _ => {
+                let projectile = ProjectileStats{
+                    damage : (projectile.damage as f32 * (1.0 + (CHARGE_DAMAGE_MULTIPLIER - 1.0) * charge)).round() as i64,
+                    size : projectile.size * (1.0 + (CHARGE_SIZE_MULTIPLIER - 1.0) * charge),
+                    ..*projectile
+                };
+                for i in 0..stats.projectiles{
+                    //centered on the aim
+                    let angle = (i as f32 - (stats.projectiles - 1) as f32 / 2.0) * stats.spread;
+                    let direction = Vec2::from_angle(angle).rotate(direction);
+                    commands.spawn((
+                        SpriteBundle{
+                            transform : Transform{
+                                translation : Vec3::new(translation.x, translation.y, 0.0),
+                                rotation : rotation_towards(direction),
+                                scale : Vec3::splat(0.2 * projectile.size / 10.0)
+                            },
+                            texture : asset_server.load(kind.texture()),
+                            ..default()
+                        },
+                        Bullet::new(&projectile, direction, now)
+                    ));
+                }
+            }
+        }
+    }
+}
+
+//shortest distance from point to the segment between start and end
+pub fn distance_to_segment(point : Vec2, start : Vec2, end : Vec2) -> f32{
+    let segment = end - start;
+    let along = if segment.length_squared() > 0.0 {((point - start).dot(segment) / segment.length_squared()).clamp(0.0, 1.0)} else {0.0};
+    point.distance(start + segment * along)
+}
+
+
+//Systems
+
+pub fn weapon_cooldown_ticker(mut weapon_query : Query<&mut Weapon>, time : Res<Time>){
+    for mut weapon in weapon_query.iter_mut(){
+        for cooldown in weapon.cooldowns.iter_mut(){
+            *cooldown = (*cooldown - time.delta_seconds()).max(0.0);
+        }
+    }
+}
+
+//number keys pick a weapon, next and previous or the mouse wheel cycle through the unlocked ones
+pub fn switch_weapon(action_state : Res<ActionState>, mut weapon_query : Query<&mut Weapon, With<Player>>, base_query : Query<&Base>){
+    let Ok(mut weapon) = weapon_query.get_single_mut() else {
+        return;
+    };
+    let base_level = base_query.get_single().map_or(1, |base| base.level);
+    let picked = Action::WEAPONS.iter().zip(WeaponKind::ALL)
+        .find(|(action, kind)| action_state.just_pressed(**action) && kind.unlocked(base_level))
+        .map(|(_, kind)| kind);
+    let step = if action_state.just_pressed(Action::NextWeapon) || action_state.scroll > 0.0 {1}
+        else if action_state.just_pressed(Action::PreviousWeapon) || action_state.scroll < 0.0 {-1}
+        else {0};
+    if let Some(kind) = picked{
+        weapon.select(kind);
+    }
+    else if step != 0{
+        let kind = weapon.cycle(step, base_level);
+        weapon.select(kind);
+    }
+}
+
+pub fn laser_hit(mut laser_query : Query<(Entity, &mut LaserBeam)>, enemy_grid : Res<EnemyGrid>, mut damage_event_writer : EventWriter<DamageEvent>, mut status_event_writer : EventWriter<StatusEvent>){
+    for (laser_entity, mut laser) in laser_query.iter_mut(){
+        if laser.fired{
+            continue;
+        }
+        laser.fired = true;
+        let (start, end) = (laser.start.truncate(), laser.end.truncate());
+        let half_extent = (end - start).abs() / 2.0 + Vec2::splat(LASER_WIDTH / 2.0);
+        for enemy in enemy_grid.candidates((laser.start + laser.end) / 2.0, half_extent){
+            if distance_to_segment(enemy.translation.truncate(), start, end) <= enemy.size.max_element() / 2.0 + LASER_WIDTH / 2.0{
+                damage_event_writer.send(DamageEvent{source : Some(laser_entity), target : enemy.entity, amount : laser.damage, damage_type : DamageType::Bullet});
+                if let Some(effect) = laser.status{
+                    status_event_writer.send(StatusEvent{target : enemy.entity, effect});
+                }
+            }
+        }
+    }
+}
+
+pub fn fade_laser_beams(mut commands : Commands, mut laser_query : Query<(Entity, &LaserBeam, &mut Sprite)>, simulation_clock : Res<SimulationClock>){
+    for (laser_entity, laser, mut sprite) in laser_query.iter_mut(){
+        let age = simulation_clock.elapsed_since(laser.spawn_time).as_secs_f32();
+        if age > LASER_FLASH{
+            commands.entity(laser_entity).despawn();
+        }
+        else{
+            sprite.color.set_a(0.8 * (1.0 - age / LASER_FLASH));
+        }
+    }
+}
+
+//straight to the aimed spot without overshooting it
+pub fn bomb_movement(mut bomb_query : Query<(&mut Transform, &PlayerBomb)>, time : Res<Time>){
+    for (mut bomb_transform, bomb) in bomb_query.iter_mut(){
+        let to_destination = bomb.destination - bomb_transform.translation;
+        let step = bomb.speed * time.delta_seconds();
+        bomb_transform.translation = if to_destination.length() <= step {bomb.destination} else {bomb_transform.translation + to_destination.normalize() * step};
+        bomb_transform.rotation *= Quat::from_rotation_z(PI * time.delta_seconds());
+    }
+}
+
+//explodes where it was aimed, or early on the first enemy in the way
+pub fn bomb_hit(mut commands: Commands, bomb_query : Query<(Entity, &Transform, &PlayerBomb)>, enemy_grid : Res<EnemyGrid>, mut damage_event_writer : EventWriter<DamageEvent>, mut status_event_writer : EventWriter<StatusEvent>, mut sound_effects : EventWriter<SoundEffect>){
+    for (bomb_entity, bomb_transform, bomb) in bomb_query.iter(){
+        let translation = bomb_transform.translation;
+        let hit = enemy_grid.candidates(translation, Vec2::splat(BOMB_SIZE / 2.0))
+            .any(|enemy| collide(enemy.translation, enemy.size, translation, Vec2::splat(BOMB_SIZE)).is_some());
+        if !hit && translation != bomb.destination{
+            continue;
+        }
+        for enemy in enemy_grid.within(translation, bomb.splash_radius){
+            damage_event_writer.send(DamageEvent{source : Some(bomb_entity), target : enemy.entity, amount : bomb.damage, damage_type : DamageType::Explosion});
+            if let Some(effect) = bomb.status{
+                status_event_writer.send(StatusEvent{target : enemy.entity, effect});
+            }
+        }
+        sound_effects.send(SoundEffect("Audio/lowFrequency_explosion_001.ogg"));
+        commands.entity(bomb_entity).despawn();
+    }
+}
+
+type WeaponShotFilter = Or<(With<LaserBeam>, With<PlayerBomb>)>;
+
+pub fn despawn_weapon_shots(mut commands : Commands, shot_query : Query<Entity, WeaponShotFilter>){
+    for shot_entity in shot_query.iter(){
+        commands.entity(shot_entity).despawn();
+    }
+}